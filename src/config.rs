@@ -0,0 +1,710 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::{
+    default_note_extensions,
+    CmarkSerializer,
+    DataFormat,
+    DestinationMapper,
+    DryRunCallback,
+    DuplicateEmbedBehavior,
+    ExcludedEmbedBehavior,
+    Exporter,
+    FalliblePostprocessor,
+    FilenameSlugifier,
+    Finalizer,
+    FrontmatterFilter,
+    FrontmatterStrategy,
+    InternalLinkFormat,
+    LineEnding,
+    LinkResolutionMode,
+    LinkedVault,
+    ListMerge,
+    MarkdownSerializer,
+    OutputFilter,
+    Postprocessor,
+    PostprocessorFn,
+    ProgressCallback,
+    UnresolvedLinkStyle,
+    WalkOptions,
+    YamlOptions,
+    DEFAULT_DOCS_BASE_PATH,
+    DEFAULT_EMBED_RECURSION_LIMIT,
+};
+
+#[derive(Clone)]
+/// A reusable set of export settings.
+///
+/// `ExportConfig` holds the same settings as [`Exporter`], but isn't tied to a vault or
+/// destination. This allows a single configuration to be applied to several exports, by calling
+/// [`ExportConfig::build`] once for each pair of source and destination.
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
+pub struct ExportConfig<'a> {
+    pub(crate) frontmatter_strategy: FrontmatterStrategy,
+    pub(crate) frontmatter_yaml_options: YamlOptions,
+    pub(crate) walk_options: WalkOptions<'a>,
+    pub(crate) ignore_filename: Option<String>,
+    pub(crate) ignore_files: Vec<PathBuf>,
+    pub(crate) process_embeds_recursively: bool,
+    pub(crate) embed_recursion_limit: usize,
+    pub(crate) max_embed_bytes: Option<usize>,
+    pub(crate) excluded_embed_behavior: ExcludedEmbedBehavior,
+    pub(crate) duplicate_embed_behavior: DuplicateEmbedBehavior,
+    pub(crate) fail_on_unresolved_links: bool,
+    pub(crate) unresolved_link_style: UnresolvedLinkStyle,
+    pub(crate) preserve_mtime: bool,
+    pub(crate) preserve_permissions: bool,
+    pub(crate) git_dates: bool,
+    pub(crate) modified_since: Option<SystemTime>,
+    pub(crate) dry_run: bool,
+    pub(crate) dry_run_callback: Option<&'a DryRunCallback<'a>>,
+    pub(crate) progress_callback: Option<&'a ProgressCallback<'a>>,
+    pub(crate) filename_slugifier: Option<&'a FilenameSlugifier<'a>>,
+    pub(crate) link_resolution_mode: LinkResolutionMode,
+    pub(crate) internal_link_format: InternalLinkFormat,
+    pub(crate) docs_base_path: String,
+    pub(crate) output_extension: Option<String>,
+    pub(crate) note_extensions: Vec<String>,
+    pub(crate) respect_export_frontmatter: bool,
+    pub(crate) resolve_frontmatter_links: bool,
+    pub(crate) resolve_relative_links: bool,
+    pub(crate) image_alt_from_filename: bool,
+    pub(crate) daily_note_folder: Option<PathBuf>,
+    pub(crate) frontmatter_defaults_file: Option<String>,
+    pub(crate) frontmatter_list_merge: HashMap<String, ListMerge>,
+    pub(crate) frontmatter_only_as_data: Option<DataFormat>,
+    pub(crate) permalink_key: Option<String>,
+    pub(crate) destination_mapper: Option<&'a DestinationMapper<'a>>,
+    pub(crate) sitemap_base_url: Option<String>,
+    pub(crate) passthrough_patterns: Vec<String>,
+    pub(crate) serializer: Arc<dyn MarkdownSerializer>,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) assets_dir: Option<PathBuf>,
+    pub(crate) index_filename: Option<String>,
+    pub(crate) linked_vaults: Vec<LinkedVault>,
+    pub(crate) frontmatter_allowlist: Vec<String>,
+    pub(crate) frontmatter_denylist: Vec<String>,
+    pub(crate) frontmatter_filters: Vec<&'a FrontmatterFilter<'a>>,
+    pub(crate) frontmatter_query: Option<&'a FrontmatterFilter<'a>>,
+    pub(crate) postprocessors: Vec<PostprocessorFn<'a>>,
+    pub(crate) embed_postprocessors: Vec<&'a Postprocessor<'a>>,
+    pub(crate) output_filters: Vec<&'a OutputFilter<'a>>,
+    pub(crate) finalizers: Vec<&'a Finalizer<'a>>,
+}
+
+impl fmt::Debug for ExportConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExportConfig")
+            .field("frontmatter_strategy", &self.frontmatter_strategy)
+            .field("frontmatter_yaml_options", &self.frontmatter_yaml_options)
+            .field("walk_options", &self.walk_options)
+            .field("ignore_filename", &self.ignore_filename)
+            .field("ignore_files", &self.ignore_files)
+            .field(
+                "process_embeds_recursively",
+                &self.process_embeds_recursively,
+            )
+            .field("embed_recursion_limit", &self.embed_recursion_limit)
+            .field("max_embed_bytes", &self.max_embed_bytes)
+            .field("excluded_embed_behavior", &self.excluded_embed_behavior)
+            .field("duplicate_embed_behavior", &self.duplicate_embed_behavior)
+            .field("fail_on_unresolved_links", &self.fail_on_unresolved_links)
+            .field("unresolved_link_style", &self.unresolved_link_style)
+            .field("preserve_mtime", &self.preserve_mtime)
+            .field("preserve_permissions", &self.preserve_permissions)
+            .field("git_dates", &self.git_dates)
+            .field("modified_since", &self.modified_since)
+            .field("dry_run", &self.dry_run)
+            .field(
+                "dry_run_callback",
+                &self.dry_run_callback.map(|_| "<function set>"),
+            )
+            .field(
+                "progress_callback",
+                &self.progress_callback.map(|_| "<function set>"),
+            )
+            .field(
+                "filename_slugifier",
+                &self.filename_slugifier.map(|_| "<function set>"),
+            )
+            .field("link_resolution_mode", &self.link_resolution_mode)
+            .field("internal_link_format", &self.internal_link_format)
+            .field("docs_base_path", &self.docs_base_path)
+            .field("output_extension", &self.output_extension)
+            .field("note_extensions", &self.note_extensions)
+            .field(
+                "respect_export_frontmatter",
+                &self.respect_export_frontmatter,
+            )
+            .field("resolve_frontmatter_links", &self.resolve_frontmatter_links)
+            .field("resolve_relative_links", &self.resolve_relative_links)
+            .field("image_alt_from_filename", &self.image_alt_from_filename)
+            .field("daily_note_folder", &self.daily_note_folder)
+            .field("frontmatter_defaults_file", &self.frontmatter_defaults_file)
+            .field("frontmatter_list_merge", &self.frontmatter_list_merge)
+            .field("frontmatter_only_as_data", &self.frontmatter_only_as_data)
+            .field("permalink_key", &self.permalink_key)
+            .field(
+                "destination_mapper",
+                &self.destination_mapper.map(|_| "<function set>"),
+            )
+            .field("sitemap_base_url", &self.sitemap_base_url)
+            .field("passthrough_patterns", &self.passthrough_patterns)
+            .field("serializer", &"<serializer set>")
+            .field("line_ending", &self.line_ending)
+            .field("assets_dir", &self.assets_dir)
+            .field("index_filename", &self.index_filename)
+            .field("linked_vaults", &self.linked_vaults)
+            .field("frontmatter_allowlist", &self.frontmatter_allowlist)
+            .field("frontmatter_denylist", &self.frontmatter_denylist)
+            .field(
+                "frontmatter_filters",
+                &format!("<{} filters active>", self.frontmatter_filters.len()),
+            )
+            .field(
+                "frontmatter_query",
+                &self.frontmatter_query.map(|_| "<function set>"),
+            )
+            .field(
+                "postprocessors",
+                &format!("<{} postprocessors active>", self.postprocessors.len()),
+            )
+            .field(
+                "embed_postprocessors",
+                &format!(
+                    "<{} postprocessors active>",
+                    self.embed_postprocessors.len()
+                ),
+            )
+            .field(
+                "output_filters",
+                &format!("<{} output filters active>", self.output_filters.len()),
+            )
+            .field(
+                "finalizers",
+                &format!("<{} finalizers active>", self.finalizers.len()),
+            )
+            .finish()
+    }
+}
+
+impl Default for ExportConfig<'_> {
+    fn default() -> Self {
+        Self {
+            frontmatter_strategy: FrontmatterStrategy::Auto,
+            frontmatter_yaml_options: YamlOptions::default(),
+            walk_options: WalkOptions::default(),
+            ignore_filename: None,
+            ignore_files: vec![],
+            process_embeds_recursively: true,
+            embed_recursion_limit: DEFAULT_EMBED_RECURSION_LIMIT,
+            max_embed_bytes: None,
+            excluded_embed_behavior: ExcludedEmbedBehavior::default(),
+            duplicate_embed_behavior: DuplicateEmbedBehavior::default(),
+            fail_on_unresolved_links: false,
+            unresolved_link_style: UnresolvedLinkStyle::default(),
+            preserve_mtime: false,
+            preserve_permissions: false,
+            git_dates: false,
+            modified_since: None,
+            dry_run: false,
+            dry_run_callback: None,
+            progress_callback: None,
+            filename_slugifier: None,
+            link_resolution_mode: LinkResolutionMode::default(),
+            internal_link_format: InternalLinkFormat::default(),
+            docs_base_path: DEFAULT_DOCS_BASE_PATH.to_owned(),
+            output_extension: None,
+            note_extensions: default_note_extensions(),
+            respect_export_frontmatter: false,
+            resolve_frontmatter_links: false,
+            resolve_relative_links: false,
+            image_alt_from_filename: false,
+            daily_note_folder: None,
+            frontmatter_defaults_file: None,
+            frontmatter_list_merge: HashMap::new(),
+            frontmatter_only_as_data: None,
+            permalink_key: None,
+            destination_mapper: None,
+            sitemap_base_url: None,
+            passthrough_patterns: vec![],
+            serializer: Arc::new(CmarkSerializer::default()),
+            line_ending: LineEnding::default(),
+            assets_dir: None,
+            index_filename: None,
+            linked_vaults: vec![],
+            frontmatter_allowlist: vec![],
+            frontmatter_denylist: vec![],
+            frontmatter_filters: vec![],
+            frontmatter_query: None,
+            postprocessors: vec![],
+            embed_postprocessors: vec![],
+            output_filters: vec![],
+            finalizers: vec![],
+        }
+    }
+}
+
+impl<'a> ExportConfig<'a> {
+    /// Create an [`Exporter`] with these settings, which reads notes from `root` and exports these
+    /// to `destination`.
+    #[must_use]
+    pub fn build(&self, root: PathBuf, destination: PathBuf) -> Exporter<'a> {
+        Exporter {
+            start_at: root.clone(),
+            root,
+            destination,
+            vault_index: None,
+            state: Arc::default(),
+            config: self.clone(),
+        }
+    }
+
+    /// Set the [`WalkOptions`] to use.
+    ///
+    /// See [`Exporter::walk_options`] for details.
+    pub fn walk_options(&mut self, options: WalkOptions<'a>) -> &mut Self {
+        self.walk_options = options;
+        self
+    }
+
+    /// Set the filename of the ignore files within the vault, such as `.exportignore`.
+    ///
+    /// See [`Exporter::ignore_filename`] for details.
+    pub fn ignore_filename(&mut self, filename: String) -> &mut Self {
+        self.ignore_filename = Some(filename);
+        self
+    }
+
+    /// Add an ignore file whose patterns apply to the whole vault, for example one which is shared
+    /// between several vaults.
+    ///
+    /// See [`Exporter::add_ignore_file`] for details.
+    pub fn add_ignore_file(&mut self, path: PathBuf) -> &mut Self {
+        self.ignore_files.push(path);
+        self
+    }
+
+    /// Set whether symbolic links within the vault should be followed.
+    ///
+    /// See [`Exporter::follow_symlinks`] for details.
+    pub fn follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.walk_options.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Set the [`FrontmatterStrategy`] to use.
+    ///
+    /// See [`Exporter::frontmatter_strategy`] for details.
+    pub fn frontmatter_strategy(&mut self, strategy: FrontmatterStrategy) -> &mut Self {
+        self.frontmatter_strategy = strategy;
+        self
+    }
+
+    /// Set the [`YamlOptions`] used to write frontmatter.
+    ///
+    /// See [`Exporter::frontmatter_yaml_options`] for details.
+    pub fn frontmatter_yaml_options(&mut self, options: YamlOptions) -> &mut Self {
+        self.frontmatter_yaml_options = options;
+        self
+    }
+
+    /// Only keep the given frontmatter keys in exported notes.
+    ///
+    /// See [`Exporter::frontmatter_allowlist`] for details.
+    pub fn frontmatter_allowlist(&mut self, keys: Vec<String>) -> &mut Self {
+        self.frontmatter_allowlist = keys;
+        self
+    }
+
+    /// Remove the given frontmatter keys from exported notes.
+    ///
+    /// See [`Exporter::frontmatter_denylist`] for details.
+    pub fn frontmatter_denylist(&mut self, keys: Vec<String>) -> &mut Self {
+        self.frontmatter_denylist = keys;
+        self
+    }
+
+    /// Set the behavior when recursive embeds are encountered.
+    ///
+    /// See [`Exporter::process_embeds_recursively`] for details.
+    pub fn process_embeds_recursively(&mut self, recursive: bool) -> &mut Self {
+        self.process_embeds_recursively = recursive;
+        self
+    }
+
+    /// Set the maximum nesting depth of embedded notes.
+    ///
+    /// See [`Exporter::embed_recursion_limit`] for details.
+    pub fn embed_recursion_limit(&mut self, limit: usize) -> &mut Self {
+        self.embed_recursion_limit = limit;
+        self
+    }
+
+    /// Set the maximum size of the content inlined by a single embedded note.
+    ///
+    /// See [`Exporter::max_embed_bytes`] for details.
+    pub fn max_embed_bytes(&mut self, limit: usize) -> &mut Self {
+        self.max_embed_bytes = Some(limit);
+        self
+    }
+
+    /// Set how embeds of notes which exist within the vault, but are excluded from the export, are
+    /// handled.
+    ///
+    /// See [`Exporter::excluded_embed_behavior`] for details.
+    pub fn excluded_embed_behavior(&mut self, behavior: ExcludedEmbedBehavior) -> &mut Self {
+        self.excluded_embed_behavior = behavior;
+        self
+    }
+
+    /// Set how repeated embeds of the same note within a single exported note are handled.
+    ///
+    /// See [`Exporter::duplicate_embed_behavior`] for details.
+    pub fn duplicate_embed_behavior(&mut self, behavior: DuplicateEmbedBehavior) -> &mut Self {
+        self.duplicate_embed_behavior = behavior;
+        self
+    }
+
+    /// Set whether references which can't be resolved should fail the export.
+    ///
+    /// See [`Exporter::fail_on_unresolved_links`] for details.
+    pub fn fail_on_unresolved_links(&mut self, fail: bool) -> &mut Self {
+        self.fail_on_unresolved_links = fail;
+        self
+    }
+
+    /// Set how references to notes or files which can't be found are rendered.
+    ///
+    /// See [`Exporter::unresolved_link_style`] for details.
+    pub fn unresolved_link_style(&mut self, style: UnresolvedLinkStyle) -> &mut Self {
+        self.unresolved_link_style = style;
+        self
+    }
+
+    /// Set whether the modified time of exported files should be preserved.
+    ///
+    /// See [`Exporter::preserve_mtime`] for details.
+    pub fn preserve_mtime(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_mtime = preserve;
+        self
+    }
+
+    /// Set whether the permission bits of copied attachments should be preserved.
+    ///
+    /// See [`Exporter::preserve_permissions`] for details.
+    pub fn preserve_permissions(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_permissions = preserve;
+        self
+    }
+
+    /// Set whether `created` and `modified` frontmatter keys are filled in from the git history
+    /// of notes.
+    ///
+    /// See [`Exporter::git_dates`] for details.
+    pub fn git_dates(&mut self, enabled: bool) -> &mut Self {
+        self.git_dates = enabled;
+        self
+    }
+
+    /// Only export files which were modified after `cutoff`, for example to publish the changes
+    /// made since the previous export.
+    ///
+    /// See [`Exporter::modified_since`] for details.
+    pub fn modified_since(&mut self, cutoff: SystemTime) -> &mut Self {
+        self.modified_since = Some(cutoff);
+        self
+    }
+
+    /// Set whether to perform a dry run.
+    ///
+    /// See [`Exporter::dry_run`] for details.
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Set the function which receives the [actions][DryRunAction] that would be taken during a
+    /// [dry run][Exporter::dry_run].
+    ///
+    /// See [`Exporter::on_dry_run_action`] for details.
+    pub fn on_dry_run_action(&mut self, callback: &'a DryRunCallback<'_>) -> &mut Self {
+        self.dry_run_callback = Some(callback);
+        self
+    }
+
+    /// Set a function which is called with [`ProgressEvent`]s while the export is running.
+    ///
+    /// See [`Exporter::on_progress`] for details.
+    pub fn on_progress(&mut self, callback: &'a ProgressCallback<'_>) -> &mut Self {
+        self.progress_callback = Some(callback);
+        self
+    }
+
+    /// Set a function which transforms the filename of every exported file.
+    ///
+    /// See [`Exporter::filename_slugifier`] for details.
+    pub fn filename_slugifier(&mut self, slugifier: &'a FilenameSlugifier<'_>) -> &mut Self {
+        self.filename_slugifier = Some(slugifier);
+        self
+    }
+
+    /// Set how the paths within references to other notes and files are interpreted.
+    ///
+    /// See [`Exporter::link_resolution_mode`] for details.
+    pub fn link_resolution_mode(&mut self, mode: LinkResolutionMode) -> &mut Self {
+        self.link_resolution_mode = mode;
+        self
+    }
+
+    /// Set the syntax used for references to other notes and files in exported notes.
+    ///
+    /// See [`Exporter::internal_link_format`] for details.
+    pub fn internal_link_format(&mut self, format: InternalLinkFormat) -> &mut Self {
+        self.internal_link_format = format;
+        self
+    }
+
+    /// Set the URL path which docs are served from, used for links to notes with
+    /// [`InternalLinkFormat::Docusaurus`].
+    ///
+    /// See [`Exporter::docs_base_path`] for details.
+    pub fn docs_base_path(&mut self, base_path: String) -> &mut Self {
+        self.docs_base_path = base_path;
+        self
+    }
+
+    /// Set the file extension of exported notes, such as `markdown` or `mdx`.
+    ///
+    /// See [`Exporter::output_extension`] for details.
+    pub fn output_extension(&mut self, mut extension: String) -> &mut Self {
+        if extension.starts_with('.') {
+            extension.remove(0);
+        }
+        self.output_extension = Some(extension);
+        self
+    }
+
+    /// Set the file extensions of files which are treated as notes, such as `md` or `txt`.
+    ///
+    /// See [`Exporter::note_extensions`] for details.
+    pub fn note_extensions(&mut self, extensions: Vec<String>) -> &mut Self {
+        self.note_extensions = extensions
+            .into_iter()
+            .map(|extension| extension.trim_start_matches('.').to_owned())
+            .collect();
+        self
+    }
+
+    /// Export notes which declare a permalink through the frontmatter `key` (such as `permalink`)
+    /// to the location given by their permalink, instead of their location within the vault.
+    ///
+    /// See [`Exporter::use_permalink_frontmatter`] for details.
+    pub fn use_permalink_frontmatter(&mut self, key: Option<String>) -> &mut Self {
+        self.permalink_key = key;
+        self
+    }
+
+    /// Set a function which determines where each note is exported to.
+    ///
+    /// See [`Exporter::destination_mapper`] for details.
+    pub fn destination_mapper(&mut self, mapper: &'a DestinationMapper<'_>) -> &mut Self {
+        self.destination_mapper = Some(mapper);
+        self
+    }
+
+    /// Set a directory (relative to the destination) to collect attachments into.
+    ///
+    /// See [`Exporter::assets_dir`] for details.
+    pub fn assets_dir(&mut self, assets_dir: Option<PathBuf>) -> &mut Self {
+        self.assets_dir = assets_dir;
+        self
+    }
+
+    /// Set the filename of the index page of folders.
+    ///
+    /// See [`Exporter::index_filename`] for details.
+    pub fn index_filename(&mut self, filename: String) -> &mut Self {
+        self.index_filename = Some(filename);
+        self
+    }
+
+    /// Add another vault which notes can link to through `[[name::Note]]` references.
+    ///
+    /// See [`Exporter::add_vault`] for details.
+    pub fn add_vault(&mut self, name: String, path: PathBuf, base_url: String) -> &mut Self {
+        self.linked_vaults.push(LinkedVault {
+            name,
+            path,
+            base_url,
+        });
+        self
+    }
+
+    /// Append a function to the chain of [postprocessors][Postprocessor] to run on exported
+    /// Obsidian Markdown notes.
+    ///
+    /// See [`Exporter::add_postprocessor`] for details.
+    pub fn add_postprocessor(&mut self, processor: &'a Postprocessor<'_>) -> &mut Self {
+        self.postprocessors
+            .push(PostprocessorFn::Infallible(processor));
+        self
+    }
+
+    /// Append a [fallible postprocessor][FalliblePostprocessor] to the chain of
+    /// [postprocessors][Postprocessor] to run on exported Obsidian Markdown notes.
+    ///
+    /// See [`Exporter::add_fallible_postprocessor`] for details.
+    pub fn add_fallible_postprocessor(
+        &mut self,
+        processor: &'a FalliblePostprocessor<'_>,
+    ) -> &mut Self {
+        self.postprocessors
+            .push(PostprocessorFn::Fallible(processor));
+        self
+    }
+
+    /// Append a function to the chain of [postprocessors][Postprocessor] for embeds.
+    ///
+    /// See [`Exporter::add_embed_postprocessor`] for details.
+    pub fn add_embed_postprocessor(&mut self, processor: &'a Postprocessor<'_>) -> &mut Self {
+        self.embed_postprocessors.push(processor);
+        self
+    }
+
+    /// Append a function to the list of [frontmatter filters][FrontmatterFilter].
+    ///
+    /// See [`Exporter::add_frontmatter_filter`] for details.
+    pub fn add_frontmatter_filter(&mut self, filter: &'a FrontmatterFilter<'_>) -> &mut Self {
+        self.frontmatter_filters.push(filter);
+        self
+    }
+
+    /// Only export notes whose frontmatter matches `query`, for example to export the subset of a
+    /// vault making up a blog.
+    ///
+    /// See [`Exporter::frontmatter_query`] for details.
+    pub fn frontmatter_query(&mut self, query: &'a FrontmatterFilter<'_>) -> &mut Self {
+        self.frontmatter_query = Some(query);
+        self
+    }
+
+    /// Skip notes which opt out of being exported through their frontmatter, using either
+    /// `export: false` or `publish: false`.
+    ///
+    /// See [`Exporter::respect_export_frontmatter`] for details.
+    pub fn respect_export_frontmatter(&mut self, respect: bool) -> &mut Self {
+        self.respect_export_frontmatter = respect;
+        self
+    }
+
+    /// Resolve `[[links]]` within frontmatter values, such as `related: "[[Other Note]]"`.
+    ///
+    /// See [`Exporter::resolve_frontmatter_links`] for details.
+    pub fn resolve_frontmatter_links(&mut self, resolve: bool) -> &mut Self {
+        self.resolve_frontmatter_links = resolve;
+        self
+    }
+
+    /// Resolve standard markdown links to other files within the vault.
+    ///
+    /// See [`Exporter::resolve_relative_links`] for details.
+    pub fn resolve_relative_links(&mut self, resolve: bool) -> &mut Self {
+        self.resolve_relative_links = resolve;
+        self
+    }
+
+    /// Use the filename stem as alt text for images embedded without alt text.
+    ///
+    /// See [`Exporter::image_alt_from_filename`] for details.
+    pub fn image_alt_from_filename(&mut self, enabled: bool) -> &mut Self {
+        self.image_alt_from_filename = enabled;
+        self
+    }
+
+    /// Resolve references to daily notes within `folder` first.
+    ///
+    /// See [`Exporter::daily_note_folder`] for details.
+    pub fn daily_note_folder(&mut self, folder: PathBuf) -> &mut Self {
+        self.daily_note_folder = Some(folder);
+        self
+    }
+
+    /// Merge the frontmatter defaults read from files named `filename` (such as `_defaults.yaml`)
+    /// into the frontmatter of every note in the same directory and its subdirectories.
+    ///
+    /// See [`Exporter::frontmatter_defaults_file`] for details.
+    pub fn frontmatter_defaults_file(&mut self, filename: String) -> &mut Self {
+        self.frontmatter_defaults_file = Some(filename);
+        self
+    }
+
+    /// Set how a list under the frontmatter `key` of a note is combined with the list under the
+    /// same key in its frontmatter defaults.
+    ///
+    /// See [`Exporter::frontmatter_list_merge`] for details.
+    pub fn frontmatter_list_merge(&mut self, key: String, merge: ListMerge) -> &mut Self {
+        self.frontmatter_list_merge.insert(key, merge);
+        self
+    }
+
+    /// Set the format in which notes consisting of nothing but frontmatter are exported as data
+    /// files.
+    ///
+    /// See [`Exporter::frontmatter_only_as_data`] for details.
+    pub fn frontmatter_only_as_data(&mut self, format: Option<DataFormat>) -> &mut Self {
+        self.frontmatter_only_as_data = format;
+        self
+    }
+
+    /// Copy files matching `pattern` to the destination verbatim, regardless of ignore rules.
+    ///
+    /// See [`Exporter::add_passthrough_pattern`] for details.
+    pub fn add_passthrough_pattern(&mut self, pattern: &str) -> &mut Self {
+        self.passthrough_patterns.push(pattern.to_owned());
+        self
+    }
+
+    /// Set the [`MarkdownSerializer`] which turns the events of each note into markdown text.
+    ///
+    /// See [`Exporter::set_serializer`] for details.
+    pub fn set_serializer(&mut self, serializer: Box<dyn MarkdownSerializer>) -> &mut Self {
+        self.serializer = Arc::from(serializer);
+        self
+    }
+
+    /// Set the line endings used in exported notes.
+    ///
+    /// See [`Exporter::line_ending`] for details.
+    pub fn line_ending(&mut self, line_ending: LineEnding) -> &mut Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Write a `sitemap.xml` listing the URL of each exported note below `base_url`.
+    ///
+    /// See [`Exporter::write_sitemap`] for details.
+    pub fn write_sitemap(&mut self, base_url: Option<String>) -> &mut Self {
+        self.sitemap_base_url = base_url;
+        self
+    }
+
+    /// Append a function which rewrites the complete markdown of each exported note.
+    ///
+    /// See [`Exporter::add_output_filter`] for details.
+    pub fn add_output_filter(&mut self, filter: &'a OutputFilter<'_>) -> &mut Self {
+        self.output_filters.push(filter);
+        self
+    }
+
+    /// Append a function which runs once after all notes have been exported.
+    ///
+    /// See [`Exporter::add_finalizer`] for details.
+    pub fn add_finalizer(&mut self, finalizer: &'a Finalizer<'_>) -> &mut Self {
+        self.finalizers.push(finalizer);
+        self
+    }
+}
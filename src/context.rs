@@ -1,6 +1,14 @@
+use std::collections::HashSet;
+use std::mem;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 
-use crate::Frontmatter;
+use serde_yaml::Value;
+
+use crate::{ExportedFile, Frontmatter, InternalLinkFormat};
+
+/// A note, or a section of a note, which is embedded into another note.
+type ExpandedEmbed = (PathBuf, Option<String>);
 
 #[derive(Debug, Clone)]
 /// Context holds metadata about a note which is being parsed.
@@ -12,6 +20,19 @@ use crate::Frontmatter;
 /// and allow modification of a note's frontmatter.
 pub struct Context {
     file_tree: Vec<PathBuf>,
+    vault_root: PathBuf,
+    destination_root: PathBuf,
+    internal_link_format: InternalLinkFormat,
+    first_heading: Option<String>,
+    title: OnceLock<String>,
+    embedded_block: Option<String>,
+    embed_source_url: Option<String>,
+    /// The notes (and sections of notes) which have been embedded into the root note so far. This
+    /// is shared with the contexts of embedded notes.
+    expanded_embeds: Arc<Mutex<HashSet<ExpandedEmbed>>>,
+    /// The files which postprocessors added to the export of the root note (see
+    /// [`Context::add_output_file`]). This is shared with the contexts of embedded notes.
+    output_files: Arc<Mutex<Vec<ExportedFile>>>,
 
     /// The path where this note will be written to when exported.
     ///
@@ -51,20 +72,110 @@ impl Context {
     pub fn new(src: PathBuf, dest: PathBuf) -> Self {
         Self {
             file_tree: vec![src],
+            vault_root: PathBuf::new(),
+            destination_root: PathBuf::new(),
+            internal_link_format: InternalLinkFormat::default(),
+            first_heading: None,
+            title: OnceLock::new(),
+            embedded_block: None,
+            embed_source_url: None,
+            expanded_embeds: Arc::default(),
+            output_files: Arc::default(),
             destination: dest,
             frontmatter: Frontmatter::new(),
         }
     }
 
+    /// Set the directory which notes are exported to.
+    ///
+    /// This is used to determine [`Context::destination_relative`].
+    #[inline]
+    #[must_use]
+    pub fn with_destination_root(mut self, root: PathBuf) -> Self {
+        self.destination_root = root;
+        self
+    }
+
+    /// Set the root directory of the vault which notes are read from.
+    ///
+    /// This is used to determine [`Context::current_file_relative`].
+    #[inline]
+    #[must_use]
+    pub fn with_vault_root(mut self, root: PathBuf) -> Self {
+        self.vault_root = root;
+        self
+    }
+
+    /// Set the [`InternalLinkFormat`] which links to other notes are exported with.
+    ///
+    /// This allows postprocessors which add links of their own to match the other links of a
+    /// note (see [`Context::internal_link_format`]).
+    #[inline]
+    #[must_use]
+    pub const fn with_internal_link_format(mut self, format: InternalLinkFormat) -> Self {
+        self.internal_link_format = format;
+        self
+    }
+
     /// Create a new `Context` which inherits from a parent Context.
     #[inline]
     #[must_use]
     pub fn from_parent(context: &Self, child: &Path) -> Self {
         let mut context = context.clone();
         context.file_tree.push(child.to_path_buf());
+        context.first_heading = None;
+        context.title = OnceLock::new();
+        context.embedded_block = None;
+        context.embed_source_url = None;
         context
     }
 
+    /// Set the text of the first top-level heading of the note, which is used by
+    /// [`Context::title`].
+    pub(crate) fn set_first_heading(&mut self, heading: Option<String>) {
+        self.first_heading = heading;
+    }
+
+    /// Record how the note is embedded: the identifier of the block which is embedded (if any),
+    /// and the URL linking to the embedded note from the note it's embedded in.
+    pub(crate) fn set_embed(&mut self, block_id: Option<String>, source_url: String) {
+        self.embedded_block = block_id;
+        self.embed_source_url = Some(source_url);
+    }
+
+    /// Record that `section` (or all) of the note at `path` is embedded into the root note,
+    /// returning whether this is the first time.
+    pub(crate) fn record_expanded_embed(&self, path: &Path, section: Option<&str>) -> bool {
+        self.expanded_embeds
+            .lock()
+            .expect("expanded embeds mutex poisoned")
+            .insert((path.to_path_buf(), section.map(ToOwned::to_owned)))
+    }
+
+    /// Export `content` to `path` (relative to the destination) along with the root note.
+    ///
+    /// This allows postprocessors to produce files of their own. These are written by
+    /// [`Exporter::run`][crate::Exporter::run] once the note is written (or reported during a
+    /// [dry run][crate::Exporter::dry_run]) and yielded by
+    /// [`Exporter::iter_exports`][crate::Exporter::iter_exports] after the note. They're discarded
+    /// when the note is skipped.
+    pub fn add_output_file(&self, path: PathBuf, content: Vec<u8>) {
+        self.output_files
+            .lock()
+            .expect("output files mutex poisoned")
+            .push(ExportedFile { path, content });
+    }
+
+    /// Take the files which postprocessors added to the export of the root note so far.
+    pub(crate) fn take_output_files(&self) -> Vec<ExportedFile> {
+        mem::take(
+            &mut self
+                .output_files
+                .lock()
+                .expect("output files mutex poisoned"),
+        )
+    }
+
     /// Return the path of the file currently being parsed.
     #[inline]
     #[must_use]
@@ -86,6 +197,87 @@ impl Context {
             .expect("Context not initialized properly, file_tree is empty")
     }
 
+    /// Return the path of the file currently being parsed, relative to the root of the vault.
+    ///
+    /// When no vault root is known (see [`Context::with_vault_root`]), the full path is returned.
+    #[inline]
+    #[must_use]
+    pub fn current_file_relative(&self) -> &Path {
+        let current_file = self.current_file();
+        current_file
+            .strip_prefix(&self.vault_root)
+            .unwrap_or(current_file)
+    }
+
+    /// Return the directory which notes are exported to.
+    ///
+    /// This is empty when no destination root is known (see [`Context::with_destination_root`]).
+    #[inline]
+    #[must_use]
+    pub fn destination_root(&self) -> &Path {
+        &self.destination_root
+    }
+
+    /// Return the destination path relative to the directory notes are exported to.
+    ///
+    /// When no destination root is known (see [`Context::with_destination_root`]), the full
+    /// destination path is returned.
+    #[inline]
+    #[must_use]
+    pub fn destination_relative(&self) -> &Path {
+        self.destination
+            .strip_prefix(&self.destination_root)
+            .unwrap_or(&self.destination)
+    }
+
+    /// Return the [`InternalLinkFormat`] which links to other notes are exported with.
+    #[inline]
+    #[must_use]
+    pub const fn internal_link_format(&self) -> InternalLinkFormat {
+        self.internal_link_format
+    }
+
+    /// Return the title of the note currently being parsed.
+    ///
+    /// This is the `title` from the frontmatter when present, or else the text of the first
+    /// top-level (`# Heading`) heading, or else the filename without its extension. The title is
+    /// determined the first time it's requested and then cached, so later changes to the
+    /// frontmatter don't affect it.
+    #[must_use]
+    pub fn title(&self) -> String {
+        self.title
+            .get_or_init(|| {
+                self.frontmatter
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned)
+                    .or_else(|| self.first_heading.clone())
+                    .unwrap_or_else(|| {
+                        self.current_file()
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                            .unwrap_or_default()
+                    })
+            })
+            .clone()
+    }
+
+    /// Return the identifier of the block when only a single block of the note is embedded
+    /// (`![[Note#^blockid]]`), without the leading `^`.
+    #[inline]
+    #[must_use]
+    pub fn embedded_block(&self) -> Option<&str> {
+        self.embedded_block.as_deref()
+    }
+
+    /// Return the URL linking to the note currently being parsed from the note it's embedded in,
+    /// or `None` when it isn't embedded.
+    #[inline]
+    #[must_use]
+    pub fn embed_source_url(&self) -> Option<&str> {
+        self.embed_source_url.as_deref()
+    }
+
     /// Return the note depth (nesting level) for this context.
     #[inline]
     #[must_use]
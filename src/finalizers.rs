@@ -0,0 +1,209 @@
+//! A collection of officially maintained [finalizers][crate::Finalizer].
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use percent_encoding::utf8_percent_encode;
+use serde_yaml::Value;
+use snafu::ResultExt;
+
+use crate::frontmatter::{
+    extract_frontmatter,
+    frontmatter_from_str,
+    frontmatter_to_str,
+    strip_frontmatter,
+};
+use crate::{
+    ExportError,
+    FinalizeContext,
+    FrontMatterDecodeSnafu,
+    FrontMatterEncodeSnafu,
+    ReadSnafu,
+    WriteSnafu,
+    PERCENTENCODE_CHARS,
+};
+
+/// The filename of the redirects file written for [`RedirectFormat::Netlify`].
+const NETLIFY_REDIRECTS_FILENAME: &str = "_redirects";
+
+/// The filename of the sitemap written for
+/// [`Exporter::write_sitemap`][crate::Exporter::write_sitemap].
+const SITEMAP_FILENAME: &str = "sitemap.xml";
+
+/// The kind of redirects written by [`emit_redirects`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RedirectFormat {
+    /// A Netlify `_redirects` file at the root of the destination, with a permanent redirect for
+    /// each note.
+    Netlify,
+    /// A `redirect_from` list in the frontmatter of each note, as used by the
+    /// `jekyll-redirect-from` plugin.
+    JekyllRedirectFrom,
+    /// An HTML page at the original path of each note (`Note.html`), which redirects to the new
+    /// path using a meta refresh.
+    HtmlMetaRefresh,
+}
+
+/// This finalizer writes redirects from the original path of each note to the path it was
+/// exported to.
+///
+/// This keeps links to notes working when their filenames change, for example through
+/// [`Exporter::filename_slugifier`][crate::Exporter::filename_slugifier].
+///
+/// Paths are written as site-absolute URLs without the file extension, as static site generators
+/// usually serve pages from these, so `Folder/My Note.md` exported as `Folder/my-note.md` results
+/// in a redirect from `/Folder/My%20Note` to `/Folder/my-note`. Notes which keep their path don't
+/// get a redirect.
+pub fn emit_redirects(
+    format: RedirectFormat,
+) -> impl Fn(&FinalizeContext) -> Result<(), ExportError> {
+    move |context: &FinalizeContext| -> Result<(), ExportError> {
+        let redirects: Vec<Redirect> = context
+            .notes
+            .iter()
+            .filter_map(|note| {
+                let from = note.source.strip_prefix(&context.root).ok()?;
+                let to = note.destination.strip_prefix(&context.destination).ok()?;
+                let redirect = Redirect {
+                    from: page_url(from),
+                    to: page_url(to),
+                    source: from.to_path_buf(),
+                    destination: note.destination.clone(),
+                };
+                (redirect.from != redirect.to).then_some(redirect)
+            })
+            .collect();
+
+        match format {
+            RedirectFormat::Netlify => write_netlify_redirects(&context.destination, &redirects),
+            RedirectFormat::JekyllRedirectFrom => redirects.iter().try_for_each(add_redirect_from),
+            RedirectFormat::HtmlMetaRefresh => redirects
+                .iter()
+                .try_for_each(|redirect| write_meta_refresh(&context.destination, redirect)),
+        }
+    }
+}
+
+/// A redirect from the original URL of a note to its new URL.
+struct Redirect {
+    from: String,
+    to: String,
+    /// The path of the note within the vault.
+    source: PathBuf,
+    /// The path the note was written to.
+    destination: PathBuf,
+}
+
+/// Return the site-absolute URL of the page generated from the note at `path`.
+fn page_url(path: &Path) -> String {
+    let segments: Vec<String> = path
+        .with_extension("")
+        .components()
+        .map(|component| {
+            utf8_percent_encode(
+                &component.as_os_str().to_string_lossy(),
+                PERCENTENCODE_CHARS,
+            )
+            .to_string()
+        })
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+fn write_netlify_redirects(destination: &Path, redirects: &[Redirect]) -> Result<(), ExportError> {
+    if redirects.is_empty() {
+        return Ok(());
+    }
+    let path = destination.join(NETLIFY_REDIRECTS_FILENAME);
+    let content = redirects
+        .iter()
+        .fold(String::new(), |mut content, redirect| {
+            content.push_str(&redirect.from);
+            content.push(' ');
+            content.push_str(&redirect.to);
+            content.push_str(" 301\n");
+            content
+        });
+    fs::write(&path, content).context(WriteSnafu { path })
+}
+
+/// Write a sitemap listing the URL of each exported note below `base_url` (see
+/// [`Exporter::write_sitemap`][crate::Exporter::write_sitemap]).
+pub(crate) fn write_sitemap(context: &FinalizeContext, base_url: &str) -> Result<(), ExportError> {
+    let base_url = base_url.trim_end_matches('/');
+    let mut sitemap = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset \
+         xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for note in &context.notes {
+        let Ok(destination) = note.destination.strip_prefix(&context.destination) else {
+            continue;
+        };
+        let url = format!("{base_url}{}", page_url(destination));
+        sitemap.push_str("  <url>\n    <loc>");
+        sitemap.push_str(&escape_xml(&url));
+        sitemap.push_str("</loc>\n  </url>\n");
+    }
+    sitemap.push_str("</urlset>\n");
+    let path = context.destination.join(SITEMAP_FILENAME);
+    fs::write(&path, sitemap).context(WriteSnafu { path })
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Add the original URL of a note to the `redirect_from` list in its frontmatter.
+fn add_redirect_from(redirect: &Redirect) -> Result<(), ExportError> {
+    let path = &redirect.destination;
+    let content = fs::read_to_string(path).context(ReadSnafu { path })?;
+    let mut frontmatter = frontmatter_from_str(&extract_frontmatter(&content))
+        .context(FrontMatterDecodeSnafu { path })?;
+
+    let key = Value::String("redirect_from".to_owned());
+    let from = Value::String(redirect.from.clone());
+    match frontmatter.get_mut(&key) {
+        Some(Value::Sequence(urls)) => {
+            if !urls.contains(&from) {
+                urls.push(from);
+            }
+        }
+        Some(Value::String(url)) if *url != redirect.from => {
+            let existing = Value::String(url.clone());
+            frontmatter.insert(key, Value::Sequence(vec![existing, from]));
+        }
+        Some(_) => {}
+        None => {
+            frontmatter.insert(key, Value::Sequence(vec![from]));
+        }
+    }
+
+    let mut rewritten =
+        frontmatter_to_str(&frontmatter).context(FrontMatterEncodeSnafu { path })?;
+    let body = strip_frontmatter(&content);
+    if !body.starts_with('\n') {
+        rewritten.push('\n');
+    }
+    rewritten.push_str(body);
+    fs::write(path, rewritten).context(WriteSnafu { path })
+}
+
+/// Write an HTML page at the original path of a note which redirects to its new URL.
+fn write_meta_refresh(destination: &Path, redirect: &Redirect) -> Result<(), ExportError> {
+    let path = destination.join(redirect.source.with_extension("html"));
+    let url = redirect.to.replace('&', "&amp;").replace('"', "&quot;");
+    let html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta http-equiv=\"refresh\" \
+         content=\"0; url={url}\">\n<link rel=\"canonical\" href=\"{url}\">\n</head>\n<body>\n<a \
+         href=\"{url}\">{url}</a>\n</body>\n</html>\n"
+    );
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context(WriteSnafu { path: parent })?;
+    }
+    fs::write(&path, html).context(WriteSnafu { path })
+}
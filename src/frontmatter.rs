@@ -1,4 +1,7 @@
-use serde_yaml::Result;
+use std::collections::HashMap;
+
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use serde_yaml::{Result, Value};
 
 /// YAML front matter from an Obsidian note.
 ///
@@ -33,20 +36,324 @@ pub fn frontmatter_from_str(mut s: &str) -> Result<Frontmatter> {
     Ok(frontmatter)
 }
 
+/// Return the raw YAML frontmatter block at the start of the markdown `content`, without parsing
+/// the rest of the note.
+///
+/// An empty string is returned when the note doesn't start with
+/// [valid frontmatter][has_frontmatter].
+#[allow(clippy::module_name_repetitions)]
+pub fn extract_frontmatter(content: &str) -> String {
+    metadata_block(content)
+        .filter(|block| is_valid_frontmatter(block))
+        .unwrap_or_default()
+}
+
+/// Return the markdown `content` without the [valid frontmatter][has_frontmatter] it starts with.
+#[allow(clippy::module_name_repetitions)]
+pub fn strip_frontmatter(content: &str) -> &str {
+    let content = without_bom(content);
+    if !has_frontmatter(content) {
+        return content;
+    }
+    let mut parser =
+        Parser::new_ext(content, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS).into_offset_iter();
+    match parser.next() {
+        Some((Event::Start(Tag::MetadataBlock(_kind)), range)) => {
+            content.get(range.end..).unwrap_or_default()
+        }
+        _ => content,
+    }
+}
+
+/// Return whether the markdown `content` starts with a frontmatter block.
+///
+/// A leading `---` line only starts frontmatter when it's closed by another `---` line with a
+/// YAML mapping in between. Otherwise, as with notes which start with a horizontal rule, it's
+/// part of the body of the note.
+#[allow(clippy::module_name_repetitions)]
+pub fn has_frontmatter(content: &str) -> bool {
+    metadata_block(content).is_some_and(|block| is_valid_frontmatter(&block))
+}
+
+/// Return the contents of the YAML-style metadata block at the start of `content`, if any.
+fn metadata_block(content: &str) -> Option<String> {
+    let mut parser = Parser::new_ext(
+        without_bom(content),
+        Options::ENABLE_YAML_STYLE_METADATA_BLOCKS,
+    );
+    if !matches!(parser.next(), Some(Event::Start(Tag::MetadataBlock(_kind)))) {
+        return None;
+    }
+    let mut block = String::new();
+    for event in parser {
+        match event {
+            Event::Text(text) => block.push_str(&text),
+            _ => break,
+        }
+    }
+    Some(block)
+}
+
+/// Return `content` without the byte order mark which some editors write at the start of files,
+/// as it would prevent frontmatter from being recognized.
+fn without_bom(content: &str) -> &str {
+    content.strip_prefix('\u{feff}').unwrap_or(content)
+}
+
+/// Return the markdown `content` without a leading byte order mark and without an empty
+/// frontmatter block (`---` directly followed by `---`).
+///
+/// pulldown-cmark doesn't recognize a metadata block without any content, so it would be rendered
+/// as two horizontal rules instead.
+#[allow(clippy::module_name_repetitions)]
+pub fn strip_empty_frontmatter(content: &str) -> &str {
+    let content = without_bom(content);
+    let mut lines = content.split_inclusive('\n');
+    let Some(first) = lines.next().filter(|line| line.trim_end() == "---") else {
+        return content;
+    };
+    let mut end = first.len();
+    for line in lines {
+        end = end.saturating_add(line.len());
+        match line.trim_end() {
+            "---" => return content.get(end..).unwrap_or_default(),
+            "" => {}
+            _ => break,
+        }
+    }
+    content
+}
+
+fn is_valid_frontmatter(block: &str) -> bool {
+    matches!(
+        serde_yaml::from_str(block),
+        Ok(Value::Mapping(_) | Value::Null)
+    )
+}
+
+/// Options controlling how frontmatter is written as YAML.
+///
+/// See [`Exporter::frontmatter_yaml_options`][crate::Exporter::frontmatter_yaml_options] for
+/// details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct YamlOptions {
+    /// Keep strings on a single line no matter how long they are.
+    ///
+    /// When disabled, strings which would extend past `line_width` are written as double-quoted
+    /// strings wrapped at spaces. Enabled by default.
+    pub no_wrap: bool,
+    /// The number of characters after which strings are wrapped when `no_wrap` is disabled.
+    ///
+    /// Defaults to 80.
+    pub line_width: usize,
+    /// Write multi-line strings as literal block scalars (`|`).
+    ///
+    /// When disabled, they are written as double-quoted strings with `\n` escapes instead.
+    /// Enabled by default.
+    pub block_scalars: bool,
+}
+
+impl Default for YamlOptions {
+    fn default() -> Self {
+        Self {
+            no_wrap: true,
+            line_width: 80,
+            block_scalars: true,
+        }
+    }
+}
+
 // Would be nice to rename this to just to_str, but that would be a breaking change.
 #[allow(clippy::module_name_repetitions)]
 pub fn frontmatter_to_str(frontmatter: &Frontmatter) -> Result<String> {
+    frontmatter_to_str_with_options(frontmatter, YamlOptions::default())
+}
+
+/// Serialize `frontmatter` as a YAML block, including its `---` delimiters, according to
+/// `options`.
+///
+/// `serde_yaml`'s emitter can't be configured. It never wraps lines and writes multi-line strings
+/// as literal block scalars (`|`), so any string which `options` want written differently is
+/// serialized as a placeholder and formatted by `restore_string` afterwards.
+#[allow(clippy::module_name_repetitions)]
+pub fn frontmatter_to_str_with_options(
+    frontmatter: &Frontmatter,
+    options: YamlOptions,
+) -> Result<String> {
     if frontmatter.is_empty() {
         return Ok("---\n---\n".to_owned());
     }
 
     let mut buffer = String::new();
     buffer.push_str("---\n");
-    buffer.push_str(&serde_yaml::to_string(&frontmatter)?);
+    if options.no_wrap && options.block_scalars {
+        buffer.push_str(&serde_yaml::to_string(&frontmatter)?);
+    } else {
+        let mut frontmatter = frontmatter.clone();
+        let mut strings = Vec::new();
+        for value in frontmatter.values_mut() {
+            replace_strings(value, options, &mut strings);
+        }
+        for line in serde_yaml::to_string(&frontmatter)?.split_inclusive('\n') {
+            match restore_string(line, &strings, options) {
+                Some(restored) => buffer.push_str(&restored),
+                None => buffer.push_str(line),
+            }
+        }
+    }
     buffer.push_str("---\n");
     Ok(buffer)
 }
 
+const YAML_PLACEHOLDER_PREFIX: &str = "__obsidian_export_string_";
+
+/// Replace the strings within `value` which `options` want formatted differently from
+/// `serde_yaml` with placeholders, collecting the original strings in `strings`.
+fn replace_strings(value: &mut Value, options: YamlOptions, strings: &mut Vec<String>) {
+    match value {
+        Value::String(string) if string.contains('\n') && options.block_scalars => {}
+        Value::String(string) if string.contains('\n') || !options.no_wrap => {
+            let placeholder = format!("{YAML_PLACEHOLDER_PREFIX}{}__", strings.len());
+            strings.push(std::mem::replace(string, placeholder));
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                replace_strings(item, options, strings);
+            }
+        }
+        Value::Mapping(mapping) => {
+            for entry in mapping.values_mut() {
+                replace_strings(entry, options, strings);
+            }
+        }
+        Value::Tagged(tagged) => replace_strings(&mut tagged.value, options, strings),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {}
+    }
+}
+
+/// Return `line` of serialized YAML with the placeholder it ends with, if any, replaced by the
+/// formatted string it stands for.
+fn restore_string(line: &str, strings: &[String], options: YamlOptions) -> Option<String> {
+    let content = line.strip_suffix('\n').unwrap_or(line);
+    let (prefix, placeholder) = content.split_at(content.rfind(YAML_PLACEHOLDER_PREFIX)?);
+    let index: usize = placeholder
+        .strip_prefix(YAML_PLACEHOLDER_PREFIX)?
+        .strip_suffix("__")?
+        .parse()
+        .ok()?;
+    let string = strings.get(index)?;
+
+    let single_line = if string.contains('\n') {
+        json_string(string)
+    } else {
+        serde_yaml::to_string(string).ok()?.trim_end().to_owned()
+    };
+    let column = prefix.chars().count();
+    let formatted = if options.no_wrap
+        || column.saturating_add(single_line.chars().count()) <= options.line_width
+    {
+        single_line
+    } else {
+        // Continuation lines have to be indented further than the key or list item the string
+        // belongs to.
+        let indent = prefix
+            .len()
+            .saturating_sub(prefix.trim_start_matches([' ', '-']).len())
+            .saturating_add(2);
+        wrap_quoted(&json_string(string), column, indent, options.line_width)
+    };
+    Some(format!("{prefix}{formatted}\n"))
+}
+
+/// Wrap the double-quoted string `quoted`, which starts at `column`, at spaces so its lines fit
+/// within `width` where possible, indenting continuation lines by `indent`.
+///
+/// A line break within a double-quoted string is read back as a single space, so only spaces
+/// between two other characters are replaced.
+fn wrap_quoted(quoted: &str, column: usize, indent: usize, width: usize) -> String {
+    let mut wrapped = String::with_capacity(quoted.len());
+    let mut line_width = column;
+    let mut previous: Option<&str> = None;
+    for word in quoted.split(' ') {
+        let word_width = word.chars().count();
+        match previous {
+            None => {
+                wrapped.push_str(word);
+                line_width = line_width.saturating_add(word_width);
+            }
+            Some(last)
+                if !last.is_empty()
+                    && !word.is_empty()
+                    && line_width.saturating_add(word_width) >= width =>
+            {
+                wrapped.push('\n');
+                wrapped.push_str(&" ".repeat(indent));
+                wrapped.push_str(word);
+                line_width = indent.saturating_add(word_width);
+            }
+            Some(_) => {
+                wrapped.push(' ');
+                wrapped.push_str(word);
+                line_width = line_width.saturating_add(word_width).saturating_add(1);
+            }
+        }
+        previous = Some(word);
+    }
+    wrapped
+}
+
+/// Serialize `value` as compact JSON, which is also a valid JavaScript literal.
+pub fn json_value(value: &Value) -> String {
+    match value {
+        Value::Bool(bool) => bool.to_string(),
+        Value::Number(number) if number.as_f64().is_some_and(f64::is_finite) => number.to_string(),
+        // JSON can't represent infinity or NaN.
+        Value::Null | Value::Number(_) => "null".to_owned(),
+        Value::String(string) => json_string(string),
+        Value::Sequence(sequence) => {
+            let items: Vec<String> = sequence.iter().map(json_value).collect();
+            format!("[{}]", items.join(","))
+        }
+        Value::Mapping(mapping) => {
+            let entries: Vec<String> = mapping
+                .iter()
+                .map(|(key, entry)| {
+                    let key = match key {
+                        Value::String(string) => json_string(string),
+                        Value::Bool(_) | Value::Number(_) | Value::Null => {
+                            json_string(&json_value(key))
+                        }
+                        _ => json_string(serde_yaml::to_string(key).unwrap_or_default().trim()),
+                    };
+                    format!("{key}:{}", json_value(entry))
+                })
+                .collect();
+            format!("{{{}}}", entries.join(","))
+        }
+        Value::Tagged(tagged) => json_value(&tagged.value),
+    }
+}
+
+/// Quote and escape `string` as a JSON string.
+fn json_string(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len().saturating_add(2));
+    quoted.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 /// Available strategies for the inclusion of frontmatter in notes.
 #[derive(Debug, Clone, Copy)]
 // Would be nice to rename this to just Strategy, but that would be a breaking change.
@@ -62,9 +369,62 @@ pub enum FrontmatterStrategy {
     Never,
 }
 
+/// How lists within the [frontmatter defaults][crate::Exporter::frontmatter_defaults_file] of a
+/// directory are combined with lists under the same key in a note.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListMerge {
+    /// Use the list of the note, ignoring the default.
+    #[default]
+    Replace,
+    /// Use the items of the default list followed by those of the note, leaving out duplicates.
+    Append,
+}
+
+/// Merge `defaults` into `frontmatter`, with the values of `frontmatter` taking precedence.
+///
+/// Keys which are missing from `frontmatter` are added and mappings present in both are merged
+/// recursively. Lists present in both are combined according to `list_merge`, which is looked up
+/// by top-level key. Any other value of `frontmatter` is kept as-is.
+pub fn merge_frontmatter_defaults(
+    frontmatter: &mut Frontmatter,
+    defaults: &Frontmatter,
+    list_merge: &HashMap<String, ListMerge>,
+) {
+    for (key, default) in defaults {
+        let merge = key
+            .as_str()
+            .and_then(|name| list_merge.get(name))
+            .copied()
+            .unwrap_or_default();
+        match (frontmatter.get_mut(key), default) {
+            (None, _) => {
+                frontmatter.insert(key.clone(), default.clone());
+            }
+            (Some(Value::Mapping(mapping)), Value::Mapping(default)) => {
+                merge_frontmatter_defaults(mapping, default, &HashMap::new());
+            }
+            (Some(Value::Sequence(items)), Value::Sequence(default))
+                if merge == ListMerge::Append =>
+            {
+                let mut merged: Vec<Value> =
+                    Vec::with_capacity(default.len().saturating_add(items.len()));
+                for item in default.iter().cloned().chain(items.drain(..)) {
+                    if !merged.contains(&item) {
+                        merged.push(item);
+                    }
+                }
+                *items = merged;
+            }
+            (Some(_), _) => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use rstest::rstest;
     use serde_yaml::Value;
 
     use super::*;
@@ -92,4 +452,116 @@ mod tests {
             format!("---\nfoo: bar\n---\n")
         );
     }
+
+    #[test]
+    fn long_frontmatter_values_are_not_wrapped() {
+        let description =
+            "A long description which goes on and on, well past the eighty characters \
+                           at which YAML emitters commonly wrap lines";
+        let mut frontmatter = Frontmatter::new();
+        frontmatter.insert(
+            Value::String("description".into()),
+            Value::String(description.into()),
+        );
+        assert_eq!(
+            frontmatter_to_str(&frontmatter).unwrap(),
+            format!("---\ndescription: {description}\n---\n")
+        );
+    }
+
+    #[test]
+    fn multiline_frontmatter_values_use_block_scalars() {
+        let mut frontmatter = Frontmatter::new();
+        frontmatter.insert(
+            Value::String("summary".into()),
+            Value::String("line one\nline two\n".into()),
+        );
+        assert_eq!(
+            frontmatter_to_str(&frontmatter).unwrap(),
+            "---\nsummary: |\n  line one\n  line two\n---\n"
+        );
+    }
+
+    #[test]
+    fn long_frontmatter_values_are_wrapped_without_no_wrap() {
+        let frontmatter = frontmatter_from_str(
+            "description: A long description which goes on and on, well past the eighty \
+             characters at which YAML emitters commonly wrap lines\n\
+             tags:\n- a tag which is short enough\n",
+        )
+        .unwrap();
+        let options = YamlOptions {
+            no_wrap: false,
+            line_width: 40,
+            ..YamlOptions::default()
+        };
+
+        let yaml = frontmatter_to_str_with_options(&frontmatter, options).unwrap();
+        assert_eq!(
+            yaml,
+            "---\ndescription: \"A long description which\n  goes on and on, well past the eighty\n  \
+             characters at which YAML emitters\n  commonly wrap lines\"\ntags:\n- a tag which is \
+             short enough\n---\n"
+        );
+        assert_eq!(
+            frontmatter_from_str(&extract_frontmatter(&yaml)).unwrap(),
+            frontmatter
+        );
+    }
+
+    #[test]
+    fn multiline_frontmatter_values_are_quoted_without_block_scalars() {
+        let mut frontmatter = Frontmatter::new();
+        frontmatter.insert(
+            Value::String("summary".into()),
+            Value::String("line one\nline \"two\"\n".into()),
+        );
+        let options = YamlOptions {
+            block_scalars: false,
+            ..YamlOptions::default()
+        };
+
+        let yaml = frontmatter_to_str_with_options(&frontmatter, options).unwrap();
+        assert_eq!(
+            yaml,
+            "---\nsummary: \"line one\\nline \\\"two\\\"\\n\"\n---\n"
+        );
+        assert_eq!(
+            frontmatter_from_str(&extract_frontmatter(&yaml)).unwrap(),
+            frontmatter
+        );
+    }
+
+    #[rstest]
+    #[case("---\ntitle: Note\n---\n\nText\n", true)]
+    #[case("---\n# Only a comment\n---\n\nText\n", true)]
+    #[case("---\n\nText\n", false)]
+    #[case("---\nSome text\n---\n\nText\n", false)]
+    #[case("---\nnot: [valid\n---\n\nText\n", false)]
+    #[case("Text\n", false)]
+    fn detects_frontmatter(#[case] content: &str, #[case] expected: bool) {
+        assert_eq!(has_frontmatter(content), expected);
+    }
+
+    #[rstest]
+    #[case(
+        ListMerge::Replace,
+        "tags:\n- draft\nlayout: post\nmeta:\n  author: Me\n  lang: en\n"
+    )]
+    #[case(
+        ListMerge::Append,
+        "tags:\n- blog\n- draft\nlayout: post\nmeta:\n  author: Me\n  lang: en\n"
+    )]
+    fn merges_frontmatter_defaults(#[case] merge: ListMerge, #[case] expected: &str) {
+        let mut frontmatter =
+            frontmatter_from_str("tags: [draft]\nlayout: post\nmeta:\n  author: Me").unwrap();
+        let defaults = frontmatter_from_str(
+            "tags: [blog, draft]\nlayout: page\nmeta:\n  author: Someone\n  lang: en",
+        )
+        .unwrap();
+        let list_merge = HashMap::from([("tags".to_owned(), merge)]);
+
+        merge_frontmatter_defaults(&mut frontmatter, &defaults, &list_merge);
+        assert_eq!(serde_yaml::to_string(&frontmatter).unwrap(), expected);
+    }
 }
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The dates of the first and the last commit which changed a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitDates {
+    /// The date of the first commit, in RFC 3339 format.
+    pub created: String,
+    /// The date of the last commit, in RFC 3339 format.
+    pub modified: String,
+}
+
+/// Read the dates of the first and last commit changing each file within `directory` from the
+/// history of the git repository containing it, keyed by their path relative to `directory`.
+///
+/// This runs `git log` once for the whole directory. `None` is returned when git isn't available
+/// or `directory` isn't part of a git repository.
+pub fn commit_dates(directory: &Path) -> Option<HashMap<PathBuf, CommitDates>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args([
+            "log",
+            "--relative",
+            "--no-renames",
+            "--format=%x1e%cI",
+            "--name-only",
+            "-z",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the output of `git log --format=%x1e%cI --name-only -z`, which lists commits from newest
+/// to oldest. Each commit starts with a record separator, followed by its date and the files it
+/// changed, all separated by NUL bytes.
+fn parse_log(log: &str) -> HashMap<PathBuf, CommitDates> {
+    let mut dates: HashMap<PathBuf, CommitDates> = HashMap::new();
+    for commit in log.split('\x1e') {
+        let mut fields = commit.split('\0');
+        let Some(date) = fields.next().filter(|date| !date.is_empty()) else {
+            continue;
+        };
+        for file in fields
+            .map(|file| file.trim_start_matches('\n'))
+            .filter(|file| !file.is_empty())
+        {
+            dates
+                .entry(PathBuf::from(file))
+                .and_modify(|file_dates| date.clone_into(&mut file_dates.created))
+                .or_insert_with(|| CommitDates {
+                    created: date.to_owned(),
+                    modified: date.to_owned(),
+                });
+        }
+    }
+    dates
+}
@@ -1,31 +1,74 @@
-pub use {pulldown_cmark, serde_yaml};
+pub use {pulldown_cmark, pulldown_cmark_to_cmark, serde_yaml};
 
+mod config;
 mod context;
+pub mod finalizers;
 mod frontmatter;
+mod git;
 pub mod postprocessors;
 mod references;
+mod serializer;
+mod vault_index;
 mod walker;
 
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::ffi::OsString;
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::ErrorKind;
-use std::path::{Path, PathBuf};
-use std::{fmt, str};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use std::time::SystemTime;
+use std::{fmt, iter, mem, str};
 
+use chrono::{DateTime, SecondsFormat, Utc};
+pub use config::ExportConfig;
 pub use context::Context;
 use filetime::set_file_mtime;
-use frontmatter::{frontmatter_from_str, frontmatter_to_str};
-pub use frontmatter::{Frontmatter, FrontmatterStrategy};
+use frontmatter::{
+    extract_frontmatter,
+    frontmatter_from_str,
+    frontmatter_to_str_with_options,
+    has_frontmatter,
+    json_value,
+    merge_frontmatter_defaults,
+    strip_empty_frontmatter,
+    strip_frontmatter,
+};
+pub use frontmatter::{Frontmatter, FrontmatterStrategy, ListMerge, YamlOptions};
+use git::CommitDates;
+use log::{info, warn};
 use pathdiff::diff_paths;
-use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
-use pulldown_cmark::{CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
-use pulldown_cmark_to_cmark::cmark_with_options;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use postprocessors::has_url_scheme;
+use pulldown_cmark::{
+    CodeBlockKind,
+    CowStr,
+    Event,
+    HeadingLevel,
+    LinkType,
+    Options,
+    Parser,
+    Tag,
+    TagEnd,
+};
 use rayon::prelude::*;
-use references::{ObsidianNoteReference, RefParser, RefParserState, RefType};
+use references::{
+    referenced_files,
+    ObsidianNoteReference,
+    RefParser,
+    RefParserState,
+    RefType,
+    WIKILINK_RE,
+};
+use regex::{Captures, Regex};
+use serde_yaml::Value;
+pub use serializer::{CmarkSerializer, MarkdownSerializer};
 use slug::slugify;
 use snafu::{ResultExt, Snafu};
 use unicode_normalization::UnicodeNormalization;
+pub use vault_index::{LinkResolutionMode, VaultIndex};
 pub use walker::{vault_contents, WalkOptions};
 
 /// A series of markdown [Event]s that are generated while traversing an Obsidian markdown note.
@@ -131,10 +174,97 @@ pub type MarkdownEvents<'a> = Vec<Event<'a>>;
 /// ```
 pub type Postprocessor<'f> =
     dyn Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult + Send + Sync + 'f;
+
+/// A [Postprocessor] which may fail.
+///
+/// Returning an error aborts the export, with [`Exporter::run`] reporting it as
+/// [`ExportError::PostprocessorError`]. This may be used to validate notes, for example.
+///
+/// See [`Exporter::add_fallible_postprocessor`].
+pub type FalliblePostprocessor<'f> = dyn Fn(
+        &mut Context,
+        &mut MarkdownEvents<'_>,
+    ) -> std::result::Result<PostprocessorResult, Box<dyn std::error::Error + Send + Sync>>
+    + Send
+    + Sync
+    + 'f;
+
+/// An entry in the chain of postprocessors, which may be either infallible or fallible.
+#[derive(Clone, Copy)]
+enum PostprocessorFn<'a> {
+    Infallible(&'a Postprocessor<'a>),
+    Fallible(&'a FalliblePostprocessor<'a>),
+}
+
+impl PostprocessorFn<'_> {
+    fn call(
+        self,
+        context: &mut Context,
+        events: &mut MarkdownEvents<'_>,
+    ) -> std::result::Result<PostprocessorResult, Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            Self::Infallible(func) => Ok(func(context, events)),
+            Self::Fallible(func) => func(context, events),
+        }
+    }
+}
+
+/// A function which decides whether a note should be exported, based on its [Frontmatter] alone.
+///
+/// See [`Exporter::add_frontmatter_filter`].
+pub type FrontmatterFilter<'f> = dyn Fn(&Frontmatter) -> bool + Send + Sync + 'f;
+
+/// A function which is called with each [`DryRunAction`] when running in
+/// [dry-run mode][Exporter::dry_run].
+pub type DryRunCallback<'f> = dyn Fn(&DryRunAction) + Send + Sync + 'f;
+
+/// A function which transforms the filename of exported files.
+///
+/// See [`Exporter::filename_slugifier`].
+pub type FilenameSlugifier<'f> = dyn Fn(&str) -> String + Send + Sync + 'f;
+
+/// A function which returns the path (relative to the destination) that a note is exported to.
+///
+/// See [`Exporter::destination_mapper`].
+pub type DestinationMapper<'f> = dyn Fn(&Context) -> PathBuf + Send + Sync + 'f;
+
+/// A function which rewrites the complete markdown of an exported note, including its
+/// frontmatter, right before it's written.
+///
+/// See [`Exporter::add_output_filter`].
+pub type OutputFilter<'f> = dyn Fn(&Context, String) -> String + Send + Sync + 'f;
+
+/// A function which is called with [`ProgressEvent`]s as an export progresses.
+///
+/// See [`Exporter::on_progress`].
+pub type ProgressCallback<'f> = dyn Fn(ProgressEvent) + Send + Sync + 'f;
+
+/// A function which runs once after all notes have been exported, for example to write an index
+/// of all notes.
+///
+/// See [`Exporter::add_finalizer`].
+pub type Finalizer<'f> =
+    dyn Fn(&FinalizeContext) -> std::result::Result<(), ExportError> + Send + Sync + 'f;
+
 type Result<T, E = ExportError> = std::result::Result<T, E>;
 
 const PERCENTENCODE_CHARS: &AsciiSet = &CONTROLS.add(b' ').add(b'(').add(b')').add(b'%').add(b'?');
-const NOTE_RECURSION_LIMIT: usize = 10;
+const DEFAULT_EMBED_RECURSION_LIMIT: usize = 10;
+const DEFAULT_DOCS_BASE_PATH: &str = "/docs";
+/// Docusaurus removes number prefixes such as `01-` or `2. ` from file and directory names, which
+/// are commonly used to order docs.
+static DOCUSAURUS_NUMBER_PREFIX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d+\s*[-_.]+\s*(?P<name>[^-_.\s].*)$").unwrap());
+/// References to daily notes, which are named after their date (`2023-01-05`).
+static DAILY_NOTE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+const DEFAULT_NOTE_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+/// The filename which notes exported through [`Exporter::export_str`] are assumed to have.
+const IN_MEMORY_NOTE_FILENAME: &str = "Untitled.md";
+/// The id given to the links which embeds of files other than notes and images (`![[clip.mp4]]`)
+/// are exported as, which tells them apart from regular links for postprocessors such as
+/// [`postprocessors::media_embeds_to_html`]. Ids aren't written for inline links.
+const EMBED_LINK_ID: &str = "obsidian-export:embed";
 
 #[non_exhaustive]
 #[derive(Debug, Snafu)]
@@ -161,6 +291,31 @@ pub enum ExportError {
         source: ignore::Error,
     },
 
+    #[snafu(display("Failed to read ignore patterns from '{}'", path.display()))]
+    /// This occurs when one of the [global ignore files][Exporter::add_ignore_file] can't be read
+    /// or contains invalid patterns.
+    IgnoreFileError {
+        path: PathBuf,
+        source: ignore::Error,
+    },
+
+    #[snafu(display("Invalid passthrough pattern '{}'", pattern))]
+    /// This occurs when a [passthrough pattern][Exporter::add_passthrough_pattern] isn't a valid
+    /// glob.
+    PassthroughPatternError {
+        pattern: String,
+        source: ignore::Error,
+    },
+
+    #[snafu(display(
+        "Symlink loop detected: '{}' points to its own parent directory '{}'",
+        path.display(),
+        ancestor.display()
+    ))]
+    /// This occurs when [following symlinks][WalkOptions::follow_symlinks] and a symlink points to
+    /// a directory which contains it, which would otherwise cause an infinite loop.
+    SymlinkLoop { path: PathBuf, ancestor: PathBuf },
+
     #[snafu(display("Failed to read the mtime of '{}'", path.display()))]
     /// This occurs when a file's modified time cannot be read
     ModTimeReadError {
@@ -175,6 +330,13 @@ pub enum ExportError {
         source: std::io::Error,
     },
 
+    #[snafu(display("Failed to set the permissions of '{}'", path.display()))]
+    /// This occurs when a file's permission bits cannot be copied
+    PermissionsSetError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
     #[snafu(display("No such file or directory: {}", path.display()))]
     /// This occurs when an operation is requested on a file or directory which does not exist.
     PathDoesNotExist { path: PathBuf },
@@ -186,12 +348,20 @@ pub enum ExportError {
     CharacterEncodingError { source: str::Utf8Error },
 
     #[snafu(display("Recursion limit exceeded"))]
-    /// This error occurs when embedded notes are too deeply nested or cause an infinite loop.
+    /// This error occurs when embedded notes are too deeply nested (see
+    /// [`Exporter::embed_recursion_limit`]) or cause an infinite loop.
     ///
     /// When this happens, `file_tree` contains a list of all the files which were processed
     /// leading up to this error.
     RecursionLimitExceeded { file_tree: Vec<PathBuf> },
 
+    #[snafu(display("Found {} unresolved links", links.len()))]
+    /// This occurs when [failing on unresolved links][Exporter::fail_on_unresolved_links] is
+    /// enabled and exported notes contain references which couldn't be resolved.
+    ///
+    /// All notes are exported before this is returned, so `links` includes every unresolved link.
+    UnresolvedLinks { links: Vec<UnresolvedLink> },
+
     #[snafu(display("Failed to export '{}'", path.display()))]
     /// This occurs when a file fails to export successfully.
     FileExportError {
@@ -200,6 +370,13 @@ pub enum ExportError {
         source: Box<ExportError>,
     },
 
+    #[snafu(display("A postprocessor failed on '{}'", path.display()))]
+    /// This occurs when a [`FalliblePostprocessor`] returns an error.
+    PostprocessorError {
+        path: PathBuf,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[snafu(display("Failed to decode YAML frontmatter in '{}'", path.display()))]
     FrontMatterDecodeError {
         path: PathBuf,
@@ -227,71 +404,380 @@ pub enum PostprocessorResult {
     StopAndSkipNote,
 }
 
-#[derive(Clone)]
+/// An action which [`Exporter::run`] would have taken, as reported in
+/// [dry-run mode][Exporter::dry_run].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DryRunAction {
+    /// `destination` does not exist yet and would be created from `source`.
+    Create {
+        source: PathBuf,
+        destination: PathBuf,
+    },
+    /// `destination` already exists and would be overwritten by `source`.
+    Overwrite {
+        source: PathBuf,
+        destination: PathBuf,
+    },
+    /// `source` would not be exported because a [postprocessor][Postprocessor] returned
+    /// [`PostprocessorResult::StopAndSkipNote`].
+    Skip { source: PathBuf },
+}
+
+impl DryRunAction {
+    fn write(source: &Path, destination: &Path) -> Self {
+        let (source, destination) = (source.to_path_buf(), destination.to_path_buf());
+        if destination.exists() {
+            Self::Overwrite {
+                source,
+                destination,
+            }
+        } else {
+            Self::Create {
+                source,
+                destination,
+            }
+        }
+    }
+}
+
+/// The syntax used for links and embeds referring to other notes and files in exported notes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InternalLinkFormat {
+    /// Turn `[[note]]` into a regular markdown link and replace `![[note]]` with the embedded
+    /// content.
+    #[default]
+    Markdown,
+    /// Keep `[[note]]` and `![[note]]` exactly as written, for tools which understand Obsidian's
+    /// syntax themselves.
+    ///
+    /// References are still resolved, so unresolved references are reported and linked
+    /// attachments are exported.
+    Wikilink,
+    /// Like [`InternalLinkFormat::Markdown`], but link to notes using the site-absolute URLs of
+    /// the pages [Docusaurus] generates for them, such as `/docs/folder/note#heading`.
+    ///
+    /// URLs are rooted at the [docs base path][Exporter::docs_base_path]. Path segments have their
+    /// number prefixes removed and are slugified, the extension is dropped, and category index
+    /// notes (`index`, `README` or named after their directory) link to their directory. Heading
+    /// anchors are generated the way Docusaurus does. Links to attachments stay relative.
+    ///
+    /// [Docusaurus]: https://docusaurus.io
+    Docusaurus,
+}
+
+/// How embeds of notes which are excluded from the export are handled.
+///
+/// Notes are excluded when they're ignored (for example through `.export-ignore` or
+/// [`WalkOptions::filter_fn`]) or rejected by a [frontmatter
+/// filter][Exporter::add_frontmatter_filter].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExcludedEmbedBehavior {
+    /// Replace the embed with a link to the note.
+    ///
+    /// As the note itself isn't exported, this link only works when the note is made available
+    /// by other means.
+    LinkTo,
+    /// Leave out the embed entirely.
+    #[default]
+    Omit,
+    /// Embed the content of the note as if it wasn't excluded.
+    Inline,
+}
+
+/// How repeated embeds of the same note (or the same section of a note) within a single exported
+/// note are handled.
+///
+/// See [`Exporter::duplicate_embed_behavior`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateEmbedBehavior {
+    /// Embed the content of the note every time.
+    #[default]
+    Inline,
+    /// Embed the content of the note the first time only, and replace later embeds with a link
+    /// to the note.
+    LinkTo,
+    /// Embed the content of the note the first time only, and leave out later embeds entirely.
+    Omit,
+}
+
+/// How references to notes or files which can't be found are rendered.
+///
+/// See [`Exporter::unresolved_link_style`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnresolvedLinkStyle {
+    /// Render the text of the reference in emphasis (`*Note*`).
+    #[default]
+    Emphasis,
+    /// Render the text of the reference as-is (`Note`).
+    PlainText,
+    /// Wrap the text of the reference in a `<span>` with the given CSS class, such as
+    /// `<span class="broken-link">Note</span>`.
+    Marked {
+        /// The CSS class of the `<span>`.
+        class: String,
+    },
+    /// Follow the text of the reference with the given text, separated by a space, such as
+    /// `Note (missing)`.
+    Suffix {
+        /// The text appended to the reference.
+        text: String,
+    },
+}
+
+impl UnresolvedLinkStyle {
+    /// Return the events rendering the text of an unresolved reference.
+    fn render<'c>(&self, text: String) -> MarkdownEvents<'c> {
+        match self {
+            Self::Emphasis => vec![
+                Event::Start(Tag::Emphasis),
+                Event::Text(CowStr::from(text)),
+                Event::End(TagEnd::Emphasis),
+            ],
+            Self::PlainText => vec![Event::Text(CowStr::from(text))],
+            Self::Marked { class } => vec![
+                Event::InlineHtml(CowStr::from(format!(
+                    "<span class=\"{}\">",
+                    class.replace('&', "&amp;").replace('"', "&quot;")
+                ))),
+                Event::Text(CowStr::from(text)),
+                Event::InlineHtml(CowStr::Borrowed("</span>")),
+            ],
+            Self::Suffix { text: suffix } => {
+                vec![Event::Text(CowStr::from(format!("{text} {suffix}")))]
+            }
+        }
+    }
+}
+
+/// The line endings used in exported notes.
+///
+/// See [`Exporter::line_ending`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LineEnding {
+    /// End lines with `\n`, as used on Unix-like systems.
+    #[default]
+    Lf,
+    /// End lines with `\r\n`, as used on Windows.
+    Crlf,
+    /// Use the line ending which most lines of the source note end with, or `\n` for notes
+    /// without any line breaks.
+    PreserveSource,
+}
+
+impl LineEnding {
+    /// Convert the line endings of `output`, the markdown rendered from the note `source`.
+    fn apply(self, output: String, source: &str) -> String {
+        let crlf = match self {
+            Self::Lf => false,
+            Self::Crlf => true,
+            Self::PreserveSource => {
+                let crlf_count = source.matches("\r\n").count();
+                crlf_count > source.matches('\n').count().saturating_sub(crlf_count)
+            }
+        };
+        if crlf {
+            output.replace("\r\n", "\n").replace('\n', "\r\n")
+        } else {
+            output
+        }
+    }
+}
+
+/// The file format used for notes which are [exported as data
+/// files][Exporter::frontmatter_only_as_data].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DataFormat {
+    /// Write the frontmatter as a YAML document to a `.yaml` file.
+    Yaml,
+    /// Write the frontmatter as a JSON object to a `.json` file.
+    Json,
+}
+
+impl DataFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Progress information emitted during [`Exporter::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// The export has started and `total` files are going to be processed.
+    Started { total: usize },
+    /// The file at `path` has been processed (either exported or skipped).
+    ///
+    /// `index` counts up from 1 to `total`, even when files are processed in parallel.
+    NoteExported { path: PathBuf, index: usize },
+    /// All files have been processed successfully.
+    Finished,
+}
+
+/// A reference to a note or file which couldn't be found in the vault.
+///
+/// See [`Exporter::unresolved_links`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct UnresolvedLink {
+    /// The note containing the reference.
+    pub source: PathBuf,
+    /// The byte offset of the reference (starting at its opening brackets) within `source`.
+    pub offset: usize,
+    /// The file which the reference points to, as written (for example `Note` for
+    /// `[[Note#Heading|Label]]`).
+    pub reference: String,
+}
+
+/// A file produced by [`Exporter::iter_exports`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExportedFile {
+    /// The path the file would be written to, relative to the destination.
+    pub path: PathBuf,
+    /// The rendered markdown of notes, the unmodified content of attachments, or the content of
+    /// files added by postprocessors (see [`Context::add_output_file`]).
+    pub content: Vec<u8>,
+}
+
+/// A note which was written by [`Exporter::run`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ExportedNote {
+    /// The path of the note within the vault.
+    pub source: PathBuf,
+    /// The path the note was written to.
+    pub destination: PathBuf,
+    /// The frontmatter of the note, as left by [postprocessors][Postprocessor].
+    pub frontmatter: Frontmatter,
+}
+
+/// Information about a completed export, which is passed to [finalizers][Finalizer].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct FinalizeContext {
+    /// The root directory of the vault which notes were exported from.
+    pub root: PathBuf,
+    /// The directory which notes were exported to.
+    pub destination: PathBuf,
+    /// All notes which were exported, ordered by their path within the vault.
+    pub notes: Vec<ExportedNote>,
+}
+
+/// The files which a run of the export consists of (see [`Exporter::start_run`]).
+struct RunPlan {
+    /// The files to export, ordered by their path.
+    files: Vec<PathBuf>,
+    /// The files among `files` which match a
+    /// [passthrough pattern][Exporter::add_passthrough_pattern], to be copied as-is.
+    passthrough: HashSet<PathBuf>,
+    /// The destination of the only note to export, when the export starts at a single file.
+    single_destination: Option<PathBuf>,
+}
+
+/// The references of a note, as scanned to find the attachments of skipped notes.
+struct ScannedNote<'a> {
+    /// Whether the note passes the frontmatter filters.
+    exported: bool,
+    attachments: Vec<&'a PathBuf>,
+    embedded_notes: Vec<&'a PathBuf>,
+}
+
+/// State which is collected while an export is running.
+#[derive(Debug, Default)]
+struct RunState {
+    /// Attachments referenced by exported notes, which are to be copied into the assets
+    /// directory (see [`Exporter::assets_dir`]).
+    referenced_attachments: BTreeSet<PathBuf>,
+    /// References which couldn't be resolved (see [`Exporter::unresolved_links`]).
+    unresolved_links: BTreeSet<UnresolvedLink>,
+    /// Notes which have been written so far, to be passed to finalizers.
+    exported_notes: Vec<ExportedNote>,
+    /// The frontmatter defaults which apply to notes within each directory, including those
+    /// inherited from parent directories (see [`Exporter::frontmatter_defaults_file`]).
+    frontmatter_defaults: HashMap<PathBuf, Arc<Frontmatter>>,
+    /// Whether notes consist of nothing but frontmatter, for notes which have been checked so far
+    /// (see [`Exporter::frontmatter_only_as_data`]).
+    data_notes: HashMap<PathBuf, bool>,
+    /// The destinations returned by the [destination mapper][Exporter::destination_mapper], for
+    /// notes which have been mapped so far.
+    mapped_destinations: HashMap<PathBuf, PathBuf>,
+    /// The dates of the first and last commit changing each file, relative to the root of the
+    /// vault, once they've been read (see [`Exporter::git_dates`]).
+    commit_dates: Option<Arc<HashMap<PathBuf, CommitDates>>>,
+    /// The index of each [linked vault][Exporter::add_vault], which is built the first time it's
+    /// needed, or `None` when it couldn't be built.
+    linked_vault_indexes: HashMap<String, Arc<OnceLock<Option<Arc<VaultIndex>>>>>,
+}
+
+/// A vault which notes can link to through `[[name::Note]]` references (see
+/// [`Exporter::add_vault`]).
+#[derive(Debug, Clone)]
+pub(crate) struct LinkedVault {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) base_url: String,
+}
+
 /// Exporter provides the main interface to this library.
 ///
 /// Users are expected to create an Exporter using [`Exporter::new`], optionally followed by
 /// customization using [`Exporter::frontmatter_strategy`] and [`Exporter::walk_options`].
 ///
-/// After that, calling [`Exporter::run`] will start the export process.
+/// After that, calling [`Exporter::run`] will start the export process. To apply the same settings
+/// to several exports, configure an [`ExportConfig`] instead and create an exporter for each of
+/// them using [`ExportConfig::build`].
 pub struct Exporter<'a> {
     root: PathBuf,
     destination: PathBuf,
     start_at: PathBuf,
-    frontmatter_strategy: FrontmatterStrategy,
-    vault_contents: Option<Vec<PathBuf>>,
-    walk_options: WalkOptions<'a>,
-    process_embeds_recursively: bool,
-    preserve_mtime: bool,
-    postprocessors: Vec<&'a Postprocessor<'a>>,
-    embed_postprocessors: Vec<&'a Postprocessor<'a>>,
+    vault_index: Option<VaultIndex>,
+    state: Arc<Mutex<RunState>>,
+    config: ExportConfig<'a>,
 }
 
-impl<'a> fmt::Debug for Exporter<'a> {
+impl fmt::Debug for Exporter<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("WalkOptions")
+        f.debug_struct("Exporter")
             .field("root", &self.root)
             .field("destination", &self.destination)
-            .field("frontmatter_strategy", &self.frontmatter_strategy)
-            .field("vault_contents", &self.vault_contents)
-            .field("walk_options", &self.walk_options)
-            .field(
-                "process_embeds_recursively",
-                &self.process_embeds_recursively,
-            )
-            .field("preserve_mtime", &self.preserve_mtime)
-            .field(
-                "postprocessors",
-                &format!("<{} postprocessors active>", self.postprocessors.len()),
-            )
-            .field(
-                "embed_postprocessors",
-                &format!(
-                    "<{} postprocessors active>",
-                    self.embed_postprocessors.len()
-                ),
-            )
+            .field("start_at", &self.start_at)
+            .field("vault_index", &self.vault_index)
+            .field("config", &self.config)
             .finish()
     }
 }
 
+impl Clone for Exporter<'_> {
+    /// Clone the configuration of the exporter. The state of the last run (such as its
+    /// [unresolved links][Exporter::unresolved_links]) isn't shared with the clone.
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            destination: self.destination.clone(),
+            start_at: self.start_at.clone(),
+            vault_index: self.vault_index.clone(),
+            state: Arc::default(),
+            config: self.config.clone(),
+        }
+    }
+}
+
 impl<'a> Exporter<'a> {
     /// Create a new exporter which reads notes from `root` and exports these to
     /// `destination`.
     #[must_use]
     pub fn new(root: PathBuf, destination: PathBuf) -> Self {
-        Self {
-            start_at: root.clone(),
-            root,
-            destination,
-            frontmatter_strategy: FrontmatterStrategy::Auto,
-            walk_options: WalkOptions::default(),
-            process_embeds_recursively: true,
-            preserve_mtime: false,
-            vault_contents: None,
-            postprocessors: vec![],
-            embed_postprocessors: vec![],
-        }
+        ExportConfig::default().build(root, destination)
     }
 
     /// Set a custom starting point for the export.
@@ -306,13 +792,89 @@ impl<'a> Exporter<'a> {
 
     /// Set the [`WalkOptions`] to be used for this exporter.
     pub fn walk_options(&mut self, options: WalkOptions<'a>) -> &mut Self {
-        self.walk_options = options;
+        self.config.walk_options(options);
+        self
+    }
+
+    /// Set the filename of the ignore files within the vault, such as `.exportignore`.
+    ///
+    /// This takes precedence over [`WalkOptions::ignore_filename`], so it may be called either
+    /// before or after [`Exporter::walk_options`].
+    pub fn ignore_filename(&mut self, filename: String) -> &mut Self {
+        self.config.ignore_filename(filename);
+        self
+    }
+
+    /// Add an ignore file whose patterns apply to the whole vault, for example one which is shared
+    /// between several vaults.
+    ///
+    /// The file follows the same [gitignore](https://git-scm.com/docs/gitignore) syntax as the
+    /// ignore files within the vault, and may be located outside of the vault. Patterns are matched
+    /// relative to the root of the vault. Like git's `core.excludesFile`, these files have a lower
+    /// precedence than the ignore files within the vault, so a pattern such as `!keep.md` within
+    /// the vault includes a file which is ignored by a global ignore file.
+    pub fn add_ignore_file(&mut self, path: PathBuf) -> &mut Self {
+        self.config.add_ignore_file(path);
+        self
+    }
+
+    /// Set whether symbolic links within the vault should be followed.
+    ///
+    /// This is a shorthand for setting [`WalkOptions::follow_symlinks`], so it should be called
+    /// after [`Exporter::walk_options`] (which replaces all walk options).
+    pub fn follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.config.follow_symlinks(follow_symlinks);
         self
     }
 
     /// Set the [`FrontmatterStrategy`] to be used for this exporter.
     pub fn frontmatter_strategy(&mut self, strategy: FrontmatterStrategy) -> &mut Self {
-        self.frontmatter_strategy = strategy;
+        self.config.frontmatter_strategy(strategy);
+        self
+    }
+
+    /// Set how frontmatter is written as YAML.
+    ///
+    /// By default, strings are never wrapped and multi-line strings are written as literal block
+    /// scalars (`|`). See [`YamlOptions`] for the alternatives.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::{Exporter, YamlOptions};
+    /// let mut options = YamlOptions::default();
+    /// options.no_wrap = false;
+    /// options.line_width = 100;
+    ///
+    /// let mut exporter = Exporter::new(PathBuf::from("vault"), PathBuf::from("export"));
+    /// exporter.frontmatter_yaml_options(options);
+    /// ```
+    pub fn frontmatter_yaml_options(&mut self, options: YamlOptions) -> &mut Self {
+        self.config.frontmatter_yaml_options(options);
+        self
+    }
+
+    /// Only keep the given frontmatter keys in exported notes.
+    ///
+    /// When `keys` is non-empty, any frontmatter key which isn't listed is removed. Keys must match
+    /// exactly (matching is case-sensitive).
+    ///
+    /// This is applied after all [postprocessors][Postprocessor] have run, right before a note is
+    /// written, so postprocessors still have access to the full frontmatter. The frontmatter of
+    /// embedded notes is never exported and thus isn't affected.
+    pub fn frontmatter_allowlist(&mut self, keys: Vec<String>) -> &mut Self {
+        self.config.frontmatter_allowlist(keys);
+        self
+    }
+
+    /// Remove the given frontmatter keys from exported notes.
+    ///
+    /// This works the same as [`Exporter::frontmatter_allowlist`], but removes the listed keys
+    /// instead. When both lists are set, a key is only kept when it is present in the allowlist
+    /// and absent from the denylist.
+    pub fn frontmatter_denylist(&mut self, keys: Vec<String>) -> &mut Self {
+        self.config.frontmatter_denylist(keys);
         self
     }
 
@@ -326,7 +888,72 @@ impl<'a> Exporter<'a> {
     /// When `recursive` is false, if a note is encountered for a second time while processing the
     /// original note, instead of embedding it again a link to the note is inserted instead.
     pub fn process_embeds_recursively(&mut self, recursive: bool) -> &mut Self {
-        self.process_embeds_recursively = recursive;
+        self.config.process_embeds_recursively(recursive);
+        self
+    }
+
+    /// Set the maximum nesting depth of embedded notes.
+    ///
+    /// The depth counts the exported note itself, so with a `limit` of 2 a note may embed another
+    /// note, but that note can't embed a third one. Exceeding the limit results in
+    /// [`ExportError::RecursionLimitExceeded`], which lists the chain of embedded notes leading up
+    /// to it.
+    ///
+    /// Defaults to 10.
+    pub fn embed_recursion_limit(&mut self, limit: usize) -> &mut Self {
+        self.config.embed_recursion_limit(limit);
+        self
+    }
+
+    /// Set the maximum size, in bytes, of the markdown inlined by a single embedded note.
+    ///
+    /// Where [`Exporter::embed_recursion_limit`] guards against embeds nesting too deeply, this
+    /// guards against a single embed (including the notes embedded within it) inflating the output.
+    /// Embeds of notes exceeding the limit are replaced with a link to the note, and a warning is
+    /// logged. There's no limit by default.
+    pub fn max_embed_bytes(&mut self, limit: usize) -> &mut Self {
+        self.config.max_embed_bytes(limit);
+        self
+    }
+
+    /// Set how embeds of notes which exist within the vault, but are excluded from the export, are
+    /// handled.
+    ///
+    /// Defaults to [`ExcludedEmbedBehavior::Omit`].
+    pub fn excluded_embed_behavior(&mut self, behavior: ExcludedEmbedBehavior) -> &mut Self {
+        self.config.excluded_embed_behavior(behavior);
+        self
+    }
+
+    /// Set how repeated embeds of the same note within a single exported note are handled.
+    ///
+    /// Embedding a note twice, for example by accident, repeats its content. With
+    /// [`DuplicateEmbedBehavior::LinkTo`] or [`DuplicateEmbedBehavior::Omit`], only the first embed
+    /// of each note (or section of a note) is expanded, counting embeds within embedded notes as
+    /// well. Defaults to [`DuplicateEmbedBehavior::Inline`].
+    pub fn duplicate_embed_behavior(&mut self, behavior: DuplicateEmbedBehavior) -> &mut Self {
+        self.config.duplicate_embed_behavior(behavior);
+        self
+    }
+
+    /// Set whether references which can't be resolved should fail the export.
+    ///
+    /// When `fail` is true, [`Exporter::run`] returns [`ExportError::UnresolvedLinks`] listing
+    /// all [unresolved links][Exporter::unresolved_links] once every note has been exported. By
+    /// default, such references are only reported as warnings.
+    pub fn fail_on_unresolved_links(&mut self, fail: bool) -> &mut Self {
+        self.config.fail_on_unresolved_links(fail);
+        self
+    }
+
+    /// Set how references to notes or files which can't be found are rendered.
+    ///
+    /// Such references can't be turned into links, so only their text is kept. By default, it's
+    /// rendered in emphasis ([`UnresolvedLinkStyle::Emphasis`]). Marking these references more
+    /// visibly, for example with a CSS class ([`UnresolvedLinkStyle::Marked`]), helps to spot
+    /// them on the rendered pages. Unresolved embeds are always left out.
+    pub fn unresolved_link_style(&mut self, style: UnresolvedLinkStyle) -> &mut Self {
+        self.config.unresolved_link_style(style);
         self
     }
 
@@ -335,171 +962,1635 @@ impl<'a> Exporter<'a> {
     /// When `preserve` is true, the modified time of exported files will be set to the modified
     /// time of the source file.
     pub fn preserve_mtime(&mut self, preserve: bool) -> &mut Self {
-        self.preserve_mtime = preserve;
+        self.config.preserve_mtime(preserve);
         self
     }
 
-    /// Append a function to the chain of [postprocessors][Postprocessor] to run on exported
-    /// Obsidian Markdown notes.
-    pub fn add_postprocessor(&mut self, processor: &'a Postprocessor<'_>) -> &mut Self {
-        self.postprocessors.push(processor);
+    /// Set whether the permission bits of copied attachments should be preserved.
+    ///
+    /// When `preserve` is true, the mode of attachments (and [passthrough
+    /// files][Exporter::add_passthrough_pattern]) is set to the mode of the source file, which
+    /// keeps shell scripts and binaries stored in the vault executable. Notes are always written
+    /// with default permissions. This has no effect on platforms other than Unix.
+    pub fn preserve_permissions(&mut self, preserve: bool) -> &mut Self {
+        self.config.preserve_permissions(preserve);
         self
     }
 
-    /// Append a function to the chain of [postprocessors][Postprocessor] for embeds.
-    pub fn add_embed_postprocessor(&mut self, processor: &'a Postprocessor<'_>) -> &mut Self {
-        self.embed_postprocessors.push(processor);
+    /// Set whether `created` and `modified` frontmatter keys are filled in from the git history
+    /// of notes.
+    ///
+    /// When `enabled` is true, `created` is set to the date of the first commit changing a note,
+    /// and `modified` to the date of the last one, both in RFC 3339 format. Keys which a note
+    /// already has are left alone. The history is read by running `git log` once per export, so
+    /// git needs to be installed. When the vault isn't part of a git repository, or for notes which
+    /// haven't been committed, the dates are taken from the filesystem instead.
+    pub fn git_dates(&mut self, enabled: bool) -> &mut Self {
+        self.config.git_dates(enabled);
         self
     }
 
-    /// Export notes using the settings configured on this exporter.
-    pub fn run(&mut self) -> Result<()> {
-        if !self.root.exists() {
-            return Err(ExportError::PathDoesNotExist {
-                path: self.root.clone(),
-            });
-        }
+    /// Only export files which were modified after `cutoff`, for example to publish the changes
+    /// made since the previous export.
+    ///
+    /// Notes which (directly or through other notes) embed a modified note are exported as well,
+    /// as their content changes along with it. Other files are skipped based on their modified
+    /// time alone, so attachments which haven't changed aren't exported again. This doesn't apply
+    /// when exporting a single note.
+    pub fn modified_since(&mut self, cutoff: SystemTime) -> &mut Self {
+        self.config.modified_since(cutoff);
+        self
+    }
 
-        self.vault_contents = Some(vault_contents(
-            self.root.as_path(),
-            self.walk_options.clone(),
-        )?);
+    /// Set whether to perform a dry run.
+    ///
+    /// When `dry_run` is true, [`Exporter::run`] goes through the entire export process (including
+    /// link resolution and postprocessors) but doesn't write anything to the destination. Instead,
+    /// every file that would be created, overwritten or skipped is reported to the function set
+    /// through [`Exporter::on_dry_run_action`].
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.config.dry_run(dry_run);
+        self
+    }
 
-        // When a single file is specified, just need to export that specific file instead of
-        // iterating over all discovered files. This also allows us to accept destination as either
-        // a file or a directory name.
-        if self.root.is_file() || self.start_at.is_file() {
-            let source_filename = self
-                .start_at
-                .file_name()
-                .expect("File without a filename? How is that possible?")
-                .to_string_lossy();
+    /// Set the function which receives the [actions][DryRunAction] that would be taken during a
+    /// [dry run][Exporter::dry_run].
+    ///
+    /// This function may be called from multiple threads concurrently.
+    pub fn on_dry_run_action(&mut self, callback: &'a DryRunCallback<'_>) -> &mut Self {
+        self.config.on_dry_run_action(callback);
+        self
+    }
 
-            let destination = match self.destination.is_dir() {
-                true => self.destination.join(String::from(source_filename)),
-                false => {
-                    let parent = self.destination.parent().unwrap_or(&self.destination);
-                    // Avoid recursively creating self.destination through the call to
-                    // export_note when the parent directory doesn't exist.
-                    if !parent.exists() {
-                        return Err(ExportError::PathDoesNotExist {
-                            path: parent.to_path_buf(),
-                        });
-                    }
-                    self.destination.clone()
-                }
-            };
-            return self.export_note(&self.start_at, &destination);
-        }
+    /// Set a function which is called with [`ProgressEvent`]s while the export is running.
+    ///
+    /// This function may be called from multiple threads, but calls are never concurrent and the
+    /// `index` of [`ProgressEvent::NoteExported`] is guaranteed to increase monotonically.
+    pub fn on_progress(&mut self, callback: &'a ProgressCallback<'_>) -> &mut Self {
+        self.config.on_progress(callback);
+        self
+    }
 
-        if !self.destination.exists() {
-            return Err(ExportError::PathDoesNotExist {
-                path: self.destination.clone(),
-            });
-        }
-        self.vault_contents
-            .as_ref()
-            .unwrap()
-            .clone()
-            .into_par_iter()
-            .filter(|file| file.starts_with(&self.start_at))
-            .try_for_each(|file| {
-                let relative_path = file
-                    .strip_prefix(self.start_at.clone())
-                    .expect("file should always be nested under root")
-                    .to_path_buf();
-                let destination = &self.destination.join(relative_path);
-                self.export_note(&file, destination)
-            })?;
-        Ok(())
+    /// Set a function which transforms the filename of every exported file.
+    ///
+    /// The function receives the original filename (including extension, but without any
+    /// directories) and returns the filename to use in the destination. Links to the file are
+    /// rewritten to use the new filename as well. Directory names are left unchanged.
+    ///
+    /// [`slugify_filename`] may be used to produce lowercase, hyphenated filenames.
+    pub fn filename_slugifier(&mut self, slugifier: &'a FilenameSlugifier<'_>) -> &mut Self {
+        self.config.filename_slugifier(slugifier);
+        self
     }
 
-    fn export_note(&self, src: &Path, dest: &Path) -> Result<()> {
-        match is_markdown_file(src) {
-            true => self.parse_and_export_obsidian_note(src, dest),
-            false => copy_file(src, dest),
-        }
-        .context(FileExportSnafu { path: src })?;
+    /// Set how the paths within references to other notes and files are interpreted.
+    ///
+    /// This should match the "New link format" setting of the vault. Defaults to
+    /// [`LinkResolutionMode::ShortestPath`].
+    pub fn link_resolution_mode(&mut self, mode: LinkResolutionMode) -> &mut Self {
+        self.config.link_resolution_mode(mode);
+        self
+    }
 
-        if self.preserve_mtime {
-            copy_mtime(src, dest).context(FileExportSnafu { path: src })?;
-        }
+    /// Set the syntax used for references to other notes and files in exported notes.
+    ///
+    /// Defaults to [`InternalLinkFormat::Markdown`].
+    pub fn internal_link_format(&mut self, format: InternalLinkFormat) -> &mut Self {
+        self.config.internal_link_format(format);
+        self
+    }
 
-        Ok(())
+    /// Set the URL path which docs are served from, used for links to notes with
+    /// [`InternalLinkFormat::Docusaurus`].
+    ///
+    /// Defaults to `/docs`, matching the `routeBasePath` Docusaurus uses by default.
+    pub fn docs_base_path(&mut self, base_path: String) -> &mut Self {
+        self.config.docs_base_path(base_path);
+        self
     }
 
-    fn parse_and_export_obsidian_note(&self, src: &Path, dest: &Path) -> Result<()> {
-        let mut context = Context::new(src.to_path_buf(), dest.to_path_buf());
+    /// Set the file extension of exported notes, such as `markdown` or `mdx`.
+    ///
+    /// Links to notes are rewritten to use this extension as well. Attachments keep their original
+    /// extension. By default, notes keep the extension they have within the vault.
+    pub fn output_extension(&mut self, extension: String) -> &mut Self {
+        self.config.output_extension(extension);
+        self
+    }
 
-        let (frontmatter, mut markdown_events) = self.parse_obsidian_note(src, &context)?;
+    /// Set the file extensions of files which are treated as notes, such as `md` or `txt`.
+    ///
+    /// Notes are parsed as markdown, processed by postprocessors and can be linked to without their
+    /// extension (`[[Note]]`). All other files are copied as attachments. By default, only `.md`
+    /// and `.markdown` files are treated as notes.
+    pub fn note_extensions(&mut self, extensions: Vec<String>) -> &mut Self {
+        self.config.note_extensions(extensions);
+        self
+    }
+
+    /// Export notes which declare a permalink through the frontmatter `key` (such as `permalink`)
+    /// to the location given by their permalink, instead of their location within the vault.
+    ///
+    /// Permalinks are relative to the destination. A note with `permalink: /blog/my-post/` is
+    /// written to `blog/my-post/index.md`, while `permalink: /blog/my-post` results in
+    /// `blog/my-post.md`. Links to these notes point to their new location. Passing `None`
+    /// (the default) ignores permalinks.
+    pub fn use_permalink_frontmatter(&mut self, key: Option<String>) -> &mut Self {
+        self.config.use_permalink_frontmatter(key);
+        self
+    }
+
+    /// Set a function which determines where each note is exported to, for full control over the
+    /// layout of the destination.
+    ///
+    /// The function is called once for every note with a [`Context`] holding the path of the note
+    /// within the vault, its frontmatter, and the [destination][Context::destination] it would be
+    /// exported to otherwise (which reflects the [filename
+    /// slugifier][Exporter::filename_slugifier], [output extension][Exporter::output_extension]
+    /// and [permalinks][Exporter::use_permalink_frontmatter]). It returns the path of the
+    /// exported note relative to the destination. Links to notes point to their mapped
+    /// location.
+    ///
+    /// # Example
+    ///
+    /// Flatten the vault, exporting all notes into the root of the destination:
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::Exporter;
+    /// # use tempfile::TempDir;
+    /// # let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    /// # let source = PathBuf::from("tests/testdata/input/main-samples");
+    /// # let destination = tmp_dir.path().to_path_buf();
+    /// let mut exporter = Exporter::new(source, destination);
+    /// exporter.destination_mapper(&|context| PathBuf::from(context.destination.file_name().unwrap()));
+    /// exporter.run().unwrap();
+    /// ```
+    pub fn destination_mapper(&mut self, mapper: &'a DestinationMapper<'_>) -> &mut Self {
+        self.config.destination_mapper(mapper);
+        self
+    }
+
+    /// Set a directory (relative to the destination) to collect attachments into.
+    ///
+    /// By default, attachments (any files which aren't notes) are copied to the same relative
+    /// location as they have within the vault. When `assets_dir` is set, only attachments which are
+    /// linked to or embedded by exported notes are copied, and these are placed directly inside
+    /// of `assets_dir` instead. Links to these attachments are rewritten to match.
+    ///
+    /// Attachments with the same filename in different directories of the vault end up at the same
+    /// location, with one overwriting the other.
+    pub fn assets_dir(&mut self, assets_dir: Option<PathBuf>) -> &mut Self {
+        self.config.assets_dir(assets_dir);
+        self
+    }
+
+    /// Set the filename of the index page of folders, such as `index.md` or `_index.md`.
+    ///
+    /// Many static site generators render the file with this name inside of a folder as the page
+    /// for the folder itself. When set, links to a folder (`[[docs]]`) which don't match any note
+    /// point at `docs/<filename>` instead. Notes named after the folder containing them
+    /// (`docs/docs.md`, often used as "folder notes") are exported as that folder's index page,
+    /// and links to them are rewritten to match. A folder note is exported as-is when its folder
+    /// already contains a file named `filename`, which is reported with a warning.
+    pub fn index_filename(&mut self, filename: String) -> &mut Self {
+        self.config.index_filename(filename);
+        self
+    }
+
+    /// Add another vault, which notes can link to through references prefixed with its `name`,
+    /// such as `[[name::Some Note]]`.
+    ///
+    /// This is meant for exporting several vaults into a single site. Such references are resolved
+    /// within the vault at `path` and turned into links to `base_url` followed by the path of the
+    /// note within that vault (`https://example.com/other/Some%20Note.md`), taking the [output
+    /// extension][Exporter::output_extension] and [filename
+    /// slugifier][Exporter::filename_slugifier] into account. Embeds of notes in other vaults are
+    /// exported as links as well. References without a prefix are resolved within the vault being
+    /// exported, like before.
+    ///
+    /// Linked vaults are scanned with the same [walk options][Exporter::walk_options] and [ignore
+    /// filename][Exporter::ignore_filename] as the vault being exported, so notes which these
+    /// leave out can't be linked to. [Ignore files][Exporter::add_ignore_file] only apply to the
+    /// vault being exported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::Exporter;
+    /// let mut exporter = Exporter::new(
+    ///     PathBuf::from("/path/to/vault"),
+    ///     PathBuf::from("/path/to/export/main"),
+    /// );
+    /// exporter.add_vault(
+    ///     "Work".to_owned(),
+    ///     PathBuf::from("/path/to/work-vault"),
+    ///     "https://example.com/work/".to_owned(),
+    /// );
+    /// exporter.run()?;
+    /// # Ok::<(), obsidian_export::ExportError>(())
+    /// ```
+    pub fn add_vault(&mut self, name: String, path: PathBuf, base_url: String) -> &mut Self {
+        self.config.add_vault(name, path, base_url);
+        self
+    }
+
+    /// Append a function to the chain of [postprocessors][Postprocessor] to run on exported
+    /// Obsidian Markdown notes.
+    pub fn add_postprocessor(&mut self, processor: &'a Postprocessor<'_>) -> &mut Self {
+        self.config.add_postprocessor(processor);
+        self
+    }
+
+    /// Append a [fallible postprocessor][FalliblePostprocessor] to the chain of
+    /// [postprocessors][Postprocessor] to run on exported Obsidian Markdown notes.
+    ///
+    /// Fallible and regular postprocessors run in the order in which they were added.
+    pub fn add_fallible_postprocessor(
+        &mut self,
+        processor: &'a FalliblePostprocessor<'_>,
+    ) -> &mut Self {
+        self.config.add_fallible_postprocessor(processor);
+        self
+    }
+
+    /// Append a function to the chain of [postprocessors][Postprocessor] for embeds.
+    pub fn add_embed_postprocessor(&mut self, processor: &'a Postprocessor<'_>) -> &mut Self {
+        self.config.add_embed_postprocessor(processor);
+        self
+    }
+
+    /// Append a function to the list of [frontmatter filters][FrontmatterFilter].
+    ///
+    /// Frontmatter filters are called with the frontmatter of each note right after it has been
+    /// read, before the rest of the note is parsed. When any filter returns `false` the note is
+    /// skipped, without expanding its embeds or running [postprocessors][Postprocessor] on it.
+    /// This makes filters a cheaper alternative to postprocessors which return
+    /// [`PostprocessorResult::StopAndSkipNote`] based on frontmatter alone.
+    ///
+    /// Embeds of notes which are rejected by a filter are handled according to
+    /// [`Exporter::excluded_embed_behavior`].
+    pub fn add_frontmatter_filter(&mut self, filter: &'a FrontmatterFilter<'_>) -> &mut Self {
+        self.config.add_frontmatter_filter(filter);
+        self
+    }
+
+    /// Only export notes whose frontmatter matches `query`, for example to export the subset of a
+    /// vault making up a blog:
+    ///
+    /// ```
+    /// # use obsidian_export::Exporter;
+    /// # use serde_yaml::Value;
+    /// # let mut exporter = Exporter::new("vault".into(), "out".into());
+    /// exporter.frontmatter_query(&|frontmatter| {
+    ///     frontmatter.get("category") == Some(&Value::from("blog"))
+    ///         && frontmatter.get("draft") != Some(&Value::from(true))
+    /// });
+    /// ```
+    ///
+    /// Like [frontmatter filters][Exporter::add_frontmatter_filter], the query is evaluated before
+    /// the rest of a note is parsed. Unlike these, attachments which are only referenced by notes
+    /// that don't match the query aren't copied to the destination. Setting a new query replaces
+    /// the previous one.
+    pub fn frontmatter_query(&mut self, query: &'a FrontmatterFilter<'_>) -> &mut Self {
+        self.config.frontmatter_query(query);
+        self
+    }
+
+    /// Skip notes which opt out of being exported through their frontmatter, using either
+    /// `export: false` or `publish: false`.
+    ///
+    /// These notes are treated like notes excluded by a
+    /// [frontmatter filter][Exporter::add_frontmatter_filter]. In addition, attachments which are
+    /// only referenced by skipped notes aren't copied to the destination.
+    ///
+    /// This is disabled by default.
+    pub fn respect_export_frontmatter(&mut self, respect: bool) -> &mut Self {
+        self.config.respect_export_frontmatter(respect);
+        self
+    }
+
+    /// Resolve references to daily notes (`[[2023-01-05]]`) within `folder` first, such as
+    /// `Journal`, before falling back to the [`LinkResolutionMode`].
+    ///
+    /// References to daily notes consist of just a date in the `YYYY-MM-DD` format, which is the
+    /// default format of Obsidian's daily notes. The folder is relative to the root of the vault.
+    /// This avoids such references resolving to a different note which happens to share the same
+    /// name elsewhere in the vault.
+    pub fn daily_note_folder(&mut self, folder: PathBuf) -> &mut Self {
+        self.config.daily_note_folder(folder);
+        self
+    }
+
+    /// Resolve `[[links]]` within frontmatter values, such as `related: "[[Other Note]]"`.
+    ///
+    /// Links within strings are rewritten according to the [`InternalLinkFormat`], including
+    /// strings nested within lists and mappings. As with links in the body of a note, links which
+    /// can't be resolved are reported and replaced with their (emphasized) text.
+    ///
+    /// This is disabled by default.
+    pub fn resolve_frontmatter_links(&mut self, resolve: bool) -> &mut Self {
+        self.config.resolve_frontmatter_links(resolve);
+        self
+    }
+
+    /// Merge the frontmatter defaults read from files named `filename` (such as `_defaults.yaml`)
+    /// into the frontmatter of every note in the same directory and its subdirectories.
+    ///
+    /// A defaults file contains a YAML mapping, like the frontmatter of a note. The keys of the
+    /// note take precedence over its defaults, and the defaults of a directory take precedence
+    /// over those of its parent directories. Mappings present in both are merged recursively,
+    /// while lists are handled as set through [`Exporter::frontmatter_list_merge`]. Defaults are
+    /// merged right after the frontmatter of a note has been read, so they're visible to
+    /// [frontmatter filters][Exporter::add_frontmatter_filter] and
+    /// [postprocessors][Postprocessor]. Defaults files themselves aren't exported.
+    pub fn frontmatter_defaults_file(&mut self, filename: String) -> &mut Self {
+        self.config.frontmatter_defaults_file(filename);
+        self
+    }
+
+    /// Set how a list under the frontmatter `key` of a note is combined with the list under the
+    /// same key in its [frontmatter defaults][Exporter::frontmatter_defaults_file], for example to
+    /// add default `tags` to the tags of a note.
+    ///
+    /// Defaults to [`ListMerge::Replace`] for all keys.
+    pub fn frontmatter_list_merge(&mut self, key: String, merge: ListMerge) -> &mut Self {
+        self.config.frontmatter_list_merge(key, merge);
+        self
+    }
+
+    /// Export notes which consist of nothing but frontmatter as data files in the given `format`
+    /// instead of as markdown, for example to feed a static site generator's data directory.
+    ///
+    /// The data file keeps the name of the note and replaces its extension with that of the
+    /// format, so `Author.md` becomes `Author.json`. It contains the frontmatter of the note after
+    /// [postprocessors][Postprocessor] and [`Exporter::frontmatter_allowlist`] have been applied.
+    /// As data files aren't pages, links to such notes are replaced with their plain text.
+    ///
+    /// This is disabled (`None`) by default.
+    pub fn frontmatter_only_as_data(&mut self, format: Option<DataFormat>) -> &mut Self {
+        self.config.frontmatter_only_as_data(format);
+        self
+    }
+
+    /// Return the references to notes and files which couldn't be found during the last call to
+    /// [`Exporter::run`], ordered by the note containing them and their position within it.
+    ///
+    /// References within embedded notes are reported with the embedded note as their source.
+    #[must_use]
+    pub fn unresolved_links(&self) -> Vec<UnresolvedLink> {
+        self.state
+            .lock()
+            .expect("run state mutex poisoned")
+            .unresolved_links
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Copy files matching `pattern` (such as `CNAME`, `.nojekyll` or `static/**/*.html`) to the
+    /// destination verbatim, keeping their path within the vault.
+    ///
+    /// Patterns follow the [gitignore](https://git-scm.com/docs/gitignore) syntax and are relative
+    /// to the root of the vault. Matching files take precedence over all other rules: they're
+    /// copied even when they're hidden or excluded through ignore files, git's ignore rules or
+    /// [`WalkOptions::filter_fn`], and notes among them are copied as-is instead of being
+    /// processed. They're also copied when an [assets directory][Exporter::assets_dir] is used.
+    /// [`Exporter::iter_exports`] and [`Exporter::to_writer`] yield these files as-is as well.
+    pub fn add_passthrough_pattern(&mut self, pattern: &str) -> &mut Self {
+        self.config.add_passthrough_pattern(pattern);
+        self
+    }
+
+    /// Resolve standard markdown links to other files within the vault, such as
+    /// `[text](../Other%20Note.md)`, the same way as `[[wikilinks]]`.
+    ///
+    /// Links are resolved relative to the note containing them, falling back to the
+    /// [`LinkResolutionMode`]. Their destination is rewritten to where the file is exported to, so
+    /// they keep working with a [filename slugifier][Exporter::filename_slugifier] or an [output
+    /// extension][Exporter::output_extension]. Links which can't be resolved are left as-is and
+    /// reported like other [unresolved links][Exporter::unresolved_links]. Links to external URLs
+    /// and headings within the same note are never changed.
+    ///
+    /// This is disabled by default, leaving markdown links untouched.
+    pub fn resolve_relative_links(&mut self, resolve: bool) -> &mut Self {
+        self.config.resolve_relative_links(resolve);
+        self
+    }
+
+    /// Use the filename stem as alt text for images embedded without alt text.
+    ///
+    /// Image embeds are exported as standard markdown images, using the reference as alt text by
+    /// default: `![[media/image.png]]` becomes `![media/image.png](media/image.png)`. When
+    /// `enabled` is true, the stem of the embedded file is used instead, resulting in
+    /// `![image](media/image.png)`. Alt text given explicitly (`![[image.png|A description]]`) is
+    /// always kept.
+    pub fn image_alt_from_filename(&mut self, enabled: bool) -> &mut Self {
+        self.config.image_alt_from_filename(enabled);
+        self
+    }
+
+    /// Set the [`MarkdownSerializer`] which turns the [`MarkdownEvents`] of each note into markdown
+    /// text, after all [postprocessors][Postprocessor] have run.
+    ///
+    /// This controls formatting choices such as the characters used for bullets and emphasis (see
+    /// [`CmarkSerializer`]), or, through a custom implementation, the style of headings and links.
+    /// Links within frontmatter are always formatted using the default serializer. Defaults to
+    /// [`CmarkSerializer::default`].
+    pub fn set_serializer(&mut self, serializer: Box<dyn MarkdownSerializer>) -> &mut Self {
+        self.config.set_serializer(serializer);
+        self
+    }
+
+    /// Set the line endings used in exported notes.
+    ///
+    /// Notes are written with `\n` line endings by default ([`LineEnding::Lf`]). With
+    /// [`LineEnding::Crlf`], lines end with `\r\n` instead, while [`LineEnding::PreserveSource`]
+    /// keeps the line ending which most lines of each source note use. This also applies to the
+    /// frontmatter of notes, but not to attachments, which are always copied as-is.
+    pub fn line_ending(&mut self, line_ending: LineEnding) -> &mut Self {
+        self.config.line_ending(line_ending);
+        self
+    }
+
+    /// Write a `sitemap.xml` to the root of the destination, listing the URL of each exported note
+    /// below `base_url` (such as `https://example.com/notes`).
+    ///
+    /// URLs are derived from the path each note was exported to, so they reflect
+    /// [slugified filenames][Exporter::filename_slugifier] and
+    /// [permalinks][Exporter::use_permalink_frontmatter], and drop the file extension as static
+    /// site generators usually serve pages from these. Notes which are skipped, for example by
+    /// [frontmatter filters][Exporter::add_frontmatter_filter], aren't listed. The sitemap is
+    /// written before [finalizers][Exporter::add_finalizer] run, and not at all during a [dry
+    /// run][Exporter::dry_run]. Passing `None` (the default) disables the sitemap.
+    pub fn write_sitemap(&mut self, base_url: Option<String>) -> &mut Self {
+        self.config.write_sitemap(base_url);
+        self
+    }
+
+    /// Append a function which rewrites the complete markdown of each exported note.
+    ///
+    /// Output filters run after all [postprocessors][Postprocessor], once a note (including its
+    /// frontmatter) has been serialized to markdown, and before it's written to the destination.
+    /// This makes them suitable for fixups which are easiest to do on the final text, such as a
+    /// regex replacement across the whole file. They run in the order they were added, each
+    /// receiving the output of the previous one, and always see `\n` line endings, as the
+    /// [line ending][Exporter::line_ending] is applied afterwards. Output filters don't apply to
+    /// notes [exported as data files][Exporter::frontmatter_only_as_data].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::Exporter;
+    /// # use tempfile::TempDir;
+    /// # let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    /// # let source = PathBuf::from("tests/testdata/input/main-samples");
+    /// # let destination = tmp_dir.path().to_path_buf();
+    /// let mut exporter = Exporter::new(source, destination);
+    /// exporter.add_output_filter(&|_context, output| output.replace("TODO", "FIXME"));
+    /// exporter.run().unwrap();
+    /// ```
+    pub fn add_output_filter(&mut self, filter: &'a OutputFilter<'_>) -> &mut Self {
+        self.config.add_output_filter(filter);
+        self
+    }
+
+    /// Append a function which runs once after all notes have been exported.
+    ///
+    /// Finalizers receive a [`FinalizeContext`] describing the exported notes, and may write
+    /// additional files to the destination, such as an index page. They run in the order they
+    /// were added, and an error returned by one of them aborts the export. Finalizers don't run
+    /// during a [dry run][Exporter::dry_run].
+    pub fn add_finalizer(&mut self, finalizer: &'a Finalizer<'_>) -> &mut Self {
+        self.config.add_finalizer(finalizer);
+        self
+    }
+
+    /// Export notes using the settings configured on this exporter.
+    pub fn run(&mut self) -> Result<()> {
+        let plan = self.start_run()?;
+        self.report_progress(ProgressEvent::Started {
+            total: plan.files.len(),
+        });
+
+        let processed = Mutex::new(0_usize);
+        plan.files.par_iter().try_for_each(|file| {
+            self.export_planned_file(&plan, file)?;
+
+            // The lock is held while the callback runs to guarantee indexes are reported in order.
+            let mut processed = processed.lock().expect("progress mutex poisoned");
+            *processed = processed.saturating_add(1);
+            self.report_progress(ProgressEvent::NoteExported {
+                path: file.clone(),
+                index: *processed,
+            });
+            drop(processed);
+            Ok(())
+        })?;
+        self.finish_run()
+    }
+
+    /// Export notes the same way as [`Exporter::run`] from within an async runtime.
+    ///
+    /// Notes are exported one at a time, and the returned future yields to the runtime after each
+    /// of them. Dropping the future therefore cancels the export before the next note, without
+    /// running finalizers. On tokio's multi-threaded runtime, each note is exported through
+    /// [`tokio::task::block_in_place`], so the runtime moves other tasks to its remaining worker
+    /// threads in the meantime. On the current-thread runtime, other tasks run in between notes.
+    /// Other than that, the output is identical to that of [`Exporter::run`].
+    ///
+    /// This requires the `async` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::Exporter;
+    /// # async fn export() -> Result<(), obsidian_export::ExportError> {
+    /// let mut exporter = Exporter::new(
+    ///     PathBuf::from("/path/to/vault"),
+    ///     PathBuf::from("/path/to/export"),
+    /// );
+    /// exporter.run_async().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn run_async(&mut self) -> Result<()> {
+        let plan = block_in_place(|| self.start_run())?;
+        self.report_progress(ProgressEvent::Started {
+            total: plan.files.len(),
+        });
+
+        for (position, file) in plan.files.iter().enumerate() {
+            block_in_place(|| self.export_planned_file(&plan, file))?;
+            self.report_progress(ProgressEvent::NoteExported {
+                path: file.clone(),
+                index: position.saturating_add(1),
+            });
+            tokio::task::yield_now().await;
+        }
+        block_in_place(|| self.finish_run())
+    }
+
+    /// Prepare a run of the export, returning the files to export.
+    fn start_run(&mut self) -> Result<RunPlan> {
+        if !self.root.exists() {
+            return Err(ExportError::PathDoesNotExist {
+                path: self.root.clone(),
+            });
+        }
+
+        self.vault_index = Some(self.build_index()?);
+        *self.state.lock().expect("run state mutex poisoned") = RunState::default();
+
+        // When a single file is specified, just need to export that specific file instead of
+        // iterating over all discovered files. This also allows us to accept destination as either
+        // a file or a directory name.
+        if self.root.is_file() || self.start_at.is_file() {
+            let source_filename = self
+                .start_at
+                .file_name()
+                .expect("File without a filename? How is that possible?")
+                .to_string_lossy();
+
+            let destination = if self.destination.is_dir() {
+                self.destination
+                    .join(self.output_path(Path::new(source_filename.as_ref())))
+            } else {
+                let parent = self.destination.parent().unwrap_or(&self.destination);
+                // Avoid recursively creating self.destination through the call to
+                // export_note when the parent directory doesn't exist.
+                if !parent.exists() {
+                    return Err(ExportError::PathDoesNotExist {
+                        path: parent.to_path_buf(),
+                    });
+                }
+                self.destination.clone()
+            };
+            return Ok(RunPlan {
+                files: vec![self.start_at.clone()],
+                passthrough: HashSet::new(),
+                single_destination: Some(destination),
+            });
+        }
+
+        if !self.destination.exists() {
+            return Err(ExportError::PathDoesNotExist {
+                path: self.destination.clone(),
+            });
+        }
+        let vault_index = self.vault_index.as_ref().unwrap();
+        self.warn_index_page_conflicts(vault_index);
+        let skipped_files = self.skipped_files(vault_index)?;
+        let passthrough = self.passthrough_files()?;
+        let files: BTreeSet<PathBuf> = vault_index
+            .contents()
+            .iter()
+            .filter(|file| self.is_exported_directly(file, &skipped_files))
+            .chain(&passthrough)
+            .cloned()
+            .collect();
+        Ok(RunPlan {
+            files: files.into_iter().collect(),
+            passthrough,
+            single_destination: None,
+        })
+    }
+
+    /// Export `file`, which is one of the files of `plan`.
+    fn export_planned_file(&self, plan: &RunPlan, file: &Path) -> Result<()> {
+        if let Some(destination) = &plan.single_destination {
+            return self.export_note(file, destination);
+        }
+        let relative_path = file
+            .strip_prefix(&self.start_at)
+            .expect("file should always be nested under root");
+        if plan.passthrough.contains(file) {
+            self.copy_verbatim(file, &self.destination.join(relative_path))
+        } else {
+            let vault_index = self.vault_index.as_ref().unwrap();
+            let destination =
+                self.destination
+                    .join(self.destination_path(file, relative_path, vault_index));
+            self.export_note(file, &destination)
+        }
+    }
+
+    /// Complete a run once all of its files have been exported.
+    fn finish_run(&self) -> Result<()> {
+        self.copy_referenced_attachments()?;
+        self.run_finalizers()?;
+        self.check_unresolved_links()?;
+        self.report_progress(ProgressEvent::Finished);
+        Ok(())
+    }
+
+    /// Whether exported notes need to be collected for [`Exporter::run_finalizers`].
+    fn collects_exported_notes(&self) -> bool {
+        !self.config.finalizers.is_empty() || self.config.sitemap_base_url.is_some()
+    }
+
+    fn run_finalizers(&self) -> Result<()> {
+        if self.config.dry_run || !self.collects_exported_notes() {
+            return Ok(());
+        }
+        let mut notes = mem::take(
+            &mut self
+                .state
+                .lock()
+                .expect("run state mutex poisoned")
+                .exported_notes,
+        );
+        notes.sort_by(|a, b| a.source.cmp(&b.source));
+        let context = FinalizeContext {
+            root: self.vault_root(),
+            destination: self.destination_root(),
+            notes,
+        };
+        if let Some(base_url) = &self.config.sitemap_base_url {
+            finalizers::write_sitemap(&context, base_url)?;
+        }
+        self.config
+            .finalizers
+            .iter()
+            .try_for_each(|finalizer| finalizer(&context))
+    }
+
+    fn check_unresolved_links(&self) -> Result<()> {
+        if !self.config.fail_on_unresolved_links {
+            return Ok(());
+        }
+        let links = self.unresolved_links();
+        if links.is_empty() {
+            Ok(())
+        } else {
+            Err(ExportError::UnresolvedLinks { links })
+        }
+    }
+
+    /// Build the [`VaultIndex`] which [`Exporter::run`] uses to resolve references, without
+    /// exporting anything.
+    ///
+    /// The index takes the same files into account as an export would (following ignore files,
+    /// [note extensions][Exporter::note_extensions] and
+    /// [permalinks][Exporter::use_permalink_frontmatter]), so it can be used to check links ahead
+    /// of time, to build navigation, or be passed to [`Exporter::export_str`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::Exporter;
+    /// let exporter = Exporter::new(
+    ///     PathBuf::from("/path/to/vault"),
+    ///     PathBuf::from("/path/to/export"),
+    /// );
+    /// let vault_index = exporter.build_index()?;
+    /// for note in vault_index.all_notes() {
+    ///     println!("{}", note.display());
+    /// }
+    /// let target = vault_index.lookup("Some Note");
+    /// # Ok::<(), obsidian_export::ExportError>(())
+    /// ```
+    pub fn build_index(&self) -> Result<VaultIndex> {
+        let vault_index = VaultIndex::with_ignore_files(
+            self.root.as_path(),
+            self.vault_walk_options(),
+            self.config.note_extensions.clone(),
+            &self.config.ignore_files,
+        )?;
+        match &self.config.permalink_key {
+            Some(key) => vault_index.with_permalinks(key),
+            None => Ok(vault_index),
+        }
+    }
+
+    /// Return the [walk options][Exporter::walk_options] to scan vaults with, taking the
+    /// [ignore filename][Exporter::ignore_filename] into account.
+    fn vault_walk_options(&self) -> WalkOptions<'_> {
+        WalkOptions {
+            ignore_filename: self
+                .config
+                .ignore_filename
+                .as_deref()
+                .unwrap_or(self.config.walk_options.ignore_filename),
+            ..self.config.walk_options.clone()
+        }
+    }
+
+    /// Export a single note from an in-memory string, returning the exported markdown.
+    ///
+    /// References to other notes and attachments are resolved through `vault_index`, treating the
+    /// note as if it were a file named `Untitled.md` at the root of the vault. Postprocessors and
+    /// frontmatter settings are applied the same way as with [`Exporter::run`], but the result is
+    /// returned instead of being written to the destination.
+    ///
+    /// If a postprocessor returns [`PostprocessorResult::StopAndSkipNote`], an empty string is
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::{Exporter, VaultIndex, WalkOptions};
+    /// let vault = PathBuf::from("/path/to/vault");
+    /// let vault_index = VaultIndex::new(&vault, WalkOptions::default())?;
+    /// let exporter = Exporter::new(vault, PathBuf::from("/path/to/export"));
+    ///
+    /// let markdown = exporter.export_str("See [[Some Note]]", &vault_index)?;
+    /// # Ok::<(), obsidian_export::ExportError>(())
+    /// ```
+    pub fn export_str(&self, source: &str, vault_index: &VaultIndex) -> Result<String> {
+        let context = Context::new(
+            vault_index.root().join(IN_MEMORY_NOTE_FILENAME),
+            self.destination.join(IN_MEMORY_NOTE_FILENAME),
+        )
+        .with_vault_root(vault_index.root().to_path_buf())
+        .with_internal_link_format(self.config.internal_link_format)
+        .with_destination_root(self.destination.clone());
+        let output = self
+            .process_note(source, context, vault_index)?
+            .map(|(_context, output)| output);
+        Ok(output.unwrap_or_default())
+    }
+
+    /// Export the single note at `path` to `w` instead of writing it to the destination.
+    ///
+    /// `path` is either absolute or relative to the root of the vault. The note is rendered the
+    /// same way as with [`Exporter::run`], with links made relative to where the note would have
+    /// been exported, which makes it possible to pipe a note into other tools without going through
+    /// a temporary file. Nothing is written when a postprocessor requested the note to be skipped,
+    /// and files which postprocessors add (see [`Context::add_output_file`]) are discarded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # use std::path::{Path, PathBuf};
+    /// # use obsidian_export::Exporter;
+    /// let exporter = Exporter::new(
+    ///     PathBuf::from("/path/to/vault"),
+    ///     PathBuf::from("/path/to/export"),
+    /// );
+    /// exporter.to_writer(Path::new("Some Note.md"), &mut io::stdout())?;
+    /// # Ok::<(), obsidian_export::ExportError>(())
+    /// ```
+    pub fn to_writer(&self, path: &Path, w: &mut dyn Write) -> Result<()> {
+        let src = self.vault_root().join(path);
+        if !src.is_file() {
+            return Err(ExportError::PathDoesNotExist { path: src });
+        }
+        let passthrough = self.is_passthrough(&src)?;
+        let vault_index = self.build_index()?;
+        let relative_path = match src.strip_prefix(&self.start_at) {
+            Ok(relative) if relative != Path::new("") => relative.to_path_buf(),
+            _ => PathBuf::from(src.file_name().unwrap_or_default()),
+        };
+        let dest = self.destination_path(&src, &relative_path, &vault_index);
+        let Some(file) = self
+            .render_file(&src, &dest, &vault_index, passthrough)?
+            .into_iter()
+            .next()
+        else {
+            info!("Skipping note\n\tSource: '{}'", src.display());
+            return Ok(());
+        };
+        w.write_all(&file.content)
+            .and_then(|()| w.flush())
+            .context(WriteSnafu { path: dest })
+            .context(FileExportSnafu { path: &src })
+    }
+
+    /// Export notes the same way as [`Exporter::run`], but yield each exported file instead of
+    /// writing it to the destination.
+    ///
+    /// Notes are parsed, postprocessed and rendered as they are consumed from the iterator, each
+    /// followed by the files its postprocessors added, while attachments are read as-is. When an
+    /// [assets directory][Exporter::assets_dir] is set, the attachments referenced by notes are
+    /// yielded after all notes, with paths inside the assets directory. Finalizers don't run,
+    /// and the destination isn't touched, although links are still made relative to it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::path::PathBuf;
+    /// # use obsidian_export::Exporter;
+    /// let exporter = Exporter::new(
+    ///     PathBuf::from("/path/to/vault"),
+    ///     PathBuf::from("/path/to/export"),
+    /// );
+    /// for file in exporter.iter_exports() {
+    ///     let file = file?;
+    ///     println!("{}: {} bytes", file.path.display(), file.content.len());
+    /// }
+    /// # Ok::<(), obsidian_export::ExportError>(())
+    /// ```
+    pub fn iter_exports(&self) -> impl Iterator<Item = Result<ExportedFile>> + '_ {
+        *self.state.lock().expect("run state mutex poisoned") = RunState::default();
+        let prepared = self.build_index().and_then(|vault_index| {
+            let skipped_files = self.skipped_files(&vault_index)?;
+            let passthrough = self.passthrough_files()?;
+            let files: BTreeSet<PathBuf> = vault_index
+                .contents()
+                .iter()
+                .filter(|file| self.is_exported_directly(file, &skipped_files))
+                .chain(&passthrough)
+                .cloned()
+                .collect();
+            Ok((
+                vault_index,
+                files.into_iter().collect::<Vec<_>>(),
+                passthrough,
+            ))
+        });
+        let (prepared, error) = match prepared {
+            Ok(prepared) => (Some(prepared), None),
+            Err(err) => (None, Some(err)),
+        };
+        let file_count = prepared.as_ref().map_or(0, |(_, files, _)| files.len());
+
+        let notes = (0..file_count)
+            .filter_map(move |position| {
+                let (vault_index, files, passthrough) = prepared.as_ref()?;
+                let file = files.get(position)?;
+                let relative_path = match file.strip_prefix(&self.start_at) {
+                    Ok(path) if path != Path::new("") => path.to_path_buf(),
+                    // A single note is exported when the start is a file.
+                    _ => PathBuf::from(file.file_name()?),
+                };
+                let is_passthrough = passthrough.contains(file);
+                let path = if is_passthrough {
+                    relative_path
+                } else {
+                    self.destination_path(file, &relative_path, vault_index)
+                };
+                Some(self.render_file(file, &path, vault_index, is_passthrough))
+            })
+            .flat_map(|rendered| match rendered {
+                Ok(files) => files.into_iter().map(Ok).collect(),
+                Err(err) => vec![Err(err)],
+            });
+        let attachments = iter::once_with(move || self.take_referenced_attachments())
+            .flatten()
+            .map(move |attachment| {
+                let path = self.assets_path(&attachment);
+                fs::read(&attachment)
+                    .context(ReadSnafu { path: &attachment })
+                    .map(|content| ExportedFile { path, content })
+                    .context(FileExportSnafu { path: &attachment })
+            });
+        error.map(Err).into_iter().chain(notes).chain(attachments)
+    }
+
+    /// Render the file at `src`, which is exported to `path` (relative to the destination).
+    ///
+    /// Notes are read as-is, like attachments, when `passthrough` is set because they match a
+    /// [passthrough pattern][Exporter::add_passthrough_pattern]. Returns the rendered file followed
+    /// by the files which postprocessors added to its export (see [`Context::add_output_file`]), or
+    /// nothing when a postprocessor requested the note to be skipped.
+    fn render_file(
+        &self,
+        src: &Path,
+        path: &Path,
+        vault_index: &VaultIndex,
+        passthrough: bool,
+    ) -> Result<Vec<ExportedFile>> {
+        let rendered = if self.is_note(src) && !passthrough {
+            fs::read_to_string(src)
+                .context(ReadSnafu { path: src })
+                .and_then(|content| {
+                    let context = Context::new(src.to_path_buf(), self.destination.join(path))
+                        .with_vault_root(self.vault_root())
+                        .with_destination_root(self.destination_root())
+                        .with_internal_link_format(self.config.internal_link_format);
+                    self.process_note(&content, context, vault_index)
+                })
+                .map(|processed| {
+                    processed
+                        .map(|(context, output)| (output.into_bytes(), context.take_output_files()))
+                })
+        } else {
+            fs::read(src)
+                .context(ReadSnafu { path: src })
+                .map(|content| Some((content, Vec::new())))
+        };
+        let Some((content, output_files)) = rendered.context(FileExportSnafu { path: src })? else {
+            return Ok(Vec::new());
+        };
+        let file = ExportedFile {
+            path: path.to_path_buf(),
+            content,
+        };
+        Ok(iter::once(file).chain(output_files).collect())
+    }
+
+    /// Copy the attachments referenced by exported notes into the
+    /// [assets directory][Exporter::assets_dir], if one is set.
+    fn copy_referenced_attachments(&self) -> Result<()> {
+        if self.config.assets_dir.is_none() {
+            return Ok(());
+        }
+        self.take_referenced_attachments()
+            .into_par_iter()
+            .try_for_each(|attachment| {
+                let destination = self.destination_root().join(self.assets_path(&attachment));
+                self.export_note(&attachment, &destination)
+            })
+    }
+
+    /// Take the attachments referenced by the notes exported so far out of the run state.
+    fn take_referenced_attachments(&self) -> BTreeSet<PathBuf> {
+        mem::take(
+            &mut self
+                .state
+                .lock()
+                .expect("run state mutex poisoned")
+                .referenced_attachments,
+        )
+    }
+
+    /// Return the path that `attachment` is exported to within the
+    /// [assets directory][Exporter::assets_dir], relative to the destination.
+    fn assets_path(&self, attachment: &Path) -> PathBuf {
+        let assets_dir = self
+            .config
+            .assets_dir
+            .as_deref()
+            .unwrap_or_else(|| Path::new(""));
+        assets_dir.join(
+            self.slugify_path(Path::new(
+                attachment
+                    .file_name()
+                    .expect("attachments should have a filename"),
+            )),
+        )
+    }
+
+    /// Return the root directory of the vault.
+    ///
+    /// This is the root itself, unless a single note is exported by passing its path as the root.
+    fn vault_root(&self) -> PathBuf {
+        if self.root.is_dir() {
+            self.root.clone()
+        } else {
+            self.root.parent().unwrap_or(&self.root).to_path_buf()
+        }
+    }
+
+    /// Return the directory which notes are exported to.
+    ///
+    /// This is the destination itself, unless a single note is exported to a specific file.
+    fn destination_root(&self) -> PathBuf {
+        if self.destination.is_dir() {
+            self.destination.clone()
+        } else {
+            self.destination
+                .parent()
+                .unwrap_or(&self.destination)
+                .to_path_buf()
+        }
+    }
+
+    /// Apply the [filename slugifier][Exporter::filename_slugifier] (if any) to the final component
+    /// of `path`, after normalizing it to Unicode NFC.
+    fn slugify_path(&self, path: &Path) -> PathBuf {
+        let mut path = nfc_path(path);
+        if let (Some(slugifier), Some(filename)) =
+            (self.config.filename_slugifier, path.file_name())
+        {
+            let filename = slugifier(&filename.to_string_lossy());
+            path.set_file_name(filename);
+        }
+        path
+    }
+
+    /// Return the path that the file at `path` is exported to, relative to the destination.
+    ///
+    /// This applies the [filename slugifier][Exporter::filename_slugifier] and, for notes, the
+    /// [output extension][Exporter::output_extension].
+    fn output_path(&self, path: &Path) -> PathBuf {
+        let mut output = self.slugify_path(path);
+        if let Some(extension) = &self.config.output_extension {
+            if self.is_note(path) {
+                output.set_extension(extension);
+            }
+        }
+        output
+    }
+
+    /// Return the path that the vault file at `file` is exported to, relative to the destination.
+    ///
+    /// This is the [output path][Exporter::output_path] of `relative_path` (the path of the file
+    /// relative to the start of the export), unless the file declares a
+    /// [permalink][Exporter::use_permalink_frontmatter]. Notes are then passed through the
+    /// [destination mapper][Exporter::destination_mapper], if any.
+    fn destination_path(
+        &self,
+        file: &Path,
+        relative_path: &Path,
+        vault_index: &VaultIndex,
+    ) -> PathBuf {
+        let path = self.unmapped_destination_path(file, relative_path, vault_index);
+        match &self.config.destination_mapper {
+            Some(mapper) if self.is_note(file) => self.map_destination(*mapper, file, path),
+            _ => path,
+        }
+    }
+
+    /// Return the destination of the note at `file` according to `mapper`, where `path` is the
+    /// destination it would be exported to otherwise.
+    fn map_destination(
+        &self,
+        mapper: &DestinationMapper<'_>,
+        file: &Path,
+        path: PathBuf,
+    ) -> PathBuf {
+        if let Some(mapped) = self
+            .state
+            .lock()
+            .expect("run state mutex poisoned")
+            .mapped_destinations
+            .get(file)
+        {
+            return mapped.clone();
+        }
+        let mut context = Context::new(file.to_path_buf(), self.destination_root().join(path))
+            .with_vault_root(self.vault_root())
+            .with_destination_root(self.destination_root())
+            .with_internal_link_format(self.config.internal_link_format);
+        // Notes with invalid frontmatter fail to export later on, which reports the error.
+        context.frontmatter = fs::read_to_string(file)
+            .ok()
+            .and_then(|content| frontmatter_from_str(&extract_frontmatter(&content)).ok())
+            .unwrap_or_default();
+        let mapped = mapper(&context);
+        self.state
+            .lock()
+            .expect("run state mutex poisoned")
+            .mapped_destinations
+            .insert(file.to_path_buf(), mapped.clone());
+        mapped
+    }
+
+    /// Return the path that the vault file at `file` is exported to, relative to the destination,
+    /// before applying the [destination mapper][Exporter::destination_mapper].
+    fn unmapped_destination_path(
+        &self,
+        file: &Path,
+        relative_path: &Path,
+        vault_index: &VaultIndex,
+    ) -> PathBuf {
+        let Some(permalink) = vault_index.permalink(file) else {
+            let path = self.index_page_name(file).map_or_else(
+                || relative_path.to_path_buf(),
+                |index_filename| relative_path.with_file_name(index_filename),
+            );
+            return self.output_path(&path);
+        };
+        // Only plain path components are kept, so permalinks can't point outside the destination.
+        let permalink = permalink.nfc().collect::<String>();
+        let mut path: PathBuf = Path::new(&permalink)
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect();
+        if permalink.ends_with('/') || path.as_os_str().is_empty() {
+            path.push("index");
+        }
+        let extension = self.config.output_extension.as_deref().map_or_else(
+            || file.extension().unwrap_or_default().to_os_string(),
+            OsString::from,
+        );
+        let mut path = path.into_os_string();
+        path.push(".");
+        path.push(extension);
+        PathBuf::from(path)
+    }
+
+    /// Check whether the vault file at `file` is exported on its own, rather than only when it's
+    /// referenced by a note (as with attachments collected into the
+    /// [assets directory][Exporter::assets_dir]) or not at all.
+    fn is_exported_directly(&self, file: &Path, skipped_files: &HashSet<PathBuf>) -> bool {
+        file.starts_with(&self.start_at)
+            && (self.config.assets_dir.is_none() || self.is_note(file))
+            && !self.is_frontmatter_defaults_file(file)
+            && !skipped_files.contains(file)
+    }
+
+    fn is_frontmatter_defaults_file(&self, file: &Path) -> bool {
+        self.config
+            .frontmatter_defaults_file
+            .as_ref()
+            .is_some_and(|filename| file.file_name() == Some(filename.as_ref()))
+    }
+
+    /// Parse the `frontmatter` of the note at `path` and merge the
+    /// [frontmatter defaults][Exporter::frontmatter_defaults_file] of its directory into it.
+    fn parse_frontmatter(&self, frontmatter: &str, path: &Path) -> Result<Frontmatter> {
+        let mut parsed =
+            frontmatter_from_str(frontmatter).context(FrontMatterDecodeSnafu { path })?;
+        if self.config.frontmatter_defaults_file.is_some() {
+            if let Some(directory) = path.parent() {
+                let defaults = self.frontmatter_defaults(directory)?;
+                merge_frontmatter_defaults(
+                    &mut parsed,
+                    &defaults,
+                    &self.config.frontmatter_list_merge,
+                );
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Return the frontmatter defaults which apply to notes within `directory`, including those
+    /// inherited from its parent directories within the vault.
+    fn frontmatter_defaults(&self, directory: &Path) -> Result<Arc<Frontmatter>> {
+        let Some(filename) = &self.config.frontmatter_defaults_file else {
+            return Ok(Arc::default());
+        };
+        if let Some(defaults) = self
+            .state
+            .lock()
+            .expect("run state mutex poisoned")
+            .frontmatter_defaults
+            .get(directory)
+        {
+            return Ok(Arc::clone(defaults));
+        }
+
+        let mut defaults = match directory.parent() {
+            Some(parent)
+                if directory != self.vault_root() && directory.starts_with(self.vault_root()) =>
+            {
+                self.frontmatter_defaults(parent)?.as_ref().clone()
+            }
+            _ => Frontmatter::new(),
+        };
+        let path = directory.join(filename);
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let mut directory_defaults = frontmatter_from_str(content.trim())
+                    .context(FrontMatterDecodeSnafu { path: &path })?;
+                merge_frontmatter_defaults(
+                    &mut directory_defaults,
+                    &defaults,
+                    &self.config.frontmatter_list_merge,
+                );
+                defaults = directory_defaults;
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err).context(ReadSnafu { path }),
+        }
+
+        let defaults = Arc::new(defaults);
+        self.state
+            .lock()
+            .expect("run state mutex poisoned")
+            .frontmatter_defaults
+            .insert(directory.to_path_buf(), Arc::clone(&defaults));
+        Ok(defaults)
+    }
+
+    /// Find the files which are left out of the export even though they would otherwise be
+    /// [exported directly][Exporter::is_exported_directly].
+    fn skipped_files(&self, vault_index: &VaultIndex) -> Result<HashSet<PathBuf>> {
+        let mut skipped = self.attachments_of_skipped_notes(vault_index)?;
+        skipped.extend(self.unmodified_files(vault_index)?);
+        Ok(skipped)
+    }
+
+    /// Find the files which are skipped because they weren't modified after the cutoff set
+    /// through [`Exporter::modified_since`].
+    fn unmodified_files(&self, vault_index: &VaultIndex) -> Result<HashSet<PathBuf>> {
+        let Some(cutoff) = self.config.modified_since else {
+            return Ok(HashSet::new());
+        };
+        let scanned: Vec<(&PathBuf, bool, Vec<&PathBuf>)> = vault_index
+            .contents()
+            .par_iter()
+            .map(|file| {
+                let modified = fs::metadata(file)
+                    .and_then(|metadata| metadata.modified())
+                    .context(ModTimeReadSnafu { path: file })?;
+                let embedded_notes = if self.is_note(file) {
+                    let content = fs::read_to_string(file).context(ReadSnafu { path: file })?;
+                    referenced_files(&content)
+                        .into_iter()
+                        .filter(|reference| reference.is_embed)
+                        .filter_map(|reference| {
+                            self.resolve_file(&reference.file, file, vault_index)
+                        })
+                        .filter(|embedded| self.is_note(embedded))
+                        .collect()
+                } else {
+                    vec![]
+                };
+                Ok((file, modified > cutoff, embedded_notes))
+            })
+            .collect::<Result<_>>()?;
+
+        // Notes embedding a modified note change along with it.
+        let mut embedded_by: HashMap<&PathBuf, Vec<&PathBuf>> = HashMap::new();
+        for (file, _, embedded_notes) in &scanned {
+            for embedded in embedded_notes {
+                embedded_by.entry(embedded).or_default().push(file);
+            }
+        }
+        let mut pending: Vec<&PathBuf> = scanned
+            .iter()
+            .filter(|(_, modified, _)| *modified)
+            .map(|(file, _, _)| *file)
+            .collect();
+        let mut modified = HashSet::new();
+        while let Some(file) = pending.pop() {
+            if modified.insert(file) {
+                pending.extend(embedded_by.get(file).into_iter().flatten());
+            }
+        }
+
+        Ok(scanned
+            .iter()
+            .filter(|(file, _, _)| !modified.contains(file))
+            .map(|(file, _, _)| (*file).clone())
+            .collect())
+    }
+
+    fn is_note(&self, path: &Path) -> bool {
+        has_note_extension(path, &self.config.note_extensions)
+    }
+
+    /// Whether the note at `path` is exported as a data file rather than as markdown (see
+    /// [`Exporter::frontmatter_only_as_data`]).
+    fn is_data_note(&self, path: &Path) -> bool {
+        if self.config.frontmatter_only_as_data.is_none() || !self.is_note(path) {
+            return false;
+        }
+        let mut state = self.state.lock().expect("run state mutex poisoned");
+        *state
+            .data_notes
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                fs::read_to_string(path).is_ok_and(|content| is_frontmatter_only(&content))
+            })
+    }
+
+    fn report_progress(&self, event: ProgressEvent) {
+        if let Some(callback) = self.config.progress_callback {
+            callback(event);
+        }
+    }
+
+    /// Return the files within the starting point of the export which match a
+    /// [passthrough pattern][Exporter::add_passthrough_pattern].
+    fn passthrough_files(&self) -> Result<HashSet<PathBuf>> {
+        if self.config.passthrough_patterns.is_empty() {
+            return Ok(HashSet::new());
+        }
+        Ok(walker::passthrough_files(
+            &self.root,
+            &self.config.passthrough_patterns,
+            self.config.walk_options.follow_symlinks,
+        )?
+        .into_iter()
+        .filter(|file| file.starts_with(&self.start_at))
+        .collect())
+    }
+
+    /// Return whether `file` matches a [passthrough pattern][Exporter::add_passthrough_pattern].
+    ///
+    /// This builds a new matcher for the patterns on each call. When exporting many files, use the
+    /// set returned by [`Exporter::passthrough_files`] instead.
+    fn is_passthrough(&self, file: &Path) -> Result<bool> {
+        if self.config.passthrough_patterns.is_empty() || !file.starts_with(&self.root) {
+            return Ok(false);
+        }
+        let matcher = walker::passthrough_matcher(&self.root, &self.config.passthrough_patterns)?;
+        Ok(matcher.matched_path_or_any_parents(file, false).is_ignore())
+    }
+
+    /// Copy `src` to `dest` as-is, even when it's a note.
+    fn copy_verbatim(&self, src: &Path, dest: &Path) -> Result<()> {
+        if self.config.dry_run {
+            self.report_dry_run_action(&DryRunAction::write(src, dest));
+            return Ok(());
+        }
+        copy_file(src, dest).context(FileExportSnafu { path: src })?;
+        if self.config.preserve_permissions {
+            copy_permissions(src, dest).context(FileExportSnafu { path: src })?;
+        }
+        if self.config.preserve_mtime {
+            copy_mtime(src, dest).context(FileExportSnafu { path: src })?;
+        }
+        Ok(())
+    }
+
+    fn export_note(&self, src: &Path, dest: &Path) -> Result<()> {
+        match self.is_note(src) {
+            true => self.parse_and_export_obsidian_note(src, dest),
+            false if self.config.dry_run => {
+                self.report_dry_run_action(&DryRunAction::write(src, dest));
+                Ok(())
+            }
+            false if self.config.preserve_permissions => {
+                copy_file(src, dest).and_then(|()| copy_permissions(src, dest))
+            }
+            false => copy_file(src, dest),
+        }
+        .context(FileExportSnafu { path: src })?;
+
+        if self.config.preserve_mtime && !self.config.dry_run {
+            copy_mtime(src, dest).context(FileExportSnafu { path: src })?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_and_export_obsidian_note(&self, src: &Path, dest: &Path) -> Result<()> {
+        let content = fs::read_to_string(src).context(ReadSnafu { path: src })?;
+        let context = Context::new(src.to_path_buf(), dest.to_path_buf())
+            .with_vault_root(self.vault_root())
+            .with_destination_root(self.destination_root())
+            .with_internal_link_format(self.config.internal_link_format);
+        let vault_index = self
+            .vault_index
+            .as_ref()
+            .expect("vault index should be built before exporting notes");
+
+        let Some((mut context, mut output)) = self.process_note(&content, context, vault_index)?
+        else {
+            info!("Skipping note\n\tSource: '{}'", src.display());
+            if self.config.dry_run {
+                self.report_dry_run_action(&DryRunAction::Skip {
+                    source: src.to_path_buf(),
+                });
+            }
+            return Ok(());
+        };
+        if let Some(format) = self.config.frontmatter_only_as_data {
+            if is_frontmatter_only(&content) {
+                context.destination.set_extension(format.extension());
+                output = data_file_contents(format, &context.frontmatter)
+                    .context(FrontMatterEncodeSnafu { path: src })?;
+            }
+        }
+
+        if self.config.dry_run {
+            self.report_dry_run_action(&DryRunAction::write(src, &context.destination));
+            self.write_output_files(src, context.take_output_files())?;
+            return Ok(());
+        }
+
+        let mut outfile = create_file(&context.destination)?;
+        outfile.write_all(output.as_bytes()).context(WriteSnafu {
+            path: &context.destination,
+        })?;
+        self.write_output_files(src, context.take_output_files())?;
+        if self.collects_exported_notes() {
+            self.state
+                .lock()
+                .expect("run state mutex poisoned")
+                .exported_notes
+                .push(ExportedNote {
+                    source: src.to_path_buf(),
+                    destination: context.destination,
+                    frontmatter: context.frontmatter,
+                });
+        }
+        Ok(())
+    }
+
+    /// Write the files which postprocessors added to the export of the note at `src` (see
+    /// [`Context::add_output_file`]), or report them during a dry run.
+    fn write_output_files(&self, src: &Path, files: Vec<ExportedFile>) -> Result<()> {
+        for file in files {
+            let dest = self.destination_root().join(&file.path);
+            if self.config.dry_run {
+                self.report_dry_run_action(&DryRunAction::write(src, &dest));
+                continue;
+            }
+            create_file(&dest)?
+                .write_all(&file.content)
+                .context(WriteSnafu { path: &dest })?;
+        }
+        Ok(())
+    }
+
+    /// Parse the note `content`, run postprocessors over it and render the result to markdown
+    /// (including frontmatter, depending on the [`FrontmatterStrategy`]).
+    ///
+    /// Returns the final context of the note along with the rendered markdown, or `None` when a
+    /// postprocessor requested the note to be skipped.
+    fn process_note(
+        &self,
+        content: &str,
+        mut context: Context,
+        vault_index: &VaultIndex,
+    ) -> Result<Option<(Context, String)>> {
+        let path = context.current_file().clone();
+        if self.filters_by_frontmatter() {
+            let frontmatter = self.parse_frontmatter(&extract_frontmatter(content), &path)?;
+            if !self.passes_frontmatter_filters(&frontmatter) {
+                return Ok(None);
+            }
+        }
+
+        let (frontmatter, mut markdown_events) =
+            self.parse_obsidian_markdown(content, &path, &context, vault_index)?;
         context.frontmatter = frontmatter;
-        for func in &self.postprocessors {
-            match func(&mut context, &mut markdown_events) {
+        if self.config.git_dates {
+            self.add_note_dates(&mut context.frontmatter, &path);
+        }
+        context.set_first_heading(first_heading(&markdown_events));
+        for func in &self.config.postprocessors {
+            let result = func
+                .call(&mut context, &mut markdown_events)
+                .context(PostprocessorSnafu { path: &path })?;
+            match result {
                 PostprocessorResult::StopHere => break,
-                PostprocessorResult::StopAndSkipNote => return Ok(()),
+                PostprocessorResult::StopAndSkipNote => return Ok(None),
                 PostprocessorResult::Continue => (),
             }
         }
 
-        let mut outfile = create_file(&context.destination)?;
-        let write_frontmatter = match self.frontmatter_strategy {
+        self.filter_frontmatter_keys(&mut context.frontmatter);
+
+        let mut output = String::new();
+        let write_frontmatter = match self.config.frontmatter_strategy {
             FrontmatterStrategy::Always => true,
             FrontmatterStrategy::Never => false,
             FrontmatterStrategy::Auto => !context.frontmatter.is_empty(),
         };
         if write_frontmatter {
-            let mut frontmatter_str = frontmatter_to_str(&context.frontmatter)
-                .context(FrontMatterEncodeSnafu { path: src })?;
-            frontmatter_str.push('\n');
-            outfile
-                .write_all(frontmatter_str.as_bytes())
-                .context(WriteSnafu {
-                    path: &context.destination,
-                })?;
-        }
-        outfile
-            .write_all(render_mdevents_to_mdtext(&markdown_events).as_bytes())
-            .context(WriteSnafu {
-                path: &context.destination,
-            })?;
-        Ok(())
+            output = frontmatter_to_str_with_options(
+                &context.frontmatter,
+                self.config.frontmatter_yaml_options,
+            )
+            .context(FrontMatterEncodeSnafu { path: &path })?;
+            output.push('\n');
+        }
+        output.push_str(&self.config.serializer.serialize(&markdown_events));
+        output.push('\n');
+        for filter in &self.config.output_filters {
+            output = filter(&context, output);
+        }
+        let output = self.config.line_ending.apply(output, content);
+        Ok(Some((context, output)))
+    }
+
+    /// Add the `created` and `modified` dates of the note at `path` to its `frontmatter`, unless
+    /// it already has these keys (see [`Exporter::git_dates`]).
+    fn add_note_dates(&self, frontmatter: &mut Frontmatter, path: &Path) {
+        let relative_path = path.strip_prefix(self.vault_root()).unwrap_or(path);
+        let Some(dates) = self
+            .commit_dates()
+            .get(relative_path)
+            .cloned()
+            .or_else(|| filesystem_dates(path))
+        else {
+            return;
+        };
+        for (key, date) in [("created", dates.created), ("modified", dates.modified)] {
+            if !frontmatter.contains_key(key) {
+                frontmatter.insert(Value::String(key.to_owned()), Value::String(date));
+            }
+        }
+    }
+
+    /// Return the dates of the first and last commit changing each file within the vault, which
+    /// are read from git the first time they're needed.
+    fn commit_dates(&self) -> Arc<HashMap<PathBuf, CommitDates>> {
+        let mut state = self.state.lock().expect("run state mutex poisoned");
+        if let Some(dates) = &state.commit_dates {
+            return Arc::clone(dates);
+        }
+        let vault_root = self.vault_root();
+        let dates = git::commit_dates(&vault_root).unwrap_or_else(|| {
+            warn!(
+                "Unable to read the git history of the vault, using dates from the filesystem instead\n\tVault: '{}'",
+                vault_root.display()
+            );
+            HashMap::new()
+        });
+        let dates = Arc::new(dates);
+        state.commit_dates = Some(Arc::clone(&dates));
+        dates
+    }
+
+    /// Apply [`Exporter::frontmatter_allowlist`] and [`Exporter::frontmatter_denylist`].
+    fn filter_frontmatter_keys(&self, frontmatter: &mut Frontmatter) {
+        if self.config.frontmatter_allowlist.is_empty()
+            && self.config.frontmatter_denylist.is_empty()
+        {
+            return;
+        }
+        let contains = |list: &[String], key: &Value| {
+            key.as_str()
+                .is_some_and(|key_str| list.iter().any(|item| item == key_str))
+        };
+        frontmatter.retain(|key, _value| {
+            (self.config.frontmatter_allowlist.is_empty()
+                || contains(&self.config.frontmatter_allowlist, key))
+                && !contains(&self.config.frontmatter_denylist, key)
+        });
+    }
+
+    fn report_dry_run_action(&self, action: &DryRunAction) {
+        if let Some(callback) = self.config.dry_run_callback {
+            callback(action);
+        }
+    }
+
+    /// Read the note at `path` to embed it with the given `context`, unless that would exceed the
+    /// [embed recursion limit][Exporter::embed_recursion_limit].
+    fn read_embedded_note(&self, path: &Path, context: &Context) -> Result<String> {
+        if context.note_depth() > self.config.embed_recursion_limit {
+            return Err(ExportError::RecursionLimitExceeded {
+                file_tree: context.file_tree(),
+            });
+        }
+        fs::read_to_string(path).context(ReadSnafu { path })
     }
 
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::panic_in_result_fn)]
     #[allow(clippy::shadow_unrelated)]
-    fn parse_obsidian_note<'b>(
+    fn parse_obsidian_markdown<'b>(
         &self,
+        content: &str,
         path: &Path,
         context: &Context,
+        vault_index: &VaultIndex,
     ) -> Result<(Frontmatter, MarkdownEvents<'b>)> {
-        if context.note_depth() > NOTE_RECURSION_LIMIT {
-            return Err(ExportError::RecursionLimitExceeded {
-                file_tree: context.file_tree(),
-            });
-        }
-        let content = fs::read_to_string(path).context(ReadSnafu { path })?;
+        let content = strip_empty_frontmatter(content);
         let mut frontmatter = String::new();
 
-        let parser_options = Options::ENABLE_TABLES
+        let mut parser_options = Options::ENABLE_TABLES
             | Options::ENABLE_FOOTNOTES
             | Options::ENABLE_STRIKETHROUGH
             | Options::ENABLE_TASKLISTS
-            | Options::ENABLE_MATH
-            | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+            | Options::ENABLE_MATH;
+        // A leading `---` which doesn't start valid frontmatter is a horizontal rule instead.
+        if has_frontmatter(content) {
+            parser_options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+        }
 
         let mut ref_parser = RefParser::new();
         let mut events = vec![];
         // Most of the time, a reference triggers 5 events: [ or ![, [, <text>, ], ]
         let mut buffer = Vec::with_capacity(5);
 
-        let mut parser = Parser::new_ext(&content, parser_options);
-        'outer: while let Some(event) = parser.next() {
+        // Pipes within wikilinks need to be escaped inside of tables.
+        let mut in_table = false;
+
+        let mut parser = Parser::new_ext(content, parser_options).into_offset_iter();
+        'outer: while let Some((event, range)) = parser.next() {
             // When encountering a metadata block (frontmatter), collect all events until getting
             // to the end of the block, at which point the nested loop will break out to the outer
             // loop again.
             if matches!(event, Event::Start(Tag::MetadataBlock(_kind))) {
-                for event in parser.by_ref() {
+                for (event, _range) in parser.by_ref() {
                     match event {
                         Event::Text(cowstr) => frontmatter.push_str(&cowstr),
                         Event::End(TagEnd::MetadataBlock(_kind)) => {
@@ -518,16 +2609,24 @@ impl<'a> Exporter<'a> {
                 buffer.clear();
                 ref_parser.reset();
             }
+            match event {
+                Event::Start(Tag::Table(_)) => in_table = true,
+                Event::End(TagEnd::Table) => in_table = false,
+                _ => {}
+            }
+            let event = self.resolve_markdown_link(event, range.start, context, vault_index);
             buffer.push(event.clone());
             match ref_parser.state {
                 RefParserState::NoState => {
                     match event {
                         Event::Text(CowStr::Borrowed("![")) => {
                             ref_parser.ref_type = Some(RefType::Embed);
+                            ref_parser.offset = range.start;
                             ref_parser.transition(RefParserState::ExpectSecondOpenBracket);
                         }
                         Event::Text(CowStr::Borrowed("[")) => {
                             ref_parser.ref_type = Some(RefType::Link);
+                            ref_parser.offset = range.start;
                             ref_parser.transition(RefParserState::ExpectSecondOpenBracket);
                         }
                         _ => {
@@ -569,12 +2668,39 @@ impl<'a> Exporter<'a> {
                 },
                 RefParserState::ExpectFinalCloseBracket => match event {
                     Event::Text(CowStr::Borrowed("]")) => match ref_parser.ref_type {
+                        Some(_) if self.config.internal_link_format == InternalLinkFormat::Wikilink => {
+                            let reference = ObsidianNoteReference::from_str(&ref_parser.ref_text);
+                            if let Some(target_file) =
+                                self.resolve_reference(reference, ref_parser.offset, context, vault_index)
+                            {
+                                if self.config.assets_dir.is_some() && !self.is_note(target_file) {
+                                    self.add_referenced_attachment(target_file);
+                                }
+                            }
+                            // Emitted as raw HTML, as text would have its brackets escaped.
+                            let prefix = match ref_parser.ref_type {
+                                Some(RefType::Embed) => "!",
+                                _ => "",
+                            };
+                            let ref_text = if in_table {
+                                ref_parser.ref_text.replace('|', "\\|")
+                            } else {
+                                ref_parser.ref_text.clone()
+                            };
+                            events.push(Event::InlineHtml(CowStr::from(format!(
+                                "{prefix}[[{ref_text}]]"
+                            ))));
+                            buffer.clear();
+                            ref_parser.transition(RefParserState::Resetting);
+                        }
                         Some(RefType::Link) => {
                             let mut elements = self.make_link_to_file(
                                 ObsidianNoteReference::from_str(
                                     ref_parser.ref_text.clone().as_ref()
                                 ),
+                                ref_parser.offset,
                                 context,
+                                vault_index,
                             );
                             events.append(&mut elements);
                             buffer.clear();
@@ -583,7 +2709,9 @@ impl<'a> Exporter<'a> {
                         Some(RefType::Embed) => {
                             let mut elements = self.embed_file(
                                 ref_parser.ref_text.clone().as_ref(),
-                                context
+                                ref_parser.offset,
+                                context,
+                                vault_index,
                             )?;
                             events.append(&mut elements);
                             buffer.clear();
@@ -602,154 +2730,905 @@ impl<'a> Exporter<'a> {
             events.append(&mut buffer);
         }
 
+        let mut frontmatter = self.parse_frontmatter(&frontmatter, path)?;
+        if self.config.resolve_frontmatter_links {
+            for value in frontmatter.values_mut() {
+                self.resolve_links_in_value(value, content, context, vault_index);
+            }
+        }
         Ok((
-            frontmatter_from_str(&frontmatter).context(FrontMatterDecodeSnafu { path })?,
+            frontmatter,
             events.into_iter().map(event_to_owned).collect(),
         ))
     }
 
-    // Generate markdown elements for a file that is embedded within another note.
-    //
-    // - If the file being embedded is a note, it's content is included at the point of embed.
-    // - If the file is an image, an image tag is generated.
-    // - For other types of file, a regular link is created instead.
-    fn embed_file<'b>(
+    /// Rewrite the `[[links]]` within the strings of the frontmatter `value` of the note
+    /// `content`, see [`Exporter::resolve_frontmatter_links`].
+    fn resolve_links_in_value(
+        &self,
+        value: &mut Value,
+        content: &str,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) {
+        match value {
+            Value::String(string) => {
+                if let Cow::Owned(resolved) =
+                    WIKILINK_RE.replace_all(string, |captures: &Captures<'_>| {
+                        let whole = captures.get(0).map_or("", |whole| whole.as_str());
+                        let reference = captures
+                            .name("reference")
+                            .map_or("", |reference| reference.as_str());
+                        self.resolve_frontmatter_link(
+                            whole,
+                            reference,
+                            content,
+                            context,
+                            vault_index,
+                        )
+                    })
+                {
+                    *string = resolved;
+                }
+            }
+            Value::Sequence(sequence) => {
+                for item in sequence {
+                    self.resolve_links_in_value(item, content, context, vault_index);
+                }
+            }
+            Value::Mapping(mapping) => {
+                for item in mapping.values_mut() {
+                    self.resolve_links_in_value(item, content, context, vault_index);
+                }
+            }
+            Value::Tagged(tagged) => {
+                self.resolve_links_in_value(&mut tagged.value, content, context, vault_index);
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+
+    /// Render the frontmatter link `whole` (with the text `reference` between its brackets) in the
+    /// [`InternalLinkFormat`] of the export.
+    fn resolve_frontmatter_link(
+        &self,
+        whole: &str,
+        reference: &str,
+        content: &str,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) -> String {
+        let note_ref = ObsidianNoteReference::from_str(reference);
+        // The offset only orders unresolved links, so the first occurrence is close enough.
+        let offset = content.find(whole).unwrap_or_default();
+        if self.config.internal_link_format == InternalLinkFormat::Wikilink {
+            if let Some(target_file) =
+                self.resolve_reference(note_ref, offset, context, vault_index)
+            {
+                if self.config.assets_dir.is_some() && !self.is_note(target_file) {
+                    self.add_referenced_attachment(target_file);
+                }
+            }
+            return whole.to_owned();
+        }
+        let events = self.make_link_to_file(note_ref, offset, context, vault_index);
+        render_mdevents_to_mdtext(&events).trim_end().to_owned()
+    }
+
+    // Generate markdown elements for a file that is embedded within another note.
+    //
+    // - If the file being embedded is a note, it's content is included at the point of embed.
+    // - If the file is an image, an image tag is generated.
+    // - For other types of file, a regular link is created instead.
+    fn embed_file<'b>(
+        &self,
+        link_text: &'a str,
+        offset: usize,
+        context: &'a Context,
+        vault_index: &VaultIndex,
+    ) -> Result<MarkdownEvents<'b>> {
+        let note_ref = ObsidianNoteReference::from_str(link_text);
+
+        let path = match note_ref.file {
+            Some(_) if self.cross_vault_reference(note_ref).is_some() => {
+                return Ok(self.make_link_to_file(note_ref, offset, context, vault_index));
+            }
+            Some(file) => self.resolve_file(file, context.current_file(), vault_index),
+
+            // If we have None file it is either to a section or id within the same file and thus
+            // the current embed logic will fail, recurssing until it reaches it's limit.
+            // For now we just bail early.
+            None => return Ok(self.make_link_to_file(note_ref, offset, context, vault_index)),
+        };
+
+        // The note may still exist within the vault, but be excluded from the export.
+        let excluded = note_ref
+            .file
+            .filter(|_| path.is_none())
+            .and_then(|file| {
+                vault_index.resolve_excluded(
+                    file,
+                    context.current_file(),
+                    self.config.link_resolution_mode,
+                )
+            })
+            .filter(|excluded| self.is_note(excluded));
+
+        let Some(path) = path.or(excluded) else {
+            warn!(
+                "Unable to find embedded note\n\tReference: '{}'\n\tSource: '{}'",
+                note_ref
+                    .file
+                    .unwrap_or_else(|| context.current_file().to_str().unwrap()),
+                context.current_file().display(),
+            );
+            self.add_unresolved_link(note_ref, offset, context);
+            return Ok(vec![]);
+        };
+        if excluded.is_some() {
+            if let Some(events) = self.embed_excluded_note(note_ref, path, context, vault_index) {
+                return Ok(events);
+            }
+        }
+
+        let child_context = Context::from_parent(context, path);
+        let no_ext = OsString::new();
+
+        if !self.config.process_embeds_recursively && context.file_tree().contains(path) {
+            return Ok([
+                vec![Event::Text(CowStr::Borrowed("→ "))],
+                self.make_link_to_file(note_ref, offset, &child_context, vault_index),
+            ]
+            .concat());
+        }
+
+        let events = match path.extension().unwrap_or(&no_ext).to_str() {
+            _ if self.is_note(path) => {
+                self.embed_note(note_ref, path, context, child_context, vault_index)?
+            }
+            Some("png" | "jpg" | "jpeg" | "gif" | "webp" | "svg") => {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let image_ref = match note_ref.label {
+                    None if self.config.image_alt_from_filename => ObsidianNoteReference {
+                        label: Some(&stem),
+                        ..note_ref
+                    },
+                    _ => note_ref,
+                };
+                self.make_link_to_file(image_ref, offset, &child_context, vault_index)
+                    .into_iter()
+                    .map(|event| match event {
+                        // make_link_to_file returns a link to a file. With this we turn the link
+                        // into an image reference instead. Slightly hacky, but avoids needing
+                        // to keep another utility function around for this, or introducing an
+                        // extra parameter on make_link_to_file.
+                        Event::Start(Tag::Link {
+                            link_type,
+                            dest_url,
+                            title,
+                            id,
+                        }) => Event::Start(Tag::Image {
+                            link_type,
+                            dest_url: CowStr::from(dest_url.into_string()),
+                            title: CowStr::from(title.into_string()),
+                            id: CowStr::from(id.into_string()),
+                        }),
+                        Event::End(TagEnd::Link) => Event::End(TagEnd::Image),
+                        _ => event,
+                    })
+                    .collect()
+            }
+            _ => {
+                mark_as_embed(self.make_link_to_file(note_ref, offset, &child_context, vault_index))
+            }
+        };
+        Ok(events)
+    }
+
+    /// Return the events of the note at `path` (or the section of it given by `note_ref`) to embed
+    /// into the note of `context`, after running the embed postprocessors over them.
+    ///
+    /// Falls back to a link to the note when it's embedded again, excluded by the frontmatter
+    /// filters or larger than [`Exporter::max_embed_bytes`], depending on the configuration.
+    fn embed_note<'b>(
+        &self,
+        note_ref: ObsidianNoteReference<'_>,
+        path: &Path,
+        context: &Context,
+        mut child_context: Context,
+        vault_index: &VaultIndex,
+    ) -> Result<MarkdownEvents<'b>> {
+        if self.config.duplicate_embed_behavior != DuplicateEmbedBehavior::Inline
+            && !context.record_expanded_embed(path, note_ref.section)
+        {
+            return Ok(self.embed_duplicate_note(note_ref, path, context, vault_index));
+        }
+        let content = self.read_embedded_note(path, &child_context)?;
+        // Excluded notes are replaced based on their frontmatter alone, so they're rejected before
+        // the rest of the note (including its own embeds) is parsed.
+        if self.config.excluded_embed_behavior != ExcludedEmbedBehavior::Inline
+            && self.filters_by_frontmatter()
+        {
+            let frontmatter = self.parse_frontmatter(&extract_frontmatter(&content), path)?;
+            if !self.passes_frontmatter_filters(&frontmatter) {
+                if let Some(replacement) =
+                    self.embed_excluded_note(note_ref, path, context, vault_index)
+                {
+                    return Ok(replacement);
+                }
+            }
+        }
+        let (frontmatter, mut events) =
+            self.parse_obsidian_markdown(&content, path, &child_context, vault_index)?;
+        child_context.frontmatter = frontmatter;
+        child_context.set_first_heading(first_heading(&events));
+        self.set_embed(&mut child_context, note_ref, path, context, vault_index);
+        if let Some(section) = note_ref.section {
+            events = match section.strip_prefix('^') {
+                Some(block_id) => reduce_to_block(events, block_id),
+                None => reduce_to_section(events, section),
+            };
+        }
+        for func in &self.config.embed_postprocessors {
+            // Postprocessors running on embeds shouldn't be able to change frontmatter (or
+            // any other metadata), so we give them a clone of the context.
+            match func(&mut child_context, &mut events) {
+                PostprocessorResult::StopHere => break,
+                PostprocessorResult::StopAndSkipNote => {
+                    events = vec![];
+                }
+                PostprocessorResult::Continue => (),
+            }
+        }
+        if let Some(limit) = self.config.max_embed_bytes {
+            let size = render_mdevents_to_mdtext(&events).len();
+            if size > limit {
+                warn!(
+                    "Embedded note exceeds the maximum embed size, linking to it instead\n\tReference: \
+                     '{}'\n\tSize: {} bytes (limit: {} bytes)\n\tSource: '{}'",
+                    path.display(),
+                    size,
+                    limit,
+                    context.current_file().display(),
+                );
+                return Ok(self.make_link_to_path(path, note_ref, context, vault_index));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Record on `child_context` which block of the note at `path` is embedded through
+    /// `reference`, along with the URL linking to the note from the note it's embedded in.
+    fn set_embed(
+        &self,
+        child_context: &mut Context,
+        reference: ObsidianNoteReference<'_>,
+        path: &Path,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) {
+        let source_reference = ObsidianNoteReference {
+            section: None,
+            ..reference
+        };
+        if let Some(Event::Start(Tag::Link { dest_url, .. })) = self
+            .make_link_to_path(path, source_reference, context, vault_index)
+            .first()
+        {
+            child_context.set_embed(
+                reference
+                    .section
+                    .and_then(|section| section.strip_prefix('^'))
+                    .map(ToOwned::to_owned),
+                dest_url.to_string(),
+            );
+        }
+    }
+
+    /// Return the events replacing the embed of a note which is excluded from the export, according
+    /// to the [`ExcludedEmbedBehavior`], or `None` when the note should be embedded regardless.
+    fn embed_excluded_note<'c>(
+        &self,
+        reference: ObsidianNoteReference<'_>,
+        path: &Path,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) -> Option<MarkdownEvents<'c>> {
+        match self.config.excluded_embed_behavior {
+            ExcludedEmbedBehavior::LinkTo => {
+                Some(self.make_link_to_path(path, reference, context, vault_index))
+            }
+            ExcludedEmbedBehavior::Omit => Some(vec![]),
+            ExcludedEmbedBehavior::Inline => None,
+        }
+    }
+
+    /// Return the events replacing an embed of a note which has already been embedded into the
+    /// same exported note, according to the [`DuplicateEmbedBehavior`].
+    fn embed_duplicate_note<'c>(
+        &self,
+        reference: ObsidianNoteReference<'_>,
+        path: &Path,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) -> MarkdownEvents<'c> {
+        match self.config.duplicate_embed_behavior {
+            DuplicateEmbedBehavior::LinkTo => {
+                self.make_link_to_path(path, reference, context, vault_index)
+            }
+            DuplicateEmbedBehavior::Omit | DuplicateEmbedBehavior::Inline => vec![],
+        }
+    }
+
+    fn passes_frontmatter_filters(&self, frontmatter: &Frontmatter) -> bool {
+        (!self.config.respect_export_frontmatter || !opts_out_of_export(frontmatter))
+            && self
+                .config
+                .frontmatter_query
+                .map_or(true, |query| query(frontmatter))
+            && self
+                .config
+                .frontmatter_filters
+                .iter()
+                .all(|filter| filter(frontmatter))
+    }
+
+    /// Whether any of the options which skip notes based on their frontmatter is set.
+    fn filters_by_frontmatter(&self) -> bool {
+        !self.config.frontmatter_filters.is_empty() || self.skips_attachments_of_skipped_notes()
+    }
+
+    /// Whether attachments which are only referenced by skipped notes are left out of the export.
+    fn skips_attachments_of_skipped_notes(&self) -> bool {
+        self.config.respect_export_frontmatter || self.config.frontmatter_query.is_some()
+    }
+
+    /// Find the attachments which are referenced by notes that are skipped because of their
+    /// frontmatter, but not by any exported note. These are left out of the export when
+    /// [`Exporter::respect_export_frontmatter`] is enabled or a
+    /// [frontmatter query][Exporter::frontmatter_query] is set.
+    ///
+    /// When an [assets directory][Exporter::assets_dir] is set, only attachments referenced by
+    /// exported notes are copied to begin with, so nothing needs to be left out.
+    fn attachments_of_skipped_notes(&self, vault_index: &VaultIndex) -> Result<HashSet<PathBuf>> {
+        if !self.skips_attachments_of_skipped_notes() || self.config.assets_dir.is_some() {
+            return Ok(HashSet::new());
+        }
+        let notes: HashMap<&PathBuf, ScannedNote<'_>> = vault_index
+            .contents()
+            .par_iter()
+            .filter(|file| self.is_note(file))
+            .map(|note| {
+                let content = fs::read_to_string(note).context(ReadSnafu { path: note })?;
+                let frontmatter = self.parse_frontmatter(&extract_frontmatter(&content), note)?;
+                let mut scanned = ScannedNote {
+                    exported: self.passes_frontmatter_filters(&frontmatter),
+                    attachments: vec![],
+                    embedded_notes: vec![],
+                };
+                for reference in referenced_files(&content) {
+                    let Some(file) = self.resolve_file(&reference.file, note, vault_index) else {
+                        continue;
+                    };
+                    if !self.is_note(file) {
+                        scanned.attachments.push(file);
+                    } else if reference.is_embed {
+                        scanned.embedded_notes.push(file);
+                    } else {
+                        // Links to other notes don't include any of their attachments.
+                    }
+                }
+                Ok((note, scanned))
+            })
+            .collect::<Result<_>>()?;
+
+        // The content of embedded notes ends up within the notes embedding them, so the
+        // attachments of (transitively) embedded notes are reachable as well. Skipped notes are
+        // only embedded when they're inlined.
+        let inline_skipped = self.config.excluded_embed_behavior == ExcludedEmbedBehavior::Inline;
+        let mut pending: Vec<&PathBuf> = notes
+            .iter()
+            .filter(|(path, note)| note.exported && path.starts_with(&self.start_at))
+            .map(|(path, _)| *path)
+            .collect();
+        let mut visited = HashSet::new();
+        let mut reachable: HashSet<&PathBuf> = HashSet::new();
+        while let Some(path) = pending.pop() {
+            let Some(note) = notes.get(path).filter(|_| visited.insert(path)) else {
+                continue;
+            };
+            reachable.extend(&note.attachments);
+            pending.extend(note.embedded_notes.iter().filter(|embedded| {
+                inline_skipped || notes.get(*embedded).is_some_and(|target| target.exported)
+            }));
+        }
+
+        Ok(notes
+            .iter()
+            .filter(|(path, note)| !note.exported && path.starts_with(&self.start_at))
+            .flat_map(|(_, note)| &note.attachments)
+            .filter(|attachment| !reachable.contains(*attachment))
+            .map(|attachment| (*attachment).clone())
+            .collect())
+    }
+
+    fn make_link_to_file<'c>(
+        &self,
+        reference: ObsidianNoteReference<'_>,
+        offset: usize,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) -> MarkdownEvents<'c> {
+        if let Some(events) = self.make_cross_vault_link(reference, offset, context) {
+            return events;
+        }
+        let Some(target_file) = self.resolve_reference(reference, offset, context, vault_index)
+        else {
+            return self
+                .config
+                .unresolved_link_style
+                .render(reference.display());
+        };
+        if let (None, Some(section)) = (reference.file, reference.section) {
+            return self.make_heading_link(section, reference);
+        }
+        // Data files aren't pages which can be linked to.
+        if self.is_data_note(target_file) {
+            return vec![Event::Text(CowStr::from(reference.display()))];
+        }
+        self.make_link_to_path(target_file, reference, context, vault_index)
+    }
+
+    /// Build an in-document link to the heading `section`, for references to a heading within the
+    /// current note (`[[#Heading]]`), which is displayed using the text of `reference`.
+    ///
+    /// Embedded notes are inserted into the note embedding them, so a heading referenced from an
+    /// embedded note ends up in the same document as the reference.
+    fn make_heading_link<'c>(
+        &self,
+        section: &str,
+        reference: ObsidianNoteReference<'_>,
+    ) -> MarkdownEvents<'c> {
+        let heading = render_heading_to_plaintext(section);
+        let anchor = if self.config.internal_link_format == InternalLinkFormat::Docusaurus {
+            docusaurus_slug(&heading)
+        } else {
+            slugify(heading)
+        };
+
+        vec![
+            Event::Start(Tag::Link {
+                link_type: LinkType::Inline,
+                dest_url: CowStr::from(format!("#{anchor}")),
+                title: CowStr::from(""),
+                id: CowStr::from(""),
+            }),
+            Event::Text(CowStr::from(reference.display())),
+            Event::End(TagEnd::Link),
+        ]
+    }
+
+    /// Split a reference to a note in a [linked vault][Exporter::add_vault] (`[[name::Note]]`)
+    /// into the vault and the file within it, or return `None` when it doesn't name a linked vault.
+    fn cross_vault_reference<'r>(
+        &self,
+        reference: ObsidianNoteReference<'r>,
+    ) -> Option<(&LinkedVault, &'r str)> {
+        let (name, file) = reference.file?.split_once("::")?;
+        let vault = self
+            .config
+            .linked_vaults
+            .iter()
+            .find(|vault| vault.name == name.trim())?;
+        Some((vault, file.trim()))
+    }
+
+    /// Build a link to a note in a [linked vault][Exporter::add_vault], if `reference` refers to
+    /// one. Unresolved references are rendered according to the [`UnresolvedLinkStyle`].
+    fn make_cross_vault_link<'c>(
+        &self,
+        reference: ObsidianNoteReference<'_>,
+        offset: usize,
+        context: &Context,
+    ) -> Option<MarkdownEvents<'c>> {
+        let (vault, file) = self.cross_vault_reference(reference)?;
+        let local_reference = ObsidianNoteReference {
+            file: Some(file),
+            ..reference
+        };
+        let target_file = self.linked_vault_index(vault).and_then(|index| {
+            let target = index.lookup(file)?;
+            Some(
+                target
+                    .strip_prefix(index.root())
+                    .unwrap_or(target)
+                    .to_path_buf(),
+            )
+        });
+        let Some(target_file) = target_file else {
+            warn!(
+                "Unable to find referenced note\n\tReference: '{}'\n\tSource: '{}'",
+                reference.file.unwrap_or_default(),
+                context.current_file().display(),
+            );
+            self.add_unresolved_link(reference, offset, context);
+            return Some(
+                self.config
+                    .unresolved_link_style
+                    .render(local_reference.display()),
+            );
+        };
+
+        let path = self
+            .output_path(&target_file)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let mut link = format!(
+            "{}/{}",
+            vault.base_url.trim_end_matches('/'),
+            utf8_percent_encode(&path, PERCENTENCODE_CHARS)
+        );
+        if let Some(section) = reference.section {
+            link.push('#');
+            link.push_str(&slugify(render_heading_to_plaintext(section)));
+        }
+        Some(vec![
+            Event::Start(Tag::Link {
+                link_type: LinkType::Inline,
+                dest_url: CowStr::from(link),
+                title: CowStr::from(""),
+                id: CowStr::from(""),
+            }),
+            Event::Text(CowStr::from(local_reference.display())),
+            Event::End(TagEnd::Link),
+        ])
+    }
+
+    /// Return the index of the [linked vault][Exporter::add_vault] `vault`, building it the first
+    /// time it's needed.
+    ///
+    /// The run state is only locked to look up the index, so building it doesn't block workers
+    /// which need the run state for anything else.
+    fn linked_vault_index(&self, vault: &LinkedVault) -> Option<Arc<VaultIndex>> {
+        let cell = Arc::clone(
+            self.state
+                .lock()
+                .expect("run state mutex poisoned")
+                .linked_vault_indexes
+                .entry(vault.name.clone())
+                .or_default(),
+        );
+        cell.get_or_init(|| {
+            VaultIndex::with_note_extensions(
+                &vault.path,
+                self.vault_walk_options(),
+                self.config.note_extensions.clone(),
+            )
+            .map_err(|err| {
+                warn!(
+                    "Unable to read linked vault '{}': {}\n\tVault: '{}'",
+                    vault.name,
+                    err,
+                    vault.path.display()
+                );
+            })
+            .ok()
+            .map(Arc::new)
+        })
+        .clone()
+    }
+
+    /// Find the file that a reference to `filename` within the note at `source` refers to.
+    ///
+    /// References to daily notes are resolved within the [daily note
+    /// folder][Exporter::daily_note_folder] first.
+    fn resolve_file<'v>(
         &self,
-        link_text: &'a str,
-        context: &'a Context,
-    ) -> Result<MarkdownEvents<'b>> {
-        let note_ref = ObsidianNoteReference::from_str(link_text);
+        filename: &str,
+        source: &Path,
+        vault_index: &'v VaultIndex,
+    ) -> Option<&'v PathBuf> {
+        self.config
+            .daily_note_folder
+            .as_ref()
+            .filter(|_| DAILY_NOTE_RE.is_match(filename))
+            .and_then(|folder| {
+                let daily_note = folder.join(filename);
+                vault_index.resolve(
+                    &daily_note.to_string_lossy(),
+                    source,
+                    LinkResolutionMode::Absolute,
+                )
+            })
+            .or_else(|| vault_index.resolve(filename, source, self.config.link_resolution_mode))
+            .or_else(|| {
+                let index_filename = self.config.index_filename.as_ref()?;
+                let index = format!("{}/{index_filename}", filename.trim_end_matches('/'));
+                vault_index.resolve(&index, source, self.config.link_resolution_mode)
+            })
+    }
 
-        let path = match note_ref.file {
-            Some(file) => lookup_filename_in_vault(file, self.vault_contents.as_ref().unwrap()),
+    /// Return the filename of the index page that the note at `file` is exported as, if it's named
+    /// after the folder containing it and an [index filename][Exporter::index_filename] is set.
+    ///
+    /// Notes are left where they are when their folder contains an index page of its own, rather
+    /// than overwriting it.
+    fn index_page_name(&self, file: &Path) -> Option<&str> {
+        let index_filename = self.config.index_filename.as_deref()?;
+        let folder = self.folder_note_folder(file)?;
+        (!folder.join(index_filename).is_file()).then_some(index_filename)
+    }
 
-            // If we have None file it is either to a section or id within the same file and thus
-            // the current embed logic will fail, recurssing until it reaches it's limit.
-            // For now we just bail early.
-            None => return Ok(self.make_link_to_file(note_ref, context)),
+    /// Return the folder containing the note at `file` if the note is named after it (such as
+    /// `docs/docs.md`), except at the root of the vault.
+    fn folder_note_folder<'p>(&self, file: &'p Path) -> Option<&'p Path> {
+        file.parent()
+            .filter(|folder| *folder != self.vault_root())
+            .filter(|folder| self.is_note(file) && folder.file_name() == file.file_stem())
+    }
+
+    /// Warn about the folder notes within `vault_index` which can't be exported as the index page
+    /// of their folder, because the folder already contains one (see
+    /// [`Exporter::index_filename`]).
+    fn warn_index_page_conflicts(&self, vault_index: &VaultIndex) {
+        let Some(index_filename) = &self.config.index_filename else {
+            return;
         };
+        for file in vault_index.contents() {
+            let Some(folder) = self.folder_note_folder(file) else {
+                continue;
+            };
+            let index_page = folder.join(index_filename);
+            if index_page.is_file() && index_page != *file {
+                warn!(
+                    "Folder already has an index page, exporting folder note as-is\n\tNote: \
+                     '{}'\n\tIndex page: '{}'",
+                    file.display(),
+                    index_page.display(),
+                );
+            }
+        }
+    }
 
-        if path.is_none() {
-            // TODO: Extract into configurable function.
-            eprintln!(
-                "Warning: Unable to find embedded note\n\tReference: '{}'\n\tSource: '{}'\n",
-                note_ref
+    /// Find the file that `reference` refers to, logging a warning when it can't be found.
+    ///
+    /// `offset` is the byte offset of the reference within the current note.
+    fn resolve_reference<'v>(
+        &self,
+        reference: ObsidianNoteReference<'_>,
+        offset: usize,
+        context: &'v Context,
+        vault_index: &'v VaultIndex,
+    ) -> Option<&'v PathBuf> {
+        let target_file = reference.file.map_or_else(
+            || Some(context.current_file()),
+            |file| self.resolve_file(file, context.current_file(), vault_index),
+        );
+        if target_file.is_none() {
+            warn!(
+                "Unable to find referenced note\n\tReference: '{}'\n\tSource: '{}'",
+                reference
                     .file
                     .unwrap_or_else(|| context.current_file().to_str().unwrap()),
                 context.current_file().display(),
             );
-            return Ok(vec![]);
+            self.add_unresolved_link(reference, offset, context);
         }
+        target_file
+    }
 
-        let path = path.unwrap();
-        let mut child_context = Context::from_parent(context, path);
-        let no_ext = OsString::new();
+    fn add_unresolved_link(
+        &self,
+        reference: ObsidianNoteReference<'_>,
+        offset: usize,
+        context: &Context,
+    ) {
+        self.state
+            .lock()
+            .expect("run state mutex poisoned")
+            .unresolved_links
+            .insert(UnresolvedLink {
+                source: context.current_file().clone(),
+                offset,
+                reference: reference.file.unwrap_or_default().to_owned(),
+            });
+    }
 
-        if !self.process_embeds_recursively && context.file_tree().contains(path) {
-            return Ok([
-                vec![Event::Text(CowStr::Borrowed("→ "))],
-                self.make_link_to_file(note_ref, &child_context),
-            ]
-            .concat());
+    /// Point the destination of a standard markdown link (`[text](../Other%20Note.md)`) at the
+    /// location the file it refers to is exported to, when [resolving relative
+    /// links][Exporter::resolve_relative_links]. Other events are returned unchanged.
+    ///
+    /// `offset` is the byte offset of the link within the current note.
+    fn resolve_markdown_link<'e>(
+        &self,
+        event: Event<'e>,
+        offset: usize,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) -> Event<'e> {
+        let Event::Start(Tag::Link {
+            link_type: LinkType::Inline,
+            dest_url,
+            title,
+            id,
+        }) = &event
+        else {
+            return event;
+        };
+        if !self.config.resolve_relative_links
+            || dest_url.is_empty()
+            || dest_url.starts_with('#')
+            || has_url_scheme(dest_url)
+        {
+            return event;
         }
 
-        let events = match path.extension().unwrap_or(&no_ext).to_str() {
-            Some("md") => {
-                let (frontmatter, mut events) = self.parse_obsidian_note(path, &child_context)?;
-                child_context.frontmatter = frontmatter;
-                if let Some(section) = note_ref.section {
-                    events = reduce_to_section(events, section);
-                }
-                for func in &self.embed_postprocessors {
-                    // Postprocessors running on embeds shouldn't be able to change frontmatter (or
-                    // any other metadata), so we give them a clone of the context.
-                    match func(&mut child_context, &mut events) {
-                        PostprocessorResult::StopHere => break,
-                        PostprocessorResult::StopAndSkipNote => {
-                            events = vec![];
-                        }
-                        PostprocessorResult::Continue => (),
-                    }
-                }
-                events
-            }
-            Some("png" | "jpg" | "jpeg" | "gif" | "webp" | "svg") => {
-                self.make_link_to_file(note_ref, &child_context)
-                    .into_iter()
-                    .map(|event| match event {
-                        // make_link_to_file returns a link to a file. With this we turn the link
-                        // into an image reference instead. Slightly hacky, but avoids needing
-                        // to keep another utility function around for this, or introducing an
-                        // extra parameter on make_link_to_file.
-                        Event::Start(Tag::Link {
-                            link_type,
-                            dest_url,
-                            title,
-                            id,
-                        }) => Event::Start(Tag::Image {
-                            link_type,
-                            dest_url: CowStr::from(dest_url.into_string()),
-                            title: CowStr::from(title.into_string()),
-                            id: CowStr::from(id.into_string()),
-                        }),
-                        Event::End(TagEnd::Link) => Event::End(TagEnd::Image),
-                        _ => event,
-                    })
-                    .collect()
-            }
-            _ => self.make_link_to_file(note_ref, &child_context),
+        let (file, fragment) = dest_url
+            .split_once('#')
+            .map_or((&**dest_url, None), |(file, fragment)| {
+                (file, Some(fragment))
+            });
+        let section = fragment.map(|heading| percent_decode_str(heading).decode_utf8_lossy());
+        let reference = ObsidianNoteReference {
+            file: Some(file),
+            section: section.as_deref(),
+            label: None,
         };
-        Ok(events)
+        let target_file = vault_index
+            .resolve(file, context.current_file(), LinkResolutionMode::Relative)
+            .or_else(|| self.resolve_file(file, context.current_file(), vault_index));
+        let Some(target_file) = target_file else {
+            warn!(
+                "Unable to find file referenced by markdown link\n\tReference: '{}'\n\tSource: '{}'",
+                dest_url,
+                context.current_file().display(),
+            );
+            self.add_unresolved_link(reference, offset, context);
+            return event;
+        };
+        let resolved = match self.make_link_to_path(target_file, reference, context, vault_index) {
+            mut link if !link.is_empty() => link.swap_remove(0),
+            _ => return event,
+        };
+        let Event::Start(Tag::Link {
+            dest_url: resolved_url,
+            ..
+        }) = resolved
+        else {
+            return event;
+        };
+        Event::Start(Tag::Link {
+            link_type: LinkType::Inline,
+            dest_url: resolved_url,
+            title: title.clone(),
+            id: id.clone(),
+        })
     }
 
-    fn make_link_to_file<'c>(
+    /// Record that `attachment` is referenced by an exported note, so it's copied into the
+    /// [assets directory][Exporter::assets_dir].
+    fn add_referenced_attachment(&self, attachment: &Path) {
+        self.state
+            .lock()
+            .expect("run state mutex poisoned")
+            .referenced_attachments
+            .insert(attachment.to_path_buf());
+    }
+
+    /// Build a link to the Docusaurus page generated from the note at `target_file`, which is
+    /// displayed using the text of `reference`.
+    fn make_docusaurus_link<'c>(
         &self,
+        target_file: &Path,
         reference: ObsidianNoteReference<'_>,
-        context: &Context,
     ) -> MarkdownEvents<'c> {
-        let target_file = reference.file.map_or_else(
-            || Some(context.current_file()),
-            |file| lookup_filename_in_vault(file, self.vault_contents.as_ref().unwrap()),
-        );
+        let vault_root = self.vault_root();
+        let doc_path = target_file
+            .strip_prefix(&self.start_at)
+            .or_else(|_| target_file.strip_prefix(&vault_root))
+            .unwrap_or(target_file);
+        let mut link = docusaurus_doc_url(&self.config.docs_base_path, doc_path);
+        if let Some(section) = reference.section {
+            link.push('#');
+            link.push_str(&docusaurus_slug(&render_heading_to_plaintext(section)));
+        }
 
-        if target_file.is_none() {
-            // TODO: Extract into configurable function.
-            eprintln!(
-                "Warning: Unable to find referenced note\n\tReference: '{}'\n\tSource: '{}'\n",
-                reference
-                    .file
-                    .unwrap_or_else(|| context.current_file().to_str().unwrap()),
-                context.current_file().display(),
-            );
-            return vec![
-                Event::Start(Tag::Emphasis),
-                Event::Text(CowStr::from(reference.display())),
-                Event::End(TagEnd::Emphasis),
-            ];
-        }
-        let target_file = target_file.unwrap();
-        // We use root_file() rather than current_file() here to make sure links are always
-        // relative to the outer-most note, which is the note which this content is inserted into
-        // in case of embedded notes.
-        let rel_link = diff_paths(
-            target_file,
-            context
-                .root_file()
-                .parent()
-                .expect("obsidian content files should always have a parent"),
-        )
-        .expect("should be able to build relative path when target file is found in vault");
+        vec![
+            Event::Start(Tag::Link {
+                link_type: LinkType::Inline,
+                dest_url: CowStr::from(link),
+                title: CowStr::from(""),
+                id: CowStr::from(""),
+            }),
+            Event::Text(CowStr::from(reference.display())),
+            Event::End(TagEnd::Link),
+        ]
+    }
 
+    /// Build a link to `target_file`, which is displayed using the text of `reference`.
+    fn make_link_to_path<'c>(
+        &self,
+        target_file: &Path,
+        reference: ObsidianNoteReference<'_>,
+        context: &Context,
+        vault_index: &VaultIndex,
+    ) -> MarkdownEvents<'c> {
+        if self.config.internal_link_format == InternalLinkFormat::Docusaurus
+            && self.is_note(target_file)
+        {
+            return self.make_docusaurus_link(target_file, reference);
+        }
+        let rel_link = match &self.config.assets_dir {
+            Some(assets_dir) if !self.is_note(target_file) => {
+                self.add_referenced_attachment(target_file);
+                let asset = self.destination_root().join(assets_dir).join(
+                    self.slugify_path(Path::new(
+                        target_file
+                            .file_name()
+                            .expect("attachments should have a filename"),
+                    )),
+                );
+                diff_paths(
+                    asset,
+                    context
+                        .destination
+                        .parent()
+                        .expect("note destinations should always have a parent"),
+                )
+                .expect("should be able to build relative path to the assets directory")
+            }
+            // Notes with a permalink or a mapped destination aren't exported to the location they
+            // have in the vault, so links from or to them are built from where both notes end up
+            // in the destination.
+            _ if self.config.destination_mapper.is_some()
+                || vault_index.permalink(target_file).is_some()
+                || vault_index.permalink(context.root_file()).is_some() =>
+            {
+                let relative_path = target_file
+                    .strip_prefix(&self.start_at)
+                    .or_else(|_| target_file.strip_prefix(self.vault_root()))
+                    .unwrap_or(target_file);
+                diff_paths(
+                    self.destination_path(target_file, relative_path, vault_index),
+                    context
+                        .destination_relative()
+                        .parent()
+                        .unwrap_or_else(|| Path::new("")),
+                )
+                .expect("should be able to build relative path between exported notes")
+            }
+            _ => {
+                // We use root_file() rather than current_file() here to make sure links are always
+                // relative to the outer-most note, which is the note which this content is
+                // inserted into in case of embedded notes.
+                let rel_link = diff_paths(
+                    target_file,
+                    context
+                        .root_file()
+                        .parent()
+                        .expect("obsidian content files should always have a parent"),
+                )
+                .expect("should be able to build relative path when target file is found in vault");
+                let rel_link = match self.index_page_name(target_file) {
+                    Some(index_filename) => rel_link.with_file_name(index_filename),
+                    None => rel_link,
+                };
+                self.output_path(&rel_link)
+            }
+        };
         let rel_link = rel_link.to_string_lossy();
         let mut link = utf8_percent_encode(&rel_link, PERCENTENCODE_CHARS).to_string();
 
         if let Some(section) = reference.section {
             link.push('#');
-            link.push_str(&slugify(section));
+            match pdf_page_fragment(target_file, section) {
+                Some(fragment) => link.push_str(fragment),
+                None => link.push_str(&slugify(render_heading_to_plaintext(section))),
+            }
         }
 
         let link_tag = Tag::Link {
-            link_type: pulldown_cmark::LinkType::Inline,
+            link_type: LinkType::Inline,
             dest_url: CowStr::from(link),
             title: CowStr::from(""),
             id: CowStr::from(""),
@@ -763,43 +3642,226 @@ impl<'a> Exporter<'a> {
     }
 }
 
+/// Turn a filename into a lowercase, hyphenated slug while preserving its extension.
+///
+/// This is intended to be used with [`Exporter::filename_slugifier`].
+///
+/// # Example
+///
+/// ```
+/// # use obsidian_export::slugify_filename;
+/// assert_eq!(slugify_filename("My Note.md"), "my-note.md");
+/// assert_eq!(slugify_filename("Diagram (v2).PNG"), "diagram-v2.png");
+/// ```
+#[must_use]
+pub fn slugify_filename(filename: &str) -> String {
+    let path = Path::new(filename);
+    let stem = path
+        .file_stem()
+        .map_or_else(|| filename.into(), |stem| stem.to_string_lossy());
+    match path.extension() {
+        Some(extension) => format!(
+            "{}.{}",
+            slugify(stem),
+            extension.to_string_lossy().to_lowercase()
+        ),
+        None => slugify(stem),
+    }
+}
+
+/// Build the URL of the page Docusaurus generates for the note at `path` (relative to the docs
+/// directory), rooted at `base_path`.
+fn docusaurus_doc_url(base_path: &str, path: &Path) -> String {
+    let mut names: Vec<String> = path
+        .with_extension("")
+        .iter()
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    // Category index notes are served at the URL of their directory.
+    let is_category_index = match names.as_slice() {
+        [.., directory, name] => {
+            ["index", "readme", directory.to_lowercase().as_str()].contains(&&*name.to_lowercase())
+        }
+        [name] => ["index", "readme"].contains(&&*name.to_lowercase()),
+        [] => false,
+    };
+    if is_category_index {
+        names.pop();
+    }
+    let segments: Vec<String> = names
+        .iter()
+        .map(|name| {
+            let name = DOCUSAURUS_NUMBER_PREFIX_RE
+                .captures(name)
+                .and_then(|captures| captures.name("name"))
+                .map_or(name.as_str(), |stripped| stripped.as_str());
+            utf8_percent_encode(&docusaurus_slug(name), PERCENTENCODE_CHARS).to_string()
+        })
+        .collect();
+    format!("{}/{}", base_path.trim_end_matches('/'), segments.join("/"))
+}
+
+/// Turn `text` into a slug the way Docusaurus does for heading anchors (using `github-slugger`):
+/// lowercase, with spaces replaced by hyphens and other punctuation removed.
+fn docusaurus_slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            _ if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Get the full path for the given filename when it's contained in `vault_contents`, taking into
 /// account:
 ///
-/// 1. Standard Obsidian note references not including a .md extension.
-/// 2. Case-insensitive matching
+/// 1. Standard Obsidian note references not including a .md extension (or any of the other
+///    `note_extensions`).
+/// 2. Case-insensitive matching, used only when there is no exact (case-sensitive) match.
 /// 3. Unicode normalization rules using normalization form C (<https://www.w3.org/TR/charmod-norm/#unicodeNormalization>)
+///
+/// When multiple files match, the one closest to the root of the vault is returned (see
+/// [`lookup_filename_near`]).
 fn lookup_filename_in_vault<'a>(
     filename: &str,
     vault_contents: &'a [PathBuf],
+    note_extensions: &[String],
+) -> Option<&'a PathBuf> {
+    lookup_filename_near(filename, vault_contents, None, note_extensions)
+}
+
+/// Like [`lookup_filename_in_vault`], but when multiple files match `filename`, prefer the one in
+/// `source_dir` (if given), then the one closest to the root of the vault. This mirrors how
+/// Obsidian resolves links written using the "shortest path when possible" format.
+fn lookup_filename_near<'a>(
+    filename: &str,
+    vault_contents: &'a [PathBuf],
+    source_dir: Option<&Path>,
+    note_extensions: &[String],
 ) -> Option<&'a PathBuf> {
-    let filename = PathBuf::from(filename);
+    // Vault-absolute references such as `[[/folder/Note]]` are matched like any other path.
+    let filename = PathBuf::from(filename.trim_start_matches('/'));
     let filename_normalized = filename.to_string_lossy().nfc().collect::<String>();
+    let filename_lowered = filename_normalized.to_lowercase();
+
+    // It would be convenient if we could just do `filename.set_extension("md")` at the start
+    // of this funtion so we don't need multiple separate + ".md" match cases here, however
+    // that would break with a reference of `[[Note.1]]` linking to `[[Note.1.md]]`.
+    let exact_matches: Vec<&PathBuf> = vault_contents
+        .iter()
+        .filter(|path| {
+            let path_normalized = PathBuf::from(path.to_string_lossy().nfc().collect::<String>());
+            matches_filename(&path_normalized, &filename_normalized, note_extensions)
+        })
+        .collect();
+    if let Some(exact_match) = closest_match(&exact_matches, source_dir) {
+        warn_if_ambiguous(&filename, &exact_matches, source_dir);
+        return Some(exact_match);
+    }
 
-    vault_contents.iter().find(|path| {
-        let path_normalized_str = path.to_string_lossy().nfc().collect::<String>();
-        let path_normalized = PathBuf::from(&path_normalized_str);
-        let path_normalized_lowered = PathBuf::from(&path_normalized_str.to_lowercase());
+    let candidates: Vec<&PathBuf> = vault_contents
+        .iter()
+        .filter(|path| {
+            let path_lowered = PathBuf::from(
+                path.to_string_lossy()
+                    .nfc()
+                    .collect::<String>()
+                    .to_lowercase(),
+            );
+            matches_filename(&path_lowered, &filename_lowered, note_extensions)
+        })
+        .collect();
 
-        // It would be convenient if we could just do `filename.set_extension("md")` at the start
-        // of this funtion so we don't need multiple separate + ".md" match cases here, however
-        // that would break with a reference of `[[Note.1]]` linking to `[[Note.1.md]]`.
+    let mut distinct_names: Vec<String> = candidates
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().nfc().collect())
+        .collect();
+    distinct_names.sort();
+    distinct_names.dedup();
+    if distinct_names.len() > 1 {
+        warn!(
+            "Ambiguous case-insensitive match for note reference\n\tReference: '{}'\n\tCandidates: {}",
+            filename.display(),
+            candidates
+                .iter()
+                .map(|path| format!("'{}'", path.display()))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    } else {
+        warn_if_ambiguous(&filename, &candidates, source_dir);
+    }
+    closest_match(&candidates, source_dir)
+}
 
-        path_normalized.ends_with(&filename_normalized)
-            || path_normalized.ends_with(filename_normalized.clone() + ".md")
-            || path_normalized_lowered.ends_with(filename_normalized.to_lowercase())
-            || path_normalized_lowered.ends_with(filename_normalized.to_lowercase() + ".md")
-    })
+/// Pick the file from `candidates` which is located in `source_dir`, or else the one with the
+/// fewest path components. Remaining ties are resolved in favor of the path which sorts first, so
+/// the result doesn't depend on the order in which files were found.
+fn closest_match<'a>(candidates: &[&'a PathBuf], source_dir: Option<&Path>) -> Option<&'a PathBuf> {
+    candidates
+        .iter()
+        .min_by_key(|path| {
+            (
+                !is_in_dir(path, source_dir),
+                path.components().count(),
+                **path,
+            )
+        })
+        .copied()
+}
+
+/// Log a warning listing the `candidates` for a reference to `filename` when there's more than
+/// one, unless one of them is located in `source_dir` (which is then picked by [`closest_match`]).
+fn warn_if_ambiguous(filename: &Path, candidates: &[&PathBuf], source_dir: Option<&Path>) {
+    if candidates.len() < 2 || candidates.iter().any(|path| is_in_dir(path, source_dir)) {
+        return;
+    }
+    let mut sorted = candidates.to_vec();
+    sorted.sort_by_key(|path| (path.components().count(), *path));
+    warn!(
+        "Ambiguous match for note reference, using the first candidate\n\tReference: \
+         '{}'\n\tCandidates: {}",
+        filename.display(),
+        sorted
+            .iter()
+            .map(|path| format!("'{}'", path.display()))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+}
+
+/// Whether `path` is located directly within `dir` (if given).
+fn is_in_dir(path: &Path, dir: Option<&Path>) -> bool {
+    dir.is_some_and(|dir| path.parent() == Some(dir))
+}
+
+/// Whether the note `content` consists of nothing but frontmatter.
+fn is_frontmatter_only(content: &str) -> bool {
+    has_frontmatter(content) && strip_frontmatter(content).trim().is_empty()
+}
+
+/// Serialize `frontmatter` as the contents of a data file in the given `format`.
+fn data_file_contents(
+    format: DataFormat,
+    frontmatter: &Frontmatter,
+) -> std::result::Result<String, serde_yaml::Error> {
+    match format {
+        DataFormat::Yaml => serde_yaml::to_string(frontmatter),
+        DataFormat::Json => {
+            let mut json = json_value(&Value::Mapping(frontmatter.clone()));
+            json.push('\n');
+            Ok(json)
+        }
+    }
 }
 
 fn render_mdevents_to_mdtext(markdown: &MarkdownEvents<'_>) -> String {
-    let mut buffer = String::new();
-    cmark_with_options(
-        markdown.iter(),
-        &mut buffer,
-        pulldown_cmark_to_cmark::Options::default(),
-    )
-    .expect("formatting to string not expected to fail");
+    let mut buffer = CmarkSerializer::default().serialize(markdown);
     buffer.push('\n');
     buffer
 }
@@ -817,6 +3879,21 @@ fn create_file(dest: &Path) -> Result<File> {
     Ok(file)
 }
 
+/// Return the creation and modification time of the file at `path` as [`CommitDates`], for files
+/// without git history. The modification time is used for both when the creation time isn't
+/// available.
+fn filesystem_dates(path: &Path) -> Option<CommitDates> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?;
+    let created = metadata.created().unwrap_or(modified);
+    let format =
+        |time: SystemTime| DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true);
+    Some(CommitDates {
+        created: format(created),
+        modified: format(modified),
+    })
+}
+
 fn copy_mtime(src: &Path, dest: &Path) -> Result<()> {
     let metadata = fs::metadata(src).context(ModTimeReadSnafu { path: src })?;
     let modified_time = metadata
@@ -827,6 +3904,39 @@ fn copy_mtime(src: &Path, dest: &Path) -> Result<()> {
     Ok(())
 }
 
+#[cfg(unix)]
+fn copy_permissions(src: &Path, dest: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(src)
+        .context(ReadSnafu { path: src })?
+        .permissions()
+        .mode();
+    fs::set_permissions(dest, fs::Permissions::from_mode(mode))
+        .context(PermissionsSetSnafu { path: dest })?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn copy_permissions(_src: &Path, _dest: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Run the blocking function `f` from within an async task. On tokio's multi-threaded runtime, the
+/// runtime moves its other tasks to another worker thread while `f` runs.
+#[cfg(feature = "async")]
+fn block_in_place<T>(f: impl FnOnce() -> T) -> T {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
 fn copy_file(src: &Path, dest: &Path) -> Result<()> {
     fs::copy(src, dest)
         .or_else(|err| {
@@ -840,59 +3950,257 @@ fn copy_file(src: &Path, dest: &Path) -> Result<()> {
     Ok(())
 }
 
-fn is_markdown_file(file: &Path) -> bool {
-    let no_ext = OsString::new();
-    let ext = file.extension().unwrap_or(&no_ext).to_string_lossy();
-    ext == "md"
+/// Return `section` if it selects a page of the PDF file at `file` (as in `[[doc.pdf#page=3]]`),
+/// in which case it's kept as-is rather than being turned into a heading anchor.
+fn pdf_page_fragment<'s>(file: &Path, section: &'s str) -> Option<&'s str> {
+    let is_pdf = file
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+    let page = section.strip_prefix("page=")?;
+    (is_pdf && !page.is_empty() && page.bytes().all(|byte| byte.is_ascii_digit()))
+        .then_some(section)
+}
+
+/// Give the links within `events` the [`EMBED_LINK_ID`], marking them as embeds of a file.
+fn mark_as_embed(events: MarkdownEvents<'_>) -> MarkdownEvents<'_> {
+    events
+        .into_iter()
+        .map(|event| match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                ..
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id: CowStr::Borrowed(EMBED_LINK_ID),
+            }),
+            _ => event,
+        })
+        .collect()
+}
+
+/// Check whether `frontmatter` excludes its note from the export through `export: false` or
+/// `publish: false`.
+fn opts_out_of_export(frontmatter: &Frontmatter) -> bool {
+    ["export", "publish"]
+        .iter()
+        .any(|key| frontmatter.get(*key) == Some(&Value::Bool(false)))
+}
+
+/// Normalize `path` to Unicode NFC, so files are exported under the same name regardless of the
+/// normalization form used by the filesystem of the vault (macOS commonly uses NFD).
+fn nfc_path(path: &Path) -> PathBuf {
+    path.to_str().map_or_else(
+        || path.to_path_buf(),
+        |utf8| PathBuf::from(utf8.nfc().collect::<String>()),
+    )
+}
+
+fn default_note_extensions() -> Vec<String> {
+    DEFAULT_NOTE_EXTENSIONS.map(ToOwned::to_owned).to_vec()
+}
+
+fn has_note_extension(file: &Path, note_extensions: &[String]) -> bool {
+    file.extension().is_some_and(|ext| {
+        note_extensions
+            .iter()
+            .any(|note_ext| ext == note_ext.as_str())
+    })
+}
+
+/// Check whether `path` is the file that `filename` refers to, either exactly or by leaving out
+/// one of the `note_extensions`.
+fn matches_filename(path: &Path, filename: &str, note_extensions: &[String]) -> bool {
+    path.ends_with(filename)
+        || note_extensions
+            .iter()
+            .any(|extension| path.ends_with(format!("{filename}.{extension}")))
+}
+
+/// Return the text of the first top-level heading within `events`, if any.
+fn first_heading(events: &MarkdownEvents<'_>) -> Option<String> {
+    let start = events.iter().position(|event| {
+        matches!(
+            event,
+            Event::Start(Tag::Heading {
+                level: HeadingLevel::H1,
+                ..
+            })
+        )
+    })?;
+    let text = events
+        .iter()
+        .skip(start)
+        .take_while(|event| !matches!(event, Event::End(TagEnd::Heading(_))))
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(&**text),
+            _ => None,
+        })
+        .collect::<String>();
+    Some(text.trim().to_owned())
 }
 
 /// Reduce a given `MarkdownEvents` to just those elements which are children of the given section
 /// (heading name).
+///
+/// Headings are compared using their rendered text, so `## A **bold** heading` matches a section
+/// named either `A bold heading` or `A **bold** heading`.
 fn reduce_to_section<'a>(events: MarkdownEvents<'a>, section: &str) -> MarkdownEvents<'a> {
-    let mut filtered_events = Vec::with_capacity(events.len());
-    let mut target_section_encountered = false;
-    let mut currently_in_target_section = false;
+    let section = render_heading_to_plaintext(section).to_lowercase();
+    let mut section_start = None;
+    let mut section_end = events.len();
     let mut section_level = HeadingLevel::H1;
-    let mut last_level = HeadingLevel::H1;
-    let mut last_tag_was_heading = false;
 
-    for event in events {
-        filtered_events.push(event.clone());
+    for (idx, event) in events.iter().enumerate() {
+        let Event::Start(Tag::Heading { level, .. }) = event else {
+            continue;
+        };
+        if section_start.is_some() {
+            if *level <= section_level {
+                section_end = idx;
+                break;
+            }
+            continue;
+        }
+        let heading_text = events
+            .iter()
+            .skip(idx)
+            .take_while(|heading_event| !matches!(heading_event, Event::End(TagEnd::Heading(_))))
+            .filter_map(|heading_event| match heading_event {
+                Event::Text(text) | Event::Code(text) | Event::InlineMath(text) => Some(&**text),
+                _ => None,
+            })
+            .collect::<String>();
+        if heading_text.to_lowercase() == section {
+            section_start = Some(idx);
+            section_level = *level;
+        }
+    }
+
+    match section_start {
+        Some(start) => events.into_iter().take(section_end).skip(start).collect(),
+        None => events,
+    }
+}
+
+/// Reduce a given `MarkdownEvents` to just the block identified by a block reference (`^blockid`).
+///
+/// The block is the paragraph, list item or heading ending with `^blockid`, or the block directly
+/// preceding it when the identifier is on a line of its own (as Obsidian does for quotes and
+/// tables). The identifier itself is removed from the returned events.
+fn reduce_to_block<'a>(events: MarkdownEvents<'a>, block_id: &str) -> MarkdownEvents<'a> {
+    let marker = format!("^{block_id}");
+    let mut open_tags: Vec<usize> = Vec::new();
+    let mut last_top_level_block = None;
+    let mut found = None;
+
+    for (idx, event) in events.iter().enumerate() {
         match event {
-            Event::Start(Tag::Heading { level, .. }) => {
-                last_tag_was_heading = true;
-                last_level = level;
-                if currently_in_target_section && level <= section_level {
-                    currently_in_target_section = false;
-                    filtered_events.pop();
+            Event::Start(_) => open_tags.push(idx),
+            Event::End(_) => {
+                let start = open_tags.pop();
+                if open_tags.is_empty() {
+                    last_top_level_block = start.map(|start_idx| (start_idx, idx));
                 }
             }
-            Event::Text(cowstr) => {
-                if !last_tag_was_heading {
-                    last_tag_was_heading = false;
-                    continue;
-                }
-                last_tag_was_heading = false;
-
-                if cowstr.to_string().to_lowercase() == section.to_lowercase() {
-                    target_section_encountered = true;
-                    currently_in_target_section = true;
-                    section_level = last_level;
-
-                    let current_event = filtered_events.pop().unwrap();
-                    let heading_start_event = filtered_events.pop().unwrap();
-                    filtered_events.clear();
-                    filtered_events.push(heading_start_event);
-                    filtered_events.push(current_event);
-                }
+            Event::Text(text) if text.trim_end().ends_with(&marker) => {
+                found = Some((idx, text.trim() == marker));
+                break;
+            }
+            _ => {}
+        }
+    }
+    let Some((marker_idx, marker_only)) = found else {
+        return events;
+    };
+
+    if marker_only && open_tags.len() == 1 {
+        // The identifier is a paragraph on its own, so it refers to the preceding block.
+        return match last_top_level_block {
+            Some((start, end)) => events
+                .into_iter()
+                .take(end.saturating_add(1))
+                .skip(start)
+                .collect(),
+            None => events,
+        };
+    }
+
+    // Walk the open tags from the innermost outwards to find the block containing the marker.
+    // Paragraphs inside of (loose) list items refer to the entire list item, in which case the
+    // enclosing list is needed to produce valid markdown.
+    let mut block_start = None;
+    let mut enclosing_list = None;
+    for idx in open_tags.iter().rev() {
+        match events.get(*idx) {
+            Some(Event::Start(Tag::Paragraph | Tag::Heading { .. })) if block_start.is_none() => {
+                block_start = Some(*idx);
             }
+            Some(Event::Start(Tag::Item)) => block_start = Some(*idx),
+            Some(Event::Start(Tag::List(first_number))) if block_start.is_some() => {
+                enclosing_list = Some(*first_number);
+                break;
+            }
+            _ if block_start.is_some() => break,
+            _ => {}
+        }
+    }
+    let Some(block_start) = block_start else {
+        return events;
+    };
+
+    let mut depth = 0_usize;
+    let mut block = Vec::new();
+    for (idx, event) in events.into_iter().enumerate().skip(block_start) {
+        match event {
+            Event::Start(_) => depth = depth.saturating_add(1),
+            Event::End(_) => depth = depth.saturating_sub(1),
             _ => {}
         }
-        if target_section_encountered && !currently_in_target_section {
-            return filtered_events;
+        if idx == marker_idx {
+            if let Event::Text(text) = &event {
+                let text = text.trim_end().trim_end_matches(marker.as_str()).trim_end();
+                block.push(Event::Text(CowStr::from(text.to_owned())));
+            }
+        } else {
+            block.push(event);
+        }
+        if depth == 0 {
+            break;
         }
     }
-    filtered_events
+
+    match enclosing_list {
+        Some(first_number) => {
+            block.insert(0, Event::Start(Tag::List(first_number)));
+            block.push(Event::End(TagEnd::List(first_number.is_some())));
+            block
+        }
+        None => block,
+    }
+}
+
+/// Render the (inline) markdown of a heading to plain text, stripping formatting such as emphasis,
+/// code spans and links.
+///
+/// This mirrors the text which ends up in a rendered heading, which is what anchors are derived
+/// from.
+fn render_heading_to_plaintext(heading: &str) -> String {
+    // Parsing the text as an ATX heading prevents it from being interpreted as a block-level
+    // construct, such as `1. Introduction` turning into an ordered list.
+    let markdown = format!("# {heading}");
+    Parser::new_ext(
+        &markdown,
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_MATH,
+    )
+    .filter_map(|event| match event {
+        Event::Text(text) | Event::Code(text) | Event::InlineMath(text) => Some(text.into_string()),
+        _ => None,
+    })
+    .collect()
 }
 
 fn event_to_owned<'a>(event: Event<'_>) -> Event<'a> {
@@ -1007,6 +4315,9 @@ mod tests {
             PathBuf::from("Note.1.md"),
             PathBuf::from("nested/NoteA.md"),
             PathBuf::from("Note\u{E4}.md"), // Noteä.md, see also encodings() below
+            PathBuf::from("Mixed Case.md"),
+            PathBuf::from("mixed case.md"),
+            PathBuf::from("Folder/Sub Note.md"),
         ]
     });
 
@@ -1045,6 +4356,15 @@ mod tests {
     #[case("notea", "NoteA.md")]
     #[case("NESTED/notea.md", "nested/NoteA.md")]
     #[case("NESTED/notea", "nested/NoteA.md")]
+    // Vault-absolute paths
+    #[case("/nested/NoteA", "nested/NoteA.md")]
+    #[case("folder/sub note", "Folder/Sub Note.md")]
+    #[case("FOLDER/Sub Note.md", "Folder/Sub Note.md")]
+    // Exact case is preferred over case-insensitive matches
+    #[case("Mixed Case", "Mixed Case.md")]
+    #[case("mixed case", "mixed case.md")]
+    // Ambiguous case-insensitive matches fall back to the first candidate
+    #[case("MIXED CASE", "Mixed Case.md")]
     // "Latin Small Letter A with Diaeresis" (U+00E4)
     #[case("Note\u{E4}.md", "Note\u{E4}.md")]
     #[case("Note\u{E4}", "Note\u{E4}.md")]
@@ -1061,10 +4381,49 @@ mod tests {
     #[case("Note\u{41}\u{308}.md", "Note\u{E4}.md")]
     #[case("Note\u{41}\u{308}", "Note\u{E4}.md")]
     fn test_lookup_filename_in_vault(#[case] input: &str, #[case] expected: &str) {
-        let result = lookup_filename_in_vault(input, &VAULT);
+        let result = lookup_filename_in_vault(input, &VAULT, &default_note_extensions());
         println!("Test input: {input:?}");
         println!("Expecting: {expected:?}");
-        println!("Got: {:?}", result.unwrap_or(&PathBuf::from("")));
+        println!("Got: {result:?}");
         assert_eq!(result, Some(&PathBuf::from(expected)));
     }
+
+    #[rstest]
+    #[case("Note", None, "Note.md")]
+    #[case("Note", Some("a"), "a/Note.md")]
+    #[case("Note", Some("b/deep"), "Note.md")]
+    #[case("b/Note", Some("a"), "b/Note.md")]
+    #[case("Other", None, "a/Other.md")]
+    #[case("deep/Other", Some("a"), "b/deep/Other.md")]
+    fn test_lookup_filename_near(
+        #[case] input: &str,
+        #[case] source_dir: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let vault = [
+            PathBuf::from("a/Note.md"),
+            PathBuf::from("a/Other.md"),
+            PathBuf::from("b/Note.md"),
+            PathBuf::from("b/deep/Other.md"),
+            PathBuf::from("Note.md"),
+        ];
+        let result = lookup_filename_near(
+            input,
+            &vault,
+            source_dir.map(Path::new),
+            &default_note_extensions(),
+        );
+        assert_eq!(result, Some(&PathBuf::from(expected)));
+    }
+
+    #[rstest]
+    #[case("Heading", "Heading")]
+    #[case("A **bold** heading", "A bold heading")]
+    #[case("A `code` heading", "A code heading")]
+    #[case("See [the docs](https://example.com)", "See the docs")]
+    #[case("1. Introduction", "1. Introduction")]
+    #[case("C# notes", "C# notes")]
+    fn test_render_heading_to_plaintext(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(render_heading_to_plaintext(input), expected);
+    }
 }
@@ -1,13 +1,54 @@
-use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::{env, io};
 
+use chrono::DateTime;
 use eyre::{eyre, Result};
 use gumdrop::Options;
+use log::{Level, LevelFilter, Log, Metadata, Record};
 use obsidian_export::postprocessors::{filter_by_tags, softbreaks_to_hardbreaks};
-use obsidian_export::{ExportError, Exporter, FrontmatterStrategy, WalkOptions};
+use obsidian_export::{
+    slugify_filename,
+    DataFormat,
+    DryRunAction,
+    DuplicateEmbedBehavior,
+    ExcludedEmbedBehavior,
+    ExportError,
+    Exporter,
+    FrontmatterStrategy,
+    InternalLinkFormat,
+    LineEnding,
+    LinkResolutionMode,
+    ListMerge,
+    WalkOptions,
+};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Prints warnings emitted during the export to stderr.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let label = match record.level() {
+            Level::Error => "Error",
+            _ => "Warning",
+        };
+        eprintln!("{label}: {}\n", record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
 #[derive(Debug, Options)]
 #[allow(clippy::struct_excessive_bools)]
 struct Opts {
@@ -20,12 +61,34 @@ struct Opts {
     #[options(help = "Read notes from this source", free, required)]
     source: Option<PathBuf>,
 
-    #[options(help = "Write notes to this destination", free, required)]
+    #[options(
+        help = "Write notes to this destination (use '-' to write a single note to stdout)",
+        free,
+        required
+    )]
     destination: Option<PathBuf>,
 
     #[options(no_short, help = "Only export notes under this sub-path")]
     start_at: Option<PathBuf>,
 
+    #[options(
+        no_short,
+        help = "Copy attachments referenced by notes into this directory (relative to destination)"
+    )]
+    assets_dir: Option<PathBuf>,
+
+    #[options(
+        no_short,
+        help = "Use this file extension for exported notes (e.g. 'markdown' or 'mdx')"
+    )]
+    output_extension: Option<String>,
+
+    #[options(
+        no_short,
+        help = "Export folder notes as, and point folder links at, this index page (e.g. 'index.md')"
+    )]
+    index_filename: Option<String>,
+
     #[options(
         help = "Frontmatter strategy (one of: always, never, auto)",
         no_short,
@@ -35,6 +98,41 @@ struct Opts {
     )]
     frontmatter_strategy: FrontmatterStrategy,
 
+    #[options(
+        help = "How paths in links are interpreted (one of: shortest, relative, absolute)",
+        no_short,
+        long = "link-format",
+        parse(try_from_str = "link_resolution_mode_from_str"),
+        default = "shortest"
+    )]
+    link_resolution_mode: LinkResolutionMode,
+
+    #[options(
+        help = "How embeds of excluded notes are handled (one of: link, omit, inline)",
+        no_short,
+        long = "excluded-embeds",
+        parse(try_from_str = "excluded_embed_behavior_from_str"),
+        default = "omit"
+    )]
+    excluded_embed_behavior: ExcludedEmbedBehavior,
+
+    #[options(
+        help = "How repeated embeds of the same note are handled (one of: inline, link, omit)",
+        no_short,
+        long = "duplicate-embeds",
+        parse(try_from_str = "duplicate_embed_behavior_from_str"),
+        default = "inline"
+    )]
+    duplicate_embed_behavior: DuplicateEmbedBehavior,
+
+    #[options(
+        help = "Line endings of exported notes (one of: lf, crlf, preserve)",
+        no_short,
+        parse(try_from_str = "line_ending_from_str"),
+        default = "lf"
+    )]
+    line_ending: LineEnding,
+
     #[options(
         no_short,
         help = "Read ignore patterns from files with this name",
@@ -42,18 +140,102 @@ struct Opts {
     )]
     ignore_file: String,
 
+    #[options(
+        no_short,
+        help = "Read ignore patterns which apply to the whole vault from this file"
+    )]
+    global_ignore_file: Vec<PathBuf>,
+
+    #[options(
+        no_short,
+        help = "Copy files matching this pattern as-is, even when they would be ignored",
+        meta = "PATTERN"
+    )]
+    passthrough: Vec<String>,
+
     #[options(no_short, help = "Exclude files with this tag from the export")]
     skip_tags: Vec<String>,
 
     #[options(no_short, help = "Export only files with this tag")]
     only_tags: Vec<String>,
 
+    #[options(
+        no_short,
+        help = "Skip notes with 'export: false' or 'publish: false' in their frontmatter",
+        default = "false"
+    )]
+    respect_export_frontmatter: bool,
+
+    #[options(
+        no_short,
+        help = "Resolve [[links]] within frontmatter values",
+        default = "false"
+    )]
+    resolve_frontmatter_links: bool,
+
+    #[options(
+        no_short,
+        help = "Resolve standard markdown links to notes and files, like [text](../Note.md)",
+        default = "false"
+    )]
+    resolve_relative_links: bool,
+
+    #[options(
+        no_short,
+        help = "Write a sitemap.xml listing the exported notes below this base URL",
+        meta = "URL"
+    )]
+    sitemap: Option<String>,
+
+    #[options(
+        no_short,
+        help = "Resolve links to daily notes (like [[2023-01-05]]) within this folder first"
+    )]
+    daily_note_folder: Option<PathBuf>,
+
+    #[options(
+        no_short,
+        help = "Merge frontmatter defaults from files with this name into the notes of their directory"
+    )]
+    frontmatter_defaults_file: Option<String>,
+
+    #[options(
+        no_short,
+        help = "Append lists under this frontmatter key to the list of the frontmatter defaults"
+    )]
+    append_frontmatter_list: Vec<String>,
+
+    #[options(
+        no_short,
+        help = "Export notes without content besides frontmatter as data files (yaml or json)",
+        meta = "FORMAT",
+        parse(try_from_str = "data_format_from_str")
+    )]
+    frontmatter_only_as_data: Option<DataFormat>,
+
     #[options(no_short, help = "Export hidden files", default = "false")]
     hidden: bool,
 
     #[options(no_short, help = "Disable git integration", default = "false")]
     no_git: bool,
 
+    #[options(no_short, help = "Follow symbolic links", default = "false")]
+    follow_symlinks: bool,
+
+    #[options(
+        no_short,
+        help = "Keep [[links]] and ![[embeds]] as-is instead of converting them to markdown",
+        default = "false"
+    )]
+    keep_wikilinks: bool,
+
+    #[options(
+        no_short,
+        help = "Fail when notes contain links which can't be resolved",
+        default = "false"
+    )]
+    fail_on_unresolved_links: bool,
+
     #[options(no_short, help = "Don't process embeds recursively", default = "false")]
     no_recursive_embeds: bool,
 
@@ -64,12 +246,48 @@ struct Opts {
     )]
     preserve_mtime: bool,
 
+    #[options(
+        no_short,
+        help = "Preserve the permission bits of copied attachments",
+        default = "false"
+    )]
+    preserve_permissions: bool,
+
+    #[options(
+        no_short,
+        help = "Set 'created' and 'modified' frontmatter from the git history of notes",
+        default = "false"
+    )]
+    git_dates: bool,
+
+    #[options(
+        no_short,
+        help = "Only export files modified after this RFC 3339 timestamp (e.g. '2024-01-31T00:00:00Z')",
+        meta = "TIMESTAMP",
+        parse(try_from_str = "timestamp_from_str")
+    )]
+    modified_since: Option<SystemTime>,
+
     #[options(
         no_short,
         help = "Convert soft line breaks to hard line breaks. This mimics Obsidian's 'Strict line breaks' setting",
         default = "false"
     )]
     hard_linebreaks: bool,
+
+    #[options(
+        no_short,
+        help = "Show which files would be written, without actually writing anything",
+        default = "false"
+    )]
+    dry_run: bool,
+
+    #[options(
+        no_short,
+        help = "Turn filenames into lowercase, hyphenated slugs (e.g. 'My Note.md' becomes 'my-note.md')",
+        default = "false"
+    )]
+    slugify_filenames: bool,
 }
 
 fn frontmatter_strategy_from_str(input: &str) -> Result<FrontmatterStrategy> {
@@ -81,6 +299,67 @@ fn frontmatter_strategy_from_str(input: &str) -> Result<FrontmatterStrategy> {
     }
 }
 
+fn link_resolution_mode_from_str(input: &str) -> Result<LinkResolutionMode> {
+    match input {
+        "shortest" => Ok(LinkResolutionMode::ShortestPath),
+        "relative" => Ok(LinkResolutionMode::Relative),
+        "absolute" => Ok(LinkResolutionMode::Absolute),
+        _ => Err(eyre!("must be one of: shortest, relative, absolute")),
+    }
+}
+
+fn excluded_embed_behavior_from_str(input: &str) -> Result<ExcludedEmbedBehavior> {
+    match input {
+        "link" => Ok(ExcludedEmbedBehavior::LinkTo),
+        "omit" => Ok(ExcludedEmbedBehavior::Omit),
+        "inline" => Ok(ExcludedEmbedBehavior::Inline),
+        _ => Err(eyre!("must be one of: link, omit, inline")),
+    }
+}
+
+fn line_ending_from_str(input: &str) -> Result<LineEnding> {
+    match input {
+        "lf" => Ok(LineEnding::Lf),
+        "crlf" => Ok(LineEnding::Crlf),
+        "preserve" => Ok(LineEnding::PreserveSource),
+        _ => Err(eyre!("must be one of: lf, crlf, preserve")),
+    }
+}
+
+fn duplicate_embed_behavior_from_str(input: &str) -> Result<DuplicateEmbedBehavior> {
+    match input {
+        "inline" => Ok(DuplicateEmbedBehavior::Inline),
+        "link" => Ok(DuplicateEmbedBehavior::LinkTo),
+        "omit" => Ok(DuplicateEmbedBehavior::Omit),
+        _ => Err(eyre!("must be one of: inline, link, omit")),
+    }
+}
+
+fn data_format_from_str(input: &str) -> Result<DataFormat> {
+    match input {
+        "yaml" => Ok(DataFormat::Yaml),
+        "json" => Ok(DataFormat::Json),
+        _ => Err(eyre!("must be one of: yaml, json")),
+    }
+}
+
+fn timestamp_from_str(input: &str) -> Result<SystemTime> {
+    let timestamp = DateTime::parse_from_rfc3339(input)
+        .map_err(|err| eyre!("must be an RFC 3339 timestamp: {err}"))?;
+    Ok(SystemTime::from(timestamp))
+}
+
+fn print_dry_run_action(action: &DryRunAction) {
+    match action {
+        DryRunAction::Create { destination, .. } => println!("create: {}", destination.display()),
+        DryRunAction::Overwrite { destination, .. } => {
+            println!("overwrite: {}", destination.display());
+        }
+        DryRunAction::Skip { source } => println!("skip: {}", source.display()),
+        _ => println!("{action:?}"),
+    }
+}
+
 fn main() {
     // Due to the use of free arguments in Opts, we must bypass Gumdrop to determine whether the
     // version flag was specified. Without this, "missing required free argument" would get printed
@@ -91,21 +370,70 @@ fn main() {
     }
 
     let args = Opts::parse_args_default_or_exit();
+    log::set_logger(&LOGGER).expect("logger should only be set once");
+    log::set_max_level(LevelFilter::Warn);
     let root = args.source.unwrap();
     let destination = args.destination.unwrap();
+    let stdout_note = (destination == Path::new("-")).then(|| root.clone());
 
     let walk_options = WalkOptions {
         ignore_filename: &args.ignore_file,
         ignore_hidden: !args.hidden,
         honor_gitignore: !args.no_git,
+        follow_symlinks: args.follow_symlinks,
         ..Default::default()
     };
 
     let mut exporter = Exporter::new(root, destination);
     exporter.frontmatter_strategy(args.frontmatter_strategy);
+    exporter.line_ending(args.line_ending);
+    exporter.link_resolution_mode(args.link_resolution_mode);
+    if args.keep_wikilinks {
+        exporter.internal_link_format(InternalLinkFormat::Wikilink);
+    }
     exporter.process_embeds_recursively(!args.no_recursive_embeds);
+    exporter.excluded_embed_behavior(args.excluded_embed_behavior);
+    exporter.duplicate_embed_behavior(args.duplicate_embed_behavior);
+    exporter.fail_on_unresolved_links(args.fail_on_unresolved_links);
+    exporter.respect_export_frontmatter(args.respect_export_frontmatter);
+    exporter.resolve_frontmatter_links(args.resolve_frontmatter_links);
+    exporter.resolve_relative_links(args.resolve_relative_links);
+    if let Some(folder) = args.daily_note_folder {
+        exporter.daily_note_folder(folder);
+    }
+    if let Some(filename) = args.frontmatter_defaults_file {
+        exporter.frontmatter_defaults_file(filename);
+    }
+    for key in args.append_frontmatter_list {
+        exporter.frontmatter_list_merge(key, ListMerge::Append);
+    }
+    exporter.frontmatter_only_as_data(args.frontmatter_only_as_data);
+    exporter.write_sitemap(args.sitemap);
     exporter.preserve_mtime(args.preserve_mtime);
+    exporter.preserve_permissions(args.preserve_permissions);
+    exporter.git_dates(args.git_dates);
+    if let Some(cutoff) = args.modified_since {
+        exporter.modified_since(cutoff);
+    }
+    exporter.assets_dir(args.assets_dir);
+    if let Some(extension) = args.output_extension {
+        exporter.output_extension(extension);
+    }
+    if let Some(filename) = args.index_filename {
+        exporter.index_filename(filename);
+    }
+    exporter.dry_run(args.dry_run);
+    exporter.on_dry_run_action(&print_dry_run_action);
+    if args.slugify_filenames {
+        exporter.filename_slugifier(&slugify_filename);
+    }
     exporter.walk_options(walk_options);
+    for file in args.global_ignore_file {
+        exporter.add_ignore_file(file);
+    }
+    for pattern in &args.passthrough {
+        exporter.add_passthrough_pattern(pattern);
+    }
 
     if args.hard_linebreaks {
         exporter.add_postprocessor(&softbreaks_to_hardbreaks);
@@ -118,36 +446,62 @@ fn main() {
         exporter.start_at(path);
     }
 
-    #[allow(clippy::pattern_type_mismatch)]
-    #[allow(clippy::ref_patterns)]
-    #[allow(clippy::shadow_unrelated)]
-    if let Err(err) = exporter.run() {
-        match err {
-            ExportError::FileExportError {
-                ref path,
-                ref source,
-            } => match &**source {
-                // An arguably better way of enhancing error reports would be to construct a custom
-                // `eyre::EyreHandler`, but that would require a fair amount of boilerplate and
-                // reimplementation of basic reporting.
-                ExportError::RecursionLimitExceeded { file_tree } => {
-                    eprintln!(
-                        "Error: {:?}",
-                        eyre!(
-                            "'{}' exceeds the maximum nesting limit of embeds",
-                            path.display()
-                        )
-                    );
-                    eprintln!("\nFile tree:");
-                    for (idx, path) in file_tree.iter().enumerate() {
-                        eprintln!("  {}-> {}", "  ".repeat(idx), path.display());
-                    }
-                    eprintln!("\nHint: Ensure notes are non-recursive, or specify --no-recursive-embeds to break cycles");
-                }
-                _ => eprintln!("Error: {:?}", eyre!(err)),
-            },
-            _ => eprintln!("Error: {:?}", eyre!(err)),
-        };
+    let result = match stdout_note {
+        Some(note) => {
+            let note = Path::new(note.file_name().unwrap_or_default());
+            exporter.to_writer(note, &mut io::stdout().lock())
+        }
+        None => exporter.run(),
+    };
+    if let Err(err) = result {
+        report_error(err);
         std::process::exit(1);
     };
 }
+
+#[allow(clippy::pattern_type_mismatch)]
+#[allow(clippy::ref_patterns)]
+#[allow(clippy::shadow_unrelated)]
+fn report_error(err: ExportError) {
+    match err {
+        ExportError::FileExportError {
+            ref path,
+            ref source,
+        } => match &**source {
+            // An arguably better way of enhancing error reports would be to construct a custom
+            // `eyre::EyreHandler`, but that would require a fair amount of boilerplate and
+            // reimplementation of basic reporting.
+            ExportError::RecursionLimitExceeded { file_tree } => {
+                eprintln!(
+                    "Error: {:?}",
+                    eyre!(
+                        "'{}' exceeds the maximum nesting limit of embeds",
+                        path.display()
+                    )
+                );
+                eprintln!("\nFile tree:");
+                for (idx, path) in file_tree.iter().enumerate() {
+                    eprintln!("  {}-> {}", "  ".repeat(idx), path.display());
+                }
+                eprintln!("\nHint: Ensure notes are non-recursive, or specify --no-recursive-embeds to break cycles");
+            }
+            _ => eprintln!("Error: {:?}", eyre!(err)),
+        },
+        ExportError::UnresolvedLinks { ref links } => {
+            eprintln!(
+                "Error: {:?}",
+                eyre!("{} links could not be resolved", links.len())
+            );
+            eprintln!("\nUnresolved links:");
+            for link in links {
+                eprintln!(
+                    "  '{}' (in '{}', at byte {})",
+                    link.reference,
+                    link.source.display(),
+                    link.offset
+                );
+            }
+        }
+        _ => eprintln!("Error: {:?}", eyre!(err)),
+    }
+}
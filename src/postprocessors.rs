@@ -1,9 +1,41 @@
 //! A collection of officially maintained [postprocessors][crate::Postprocessor].
 
-use pulldown_cmark::Event;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+use std::{fmt, mem};
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use log::warn;
+use pathdiff::diff_paths;
+use percent_encoding::{percent_decode_str, utf8_percent_encode};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, Parser, Tag, TagEnd};
+use regex::{Captures, Regex};
 use serde_yaml::Value;
+use slug::slugify;
+use snafu::Snafu;
+
+use super::frontmatter::json_value;
+use super::{
+    docusaurus_slug,
+    Context,
+    Frontmatter,
+    InternalLinkFormat,
+    MarkdownEvents,
+    PostprocessorResult,
+    EMBED_LINK_ID,
+    PERCENTENCODE_CHARS,
+};
 
-use super::{Context, MarkdownEvents, PostprocessorResult};
+static BARE_URL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bhttps?://[^\s<>]+").unwrap());
+static CALLOUT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[!(?P<kind>[\w-]+)\](?P<fold>[+-])?\s*(?P<title>.*)$").unwrap()
+});
+static TEMPLATE_TOKEN_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").unwrap());
 
 /// This postprocessor converts all soft line breaks to hard line breaks. Enabling this mimics
 /// Obsidian's _'Strict line breaks'_ setting.
@@ -19,6 +51,2510 @@ pub fn softbreaks_to_hardbreaks(
     PostprocessorResult::Continue
 }
 
+/// This postprocessor removes the blank lines left behind when other postprocessors remove
+/// content from a note, so that blocks are separated by at most one blank line.
+///
+/// Paragraphs and HTML blocks which are empty (or only contain whitespace) are removed, and runs
+/// of line breaks within paragraphs are collapsed into a single line break. Code blocks are left
+/// untouched. As this cleans up after other postprocessors, it should be added after them.
+pub fn collapse_blank_lines(
+    _context: &mut Context,
+    events: &mut MarkdownEvents<'_>,
+) -> PostprocessorResult {
+    let mut collapsed = Vec::with_capacity(events.len());
+    let mut block: Option<MarkdownEvents<'_>> = None;
+    for event in mem::take(events) {
+        match event {
+            Event::Start(Tag::Paragraph | Tag::HtmlBlock) => block = Some(vec![event]),
+            Event::End(TagEnd::Paragraph) => {
+                let content = block.take().unwrap_or_default().into_iter().skip(1);
+                let content = collapse_line_breaks(content);
+                if !content.is_empty() {
+                    collapsed.push(Event::Start(Tag::Paragraph));
+                    collapsed.extend(content);
+                    collapsed.push(event);
+                }
+            }
+            Event::End(TagEnd::HtmlBlock) => {
+                let html = block.take().unwrap_or_default();
+                if !html.iter().skip(1).all(is_blank) {
+                    collapsed.extend(html);
+                    collapsed.push(event);
+                }
+            }
+            _ => match &mut block {
+                Some(block) => block.push(event),
+                None => collapsed.push(event),
+            },
+        }
+    }
+    *events = collapsed;
+    PostprocessorResult::Continue
+}
+
+/// Collapse runs of line breaks within the content of a paragraph into a single line break, and
+/// remove line breaks at its start and end. Whitespace-only text containing a newline counts as a
+/// line break, and whitespace-only text at the start of a line is removed.
+fn collapse_line_breaks<'a>(content: impl Iterator<Item = Event<'a>>) -> MarkdownEvents<'a> {
+    let mut collapsed: MarkdownEvents<'a> = Vec::new();
+    for event in content {
+        let event = match event {
+            Event::Text(text) if text.trim().is_empty() && text.contains('\n') => Event::SoftBreak,
+            other => other,
+        };
+        // Whitespace at the start of a line is insignificant.
+        let starts_line = collapsed.last().map_or(true, is_line_break);
+        if starts_line && (is_line_break(&event) || is_blank(&event)) {
+            continue;
+        }
+        collapsed.push(event);
+    }
+    while collapsed.last().is_some_and(is_line_break) {
+        collapsed.pop();
+    }
+    collapsed
+}
+
+const fn is_line_break(event: &Event<'_>) -> bool {
+    matches!(event, Event::SoftBreak | Event::HardBreak)
+}
+
+fn is_blank(event: &Event<'_>) -> bool {
+    match event {
+        Event::Text(text) | Event::Html(text) => text.trim().is_empty(),
+        _ => is_line_break(event),
+    }
+}
+
+/// This postprocessor turns bare URLs (`https://...` or `http://...`) within the text of a note into
+/// autolinks (`<https://...>`), as not all renderers linkify them on their own.
+///
+/// Punctuation directly following a URL, such as the period ending a sentence, isn't considered
+/// part of it, although closing parentheses are kept when they balance opening ones within the URL.
+/// URLs which are already part of a link, code or HTML are left untouched, as is the frontmatter.
+pub fn linkify_bare_urls(
+    _context: &mut Context,
+    events: &mut MarkdownEvents<'_>,
+) -> PostprocessorResult {
+    let mut linkified = Vec::with_capacity(events.len());
+    let mut text = String::new();
+    let mut link_depth = 0_usize;
+    let mut in_code_block = false;
+    for event in mem::take(events) {
+        match event {
+            // Text may be split across multiple events, which would split URLs as well.
+            Event::Text(fragment) if link_depth == 0 && !in_code_block => {
+                text.push_str(&fragment);
+                continue;
+            }
+            Event::Start(Tag::Link { .. } | Tag::Image { .. }) => {
+                link_depth = link_depth.saturating_add(1);
+            }
+            Event::End(TagEnd::Link | TagEnd::Image) => link_depth = link_depth.saturating_sub(1),
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            _ => {}
+        }
+        linkify_text(&mem::take(&mut text), &mut linkified);
+        linkified.push(event);
+    }
+    linkify_text(&text, &mut linkified);
+    *events = linkified;
+    PostprocessorResult::Continue
+}
+
+/// Push `text` onto `events`, turning any bare URLs within it into autolinks.
+fn linkify_text(text: &str, events: &mut MarkdownEvents<'_>) {
+    let mut last_end = 0;
+    for found in BARE_URL_RE.find_iter(text) {
+        let url = trim_url(found.as_str());
+        if let Some(before) = text
+            .get(last_end..found.start())
+            .filter(|before| !before.is_empty())
+        {
+            events.push(Event::Text(CowStr::from(before.to_owned())));
+        }
+        events.extend([
+            Event::Start(Tag::Link {
+                link_type: LinkType::Autolink,
+                dest_url: CowStr::from(url.to_owned()),
+                title: CowStr::from(""),
+                id: CowStr::from(""),
+            }),
+            Event::Text(CowStr::from(url.to_owned())),
+            Event::End(TagEnd::Link),
+        ]);
+        last_end = found.start().saturating_add(url.len());
+    }
+    if let Some(rest) = text.get(last_end..).filter(|rest| !rest.is_empty()) {
+        events.push(Event::Text(CowStr::from(rest.to_owned())));
+    }
+}
+
+/// Remove trailing punctuation which most likely isn't part of `url`, keeping closing parentheses
+/// and brackets which balance opening ones within it.
+fn trim_url(mut url: &str) -> &str {
+    loop {
+        let unbalanced = |open: char, close: char| {
+            url.ends_with(close) && url.matches(close).count() > url.matches(open).count()
+        };
+        let trimmed = url.trim_end_matches(['.', ',', ':', ';', '!', '?', '\'', '"', '*', '_']);
+        if trimmed != url {
+            url = trimmed;
+        } else if unbalanced('(', ')') || unbalanced('[', ']') {
+            url = url.get(..url.len().saturating_sub(1)).unwrap_or_default();
+        } else {
+            return url;
+        }
+    }
+}
+
+/// This postprocessor replaces a placeholder paragraph with a table of contents of the note.
+///
+/// Any paragraph consisting of just `marker` (such as `%% toc %%`) is replaced with a bullet list
+/// linking to each heading of the note, nested according to the level of the headings. Links
+/// follow the [`InternalLinkFormat`] of the export, so they match the anchors of other links to
+/// headings. Notes without the placeholder are left unchanged.
+///
+/// As `[[TOC]]` would be treated as a link to a note named `TOC`, the marker should be something
+/// which isn't otherwise processed, like an Obsidian comment.
+pub fn insert_toc(
+    marker: String,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let marker = marker.trim();
+        let mut paragraph_start = None;
+        let mut placeholders = Vec::new();
+        for (idx, event) in events.iter().enumerate() {
+            match event {
+                Event::Start(Tag::Paragraph) => paragraph_start = Some(idx),
+                Event::End(TagEnd::Paragraph) => {
+                    if let Some(start) = paragraph_start.take() {
+                        let paragraph = events.get(start..idx).unwrap_or_default();
+                        if plain_text(paragraph).trim() == marker {
+                            placeholders.push(start..=idx);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if placeholders.is_empty() {
+            return PostprocessorResult::Continue;
+        }
+
+        let toc = toc_events(&headings(events), context.internal_link_format());
+        for placeholder in placeholders.into_iter().rev() {
+            events.splice(placeholder, toc.clone());
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+/// Concatenate the text of `events`, ignoring any formatting.
+fn plain_text(events: &[Event<'_>]) -> String {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) | Event::InlineHtml(text) => Some(&**text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Return the level and text of all headings within `events`.
+fn headings(events: &[Event<'_>]) -> Vec<(HeadingLevel, String)> {
+    let mut headings = Vec::new();
+    let mut current: Option<(HeadingLevel, usize)> = None;
+    for (idx, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => current = Some((*level, idx)),
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, start)) = current.take() {
+                    let text = plain_text(events.get(start..idx).unwrap_or_default());
+                    headings.push((level, text.trim().to_owned()));
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// Build a (nested) bullet list linking to `headings`.
+fn toc_events(
+    headings: &[(HeadingLevel, String)],
+    format: InternalLinkFormat,
+) -> MarkdownEvents<'static> {
+    let mut events = Vec::new();
+    let mut open_levels: Vec<HeadingLevel> = Vec::new();
+    for (level, text) in headings {
+        // Headings above the level of the first one are listed at that level.
+        let level = open_levels
+            .first()
+            .map_or(*level, |first| (*level).max(*first));
+        while open_levels.last().is_some_and(|open| *open > level) {
+            open_levels.pop();
+            events.extend([Event::End(TagEnd::Item), Event::End(TagEnd::List(false))]);
+        }
+        if open_levels.last() == Some(&level) {
+            events.push(Event::End(TagEnd::Item));
+        } else {
+            events.push(Event::Start(Tag::List(None)));
+            open_levels.push(level);
+        }
+        events.push(Event::Start(Tag::Item));
+        events.extend(heading_link(text, format));
+    }
+    for _ in open_levels {
+        events.extend([Event::End(TagEnd::Item), Event::End(TagEnd::List(false))]);
+    }
+    events
+}
+
+/// Build a link to the heading with the given `text` within the same note.
+fn heading_link(text: &str, format: InternalLinkFormat) -> MarkdownEvents<'static> {
+    let anchor = match format {
+        InternalLinkFormat::Wikilink => {
+            return vec![Event::InlineHtml(CowStr::from(format!("[[#{text}]]")))];
+        }
+        InternalLinkFormat::Docusaurus => docusaurus_slug(text),
+        InternalLinkFormat::Markdown => slugify(text),
+    };
+    vec![
+        Event::Start(Tag::Link {
+            link_type: LinkType::Inline,
+            dest_url: CowStr::from(format!("#{anchor}")),
+            title: CowStr::from(""),
+            id: CowStr::from(""),
+        }),
+        Event::Text(CowStr::from(text.to_owned())),
+        Event::End(TagEnd::Link),
+    ]
+}
+
+/// This postprocessor removes regions of a note which are delimited by a pair of markers.
+///
+/// Everything from a text consisting of just `begin` (such as `%% BEGIN PRIVATE %%`) up to and
+/// including the matching `end` marker is dropped, while any enclosing block structure (such as
+/// a list the region starts in) is kept intact. Regions may be nested. A region which is never
+/// closed extends to the end of the note, which is reported as a warning.
+pub fn strip_regions(
+    begin: String,
+    end: String,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let (begin, end) = (begin.trim(), end.trim());
+        let is_marker = |event: &Event<'_>, marker: &str| matches!(event, Event::Text(text) if text.trim() == marker);
+
+        let mut regions = Vec::new();
+        let mut region_start = None;
+        let mut depth = 0_usize;
+        for (idx, event) in events.iter().enumerate() {
+            if is_marker(event, begin) {
+                if depth == 0 {
+                    region_start = Some(idx);
+                }
+                depth = depth.saturating_add(1);
+            } else if depth > 0 && is_marker(event, end) {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    regions.extend(region_start.take().map(|start| start..=idx));
+                }
+            } else {
+                // Neither a marker nor part of a region.
+            }
+        }
+        if let Some(start) = region_start {
+            warn!(
+                "Region starting with '{}' isn't closed by '{}', removing the rest of the note\n\tSource: '{}'",
+                begin,
+                end,
+                context.current_file().display(),
+            );
+            regions.push(start..=events.len().saturating_sub(1));
+        }
+        if regions.is_empty() {
+            return PostprocessorResult::Continue;
+        }
+
+        for region in regions.into_iter().rev() {
+            let removed: MarkdownEvents<'_> = events.drain(region.clone()).collect();
+            let start = *region.start();
+            let kept = unbalanced_tags(removed);
+            if kept.is_empty() && is_line_break_pair(events, start) {
+                // Don't leave a blank line where the region was within a paragraph.
+                events.remove(start);
+            }
+            events.splice(start..start, kept);
+        }
+        remove_empty_paragraphs(events);
+        PostprocessorResult::Continue
+    }
+}
+
+/// Return the tags of `events` which are opened or closed outside of them, so the surrounding
+/// structure stays balanced when `events` are removed.
+fn unbalanced_tags(events: MarkdownEvents<'_>) -> MarkdownEvents<'_> {
+    let mut kept = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    for event in events {
+        match event {
+            Event::Start(_) => {
+                open.push(kept.len());
+                kept.push(Some(event));
+            }
+            Event::End(_) => match open.pop() {
+                Some(start) => {
+                    if let Some(slot) = kept.get_mut(start) {
+                        *slot = None;
+                    }
+                }
+                None => kept.push(Some(event)),
+            },
+            _ => {}
+        }
+    }
+    kept.into_iter().flatten().collect()
+}
+
+/// Whether the events on either side of `idx` are both line breaks.
+fn is_line_break_pair(events: &[Event<'_>], idx: usize) -> bool {
+    let before = idx.checked_sub(1).and_then(|before| events.get(before));
+    before.is_some_and(is_line_break) && events.get(idx).is_some_and(is_line_break)
+}
+
+/// Remove paragraphs which are left without any content.
+fn remove_empty_paragraphs(events: &mut MarkdownEvents<'_>) {
+    let mut idx = 0;
+    while let (Some(first), Some(second)) = (events.get(idx), events.get(idx.saturating_add(1))) {
+        if matches!(
+            (first, second),
+            (Event::Start(Tag::Paragraph), Event::End(TagEnd::Paragraph))
+        ) {
+            events.drain(idx..idx.saturating_add(2));
+        } else {
+            idx = idx.saturating_add(1);
+        }
+    }
+}
+
+/// The position within the body of a note at which
+/// [`frontmatter_tags_to_inline`] inserts tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TagPosition {
+    /// Insert tags before the first block of the note.
+    Top,
+    /// Insert tags after the last block of the note.
+    Bottom,
+}
+
+/// This postprocessor turns the `tags` listed in the frontmatter of a note into a paragraph of
+/// hashtags (`#tag #parent/child`) within its body, for platforms which only pick up inline tags.
+///
+/// Tags may be given as either a list or a single comma or space separated string, with or
+/// without a leading `#`. When `remove_key` is set, the `tags` key is removed from the frontmatter
+/// afterwards. Notes without tags are left unchanged.
+pub fn frontmatter_tags_to_inline(
+    position: TagPosition,
+    remove_key: bool,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let hashtags: Vec<String> = match context.frontmatter.get("tags") {
+            Some(Value::Sequence(tags)) => tags
+                .iter()
+                .filter_map(Value::as_str)
+                .flat_map(split_tags)
+                .map(|tag| format!("#{tag}"))
+                .collect(),
+            Some(Value::String(tags)) => split_tags(tags).map(|tag| format!("#{tag}")).collect(),
+            _ => Vec::new(),
+        };
+        if remove_key {
+            context.frontmatter.remove("tags");
+        }
+        if hashtags.is_empty() {
+            return PostprocessorResult::Continue;
+        }
+
+        let paragraph = [
+            Event::Start(Tag::Paragraph),
+            // As text, a leading `#` would be escaped to keep it from turning into a heading.
+            Event::InlineHtml(CowStr::from(hashtags.join(" "))),
+            Event::End(TagEnd::Paragraph),
+        ];
+        match position {
+            TagPosition::Top => {
+                events.splice(0..0, paragraph);
+            }
+            TagPosition::Bottom => events.extend(paragraph),
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+/// Split a frontmatter tag value on commas and whitespace, dropping any leading `#`.
+fn split_tags(tags: &str) -> impl Iterator<Item = &str> {
+    tags.split(|c: char| c == ',' || c.is_whitespace())
+        .map(|tag| tag.trim_start_matches('#'))
+        .filter(|tag| !tag.is_empty())
+}
+
+/// The shape in which [`obsidian_publish_frontmatter`] writes list values, such as `tags`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrontmatterListStyle {
+    /// A YAML list (`tags: [foo, bar]`), as written by current versions of Obsidian.
+    #[default]
+    List,
+    /// A single comma separated string (`tags: foo, bar`), as written by older versions of
+    /// Obsidian.
+    String,
+}
+
+/// The frontmatter keys holding lists which Obsidian Publish reads, along with the singular
+/// forms which older versions of Obsidian used.
+const PUBLISH_LIST_KEYS: [(&str, &str); 3] = [
+    ("aliases", "alias"),
+    ("tags", "tag"),
+    ("cssclasses", "cssclass"),
+];
+
+/// This postprocessor brings the frontmatter of notes into the shape expected by [Obsidian
+/// Publish].
+///
+/// The `aliases`, `tags` and `cssclasses` keys are written as lists or as comma separated strings,
+/// according to `list_style`, with the leading `#` removed from tags. Their deprecated singular
+/// forms (`alias`, `tag` and `cssclass`) are renamed accordingly. Notes which don't specify
+/// whether they're published get `publish: true`.
+///
+/// [Obsidian Publish]: https://help.obsidian.md/Obsidian+Publish/Introduction+to+Obsidian+Publish
+pub fn obsidian_publish_frontmatter(
+    list_style: FrontmatterListStyle,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, _events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut frontmatter = Frontmatter::new();
+        for (key, value) in mem::take(&mut context.frontmatter) {
+            let list_key = PUBLISH_LIST_KEYS.iter().find(|(plural, singular)| {
+                key.as_str()
+                    .is_some_and(|name| name == *plural || name == *singular)
+            });
+            let Some(&(plural, _singular)) = list_key else {
+                frontmatter.insert(key, value);
+                continue;
+            };
+            // The plural key takes precedence when both forms are present.
+            if frontmatter.contains_key(plural) && key.as_str() != Some(plural) {
+                continue;
+            }
+            let items = publish_list_items(plural, &value);
+            let value = match (items.is_empty(), list_style) {
+                (true, _) => value,
+                (false, FrontmatterListStyle::List) => {
+                    Value::Sequence(items.into_iter().map(Value::String).collect())
+                }
+                (false, FrontmatterListStyle::String) => Value::String(items.join(", ")),
+            };
+            frontmatter.insert(Value::String(plural.to_owned()), value);
+        }
+        if !frontmatter.contains_key("publish") {
+            frontmatter.insert(Value::String("publish".to_owned()), Value::Bool(true));
+        }
+        context.frontmatter = frontmatter;
+        PostprocessorResult::Continue
+    }
+}
+
+/// Return the items of the list-like frontmatter `value` of `key`, which may be a list or a
+/// comma separated string.
+fn publish_list_items(key: &str, value: &Value) -> Vec<String> {
+    let scalar = |item: &Value| match item {
+        Value::String(string) => Some(string.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(bool) => Some(bool.to_string()),
+        _ => None,
+    };
+    match value {
+        // Tags and CSS classes can't contain whitespace, so they're split on it as well.
+        Value::Sequence(items) if key != "aliases" => items
+            .iter()
+            .filter_map(scalar)
+            .flat_map(|item| split_tags(&item).map(ToOwned::to_owned).collect::<Vec<_>>())
+            .collect(),
+        Value::Sequence(items) => items.iter().filter_map(scalar).collect(),
+        Value::String(items) if key != "aliases" => {
+            split_tags(items).map(ToOwned::to_owned).collect()
+        }
+        Value::String(items) => items
+            .split(',')
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .map(ToOwned::to_owned)
+            .collect(),
+        _ => scalar(value).into_iter().collect(),
+    }
+}
+
+/// This postprocessor protects notes from being processed as [Liquid] templates, as done by
+/// Jekyll.
+///
+/// Text, inline code, code blocks and math containing Liquid syntax (`{{` or `{%`) are wrapped in
+/// `{% raw %}...{% endraw %}` tags, so they show up exactly as written on the rendered site.
+///
+/// [Liquid]: https://shopify.github.io/liquid/
+pub fn escape_liquid(
+    _context: &mut Context,
+    events: &mut MarkdownEvents<'_>,
+) -> PostprocessorResult {
+    for event in events.iter_mut() {
+        if let Event::Text(text)
+        | Event::Code(text)
+        | Event::InlineMath(text)
+        | Event::DisplayMath(text) = event
+        {
+            if text.contains("{{") || text.contains("{%") {
+                // Keep trailing newlines (as found in code blocks) outside of the raw tags, so no
+                // extra blank line remains once Liquid strips them.
+                let content = text.trim_end_matches('\n');
+                let newlines = text.get(content.len()..).unwrap_or_default();
+                *text = CowStr::from(format!("{{% raw %}}{content}{{% endraw %}}{newlines}"));
+            }
+        }
+    }
+    PostprocessorResult::Continue
+}
+
+/// This postprocessor resolves Obsidian template placeholders such as `{{title}}` and `{{date}}`
+/// within the text of notes.
+///
+/// Besides the tokens given in `vars`, `{{title}}` resolves to the [title][Context::title] of the
+/// note, while `{{date}}` and `{{time}}` resolve to the date (`2024-01-31`) and time (`14:05`) at
+/// which the postprocessor was created, in UTC. Entries in `vars` take precedence over these
+/// built-in tokens. Tokens within inline code and code blocks are left alone, as are unknown
+/// tokens, which are reported with a warning when `warn_unknown` is set.
+pub fn resolve_templates<S: BuildHasher + Send + Sync>(
+    vars: HashMap<String, String, S>,
+    warn_unknown: bool,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    let now = DateTime::<Utc>::from(SystemTime::now());
+    let date = now.format("%Y-%m-%d").to_string();
+    let time = now.format("%H:%M").to_string();
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut in_code_block = false;
+        for event in events.iter_mut() {
+            let text = match event {
+                Event::Start(Tag::CodeBlock(_)) => {
+                    in_code_block = true;
+                    continue;
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    in_code_block = false;
+                    continue;
+                }
+                Event::Text(text) if !in_code_block => text,
+                _ => continue,
+            };
+            if !TEMPLATE_TOKEN_RE.is_match(text) {
+                continue;
+            }
+            let resolved = TEMPLATE_TOKEN_RE.replace_all(text, |captures: &Captures<'_>| {
+                let token = &captures[1];
+                match vars.get(token) {
+                    Some(value) => value.clone(),
+                    None if token == "title" => context.title(),
+                    None if token == "date" => date.clone(),
+                    None if token == "time" => time.clone(),
+                    None => {
+                        if warn_unknown {
+                            warn!(
+                                "Unknown template token\n\tToken: '{}'\n\tSource: '{}'",
+                                token,
+                                context.current_file().display()
+                            );
+                        }
+                        captures[0].to_owned()
+                    }
+                }
+            });
+            *text = CowStr::from(resolved.into_owned());
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+/// This postprocessor removes frontmatter in which every value is empty.
+///
+/// Values count as empty when they are `null`, an empty string, an empty list or an empty mapping.
+/// When using [`FrontmatterStrategy::Auto`][crate::FrontmatterStrategy::Auto], no frontmatter
+/// block is written for such notes at all.
+///
+/// As other postprocessors may add or change frontmatter, this should be added last.
+pub fn remove_empty_frontmatter(
+    context: &mut Context,
+    _events: &mut MarkdownEvents<'_>,
+) -> PostprocessorResult {
+    let is_empty = |value: &Value| match value {
+        Value::Null => true,
+        Value::String(string) => string.is_empty(),
+        Value::Sequence(sequence) => sequence.is_empty(),
+        Value::Mapping(mapping) => mapping.is_empty(),
+        _ => false,
+    };
+    if context.frontmatter.values().all(is_empty) {
+        context.frontmatter.clear();
+    }
+    PostprocessorResult::Continue
+}
+
+/// This postprocessor rewrites the dates found under the given frontmatter `keys` using a
+/// [chrono format string][chrono::format::strftime], such as `%Y-%m-%dT%H:%M:%SZ`.
+///
+/// Dates (`2023-01-05`) and datetimes both with a timezone (`2023-01-05T10:30:00+02:00`) and
+/// without one (`2023-01-05 10:30:00`) are understood. Dates are taken to be at midnight and
+/// values without a timezone to be in UTC. Values which aren't dates are left untouched.
+///
+/// # Panics
+///
+/// Panics when `format` isn't a valid format string.
+pub fn normalize_dates(
+    keys: Vec<String>,
+    format: String,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    assert!(
+        !StrftimeItems::new(&format).any(|item| item == Item::Error),
+        "invalid date format: {}",
+        format
+    );
+    move |context: &mut Context, _events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        for key in &keys {
+            if let Some(Value::String(value)) = context.frontmatter.get_mut(key.as_str()) {
+                if let Some(datetime) = parse_date(value) {
+                    *value = datetime.format(&format).to_string();
+                }
+            }
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime);
+    }
+    let naive = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .map(|date| date.and_time(NaiveTime::MIN))
+    })?;
+    Some(naive.and_utc().fixed_offset())
+}
+
+/// This postprocessor removes tags from the end of headings, turning `# Heading #tag` into
+/// `# Heading`.
+///
+/// Only whitespace-delimited tags at the very end of a heading are removed, so headings such as
+/// `# C# notes` or `# Issue #123` (tags can't be purely numeric) are left alone. When
+/// `collect_into` is set, the removed tags are added to the list under that frontmatter key (such
+/// as `tags`), unless they're already present.
+pub fn strip_heading_tags(
+    collect_into: Option<String>,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut stripped = Vec::new();
+        let mut last_text = None;
+        for idx in 0..events.len() {
+            match events.get(idx) {
+                Some(Event::Start(Tag::Heading { .. })) => last_text = None,
+                Some(Event::Text(_)) => last_text = Some(idx),
+                Some(Event::End(TagEnd::Heading(_))) => {
+                    if let Some(Event::Text(text)) =
+                        last_text.and_then(|text_idx| events.get_mut(text_idx))
+                    {
+                        let (heading, tags) = split_trailing_tags(text);
+                        if !tags.is_empty() {
+                            *text = CowStr::from(heading.to_owned());
+                            stripped.extend(tags);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(key) = &collect_into {
+            let key = Value::String(key.clone());
+            let tags = context
+                .frontmatter
+                .entry(key)
+                .or_insert_with(|| Value::Sequence(vec![]));
+            if let Value::Sequence(tags) = tags {
+                for tag in stripped {
+                    let tag = Value::String(tag);
+                    if !tags.contains(&tag) {
+                        tags.push(tag);
+                    }
+                }
+            }
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+/// Split the tags at the end of `text` off from the text before them, returning the tags (without
+/// their leading `#`) in the order they appear.
+fn split_trailing_tags(text: &str) -> (&str, Vec<String>) {
+    let mut heading = text.trim_end();
+    let mut tags = Vec::new();
+    while let Some((rest, token)) = heading.rsplit_once(char::is_whitespace) {
+        match token.strip_prefix('#') {
+            Some(tag) if is_tag(tag) => {
+                tags.insert(0, tag.to_owned());
+                heading = rest.trim_end();
+            }
+            _ => break,
+        }
+    }
+    (heading, tags)
+}
+
+/// Return whether `tag` (without its leading `#`) is a valid Obsidian tag.
+fn is_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+        && !tag.chars().all(|c| c.is_ascii_digit())
+}
+
+/// This postprocessor records the path of each note within the vault in its frontmatter, under the
+/// given `key`.
+///
+/// Paths are relative to the root of the vault and always use forward slashes, for example
+/// `source: folder/Note.md`. Embedded notes are left alone, so when this is also added as an
+/// [embed postprocessor][crate::Exporter::add_embed_postprocessor] it has no effect on those.
+pub fn add_source_path(
+    key: String,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, _events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        if context.note_depth() > 1 {
+            return PostprocessorResult::Continue;
+        }
+        let path = context
+            .current_file_relative()
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        context
+            .frontmatter
+            .insert(Value::String(key.clone()), Value::String(path));
+        PostprocessorResult::Continue
+    }
+}
+
+/// This postprocessor records the folders containing each note in its frontmatter, under the given
+/// `key`, as a list which can be used to render breadcrumb navigation.
+///
+/// The list holds the name of each folder from the root of the vault down to the folder of the
+/// note, preceded by `root_label` when one is given. With `Some("Home")`, the note
+/// `Docs/Guides/Install.md` gets `breadcrumbs: [Home, Docs, Guides]`, while notes at the root of
+/// the vault get just `[Home]`. Embedded notes are left alone.
+#[allow(clippy::needless_pass_by_value)]
+pub fn folder_breadcrumbs(
+    key: String,
+    root_label: Option<String>,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, _events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        if context.note_depth() > 1 {
+            return PostprocessorResult::Continue;
+        }
+        let folders = context
+            .current_file_relative()
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .map(|component| component.as_os_str().to_string_lossy().into_owned());
+        let breadcrumbs = root_label
+            .iter()
+            .cloned()
+            .chain(folders)
+            .map(Value::String)
+            .collect();
+        context
+            .frontmatter
+            .insert(Value::String(key.clone()), Value::Sequence(breadcrumbs));
+        PostprocessorResult::Continue
+    }
+}
+
+/// This postprocessor turns links to other notes into footnotes, as is common for printed output.
+///
+/// The link text is kept, followed by a footnote reference, and a footnote citing the title of
+/// the linked note (or the name of a linked attachment) is added to the end of the note: `See
+/// [[Other Note]]` becomes `See Other Note[^1]`, together with `[^1]: Other Note`. Footnotes are
+/// numbered in order of their first use within each note, with repeated links to the same note
+/// sharing a footnote.
+///
+/// Footnote labels are prefixed with `label_prefix` (for example `link-` for `[^link-1]`) to
+/// keep them apart from footnotes which are already part of the note. External links, links to
+/// headings within the same note and embedded notes are left untouched.
+pub fn links_to_footnotes(
+    label_prefix: String,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        if context.note_depth() > 1 {
+            return PostprocessorResult::Continue;
+        }
+        let mut rewritten = Vec::with_capacity(events.len());
+        // Titles of the cited notes, in order of their footnote numbers.
+        let mut citations: Vec<String> = Vec::new();
+        // For each open link, the footnote label to insert once it's closed.
+        let mut open_links = Vec::new();
+
+        for event in mem::take(events) {
+            match event {
+                Event::Start(Tag::Link { ref dest_url, .. }) => {
+                    let label = internal_link_title(dest_url).map(|title| {
+                        let number = citations
+                            .iter()
+                            .position(|cited| *cited == title)
+                            .unwrap_or_else(|| {
+                                citations.push(title);
+                                citations.len().saturating_sub(1)
+                            })
+                            .saturating_add(1);
+                        format!("{label_prefix}{number}")
+                    });
+                    if label.is_none() {
+                        rewritten.push(event);
+                    }
+                    open_links.push(label);
+                }
+                Event::End(TagEnd::Link) => match open_links.pop().flatten() {
+                    Some(label) => rewritten.push(Event::FootnoteReference(CowStr::from(label))),
+                    None => rewritten.push(event),
+                },
+                other => rewritten.push(other),
+            }
+        }
+
+        for (idx, title) in citations.into_iter().enumerate() {
+            let label = CowStr::from(format!("{label_prefix}{}", idx.saturating_add(1)));
+            rewritten.extend([
+                Event::Start(Tag::FootnoteDefinition(label)),
+                Event::Start(Tag::Paragraph),
+                Event::Text(CowStr::from(title)),
+                Event::End(TagEnd::Paragraph),
+                Event::End(TagEnd::FootnoteDefinition),
+            ]);
+        }
+        *events = rewritten;
+        PostprocessorResult::Continue
+    }
+}
+
+/// Return the title of the note (or attachment) that `link` points to, if it's a link to another
+/// file within the vault.
+fn internal_link_title(link: &str) -> Option<String> {
+    if link.is_empty() || link.starts_with(['/', '#']) || has_url_scheme(link) {
+        return None;
+    }
+    let path = link.split_once('#').map_or(link, |(path, _)| path);
+    let path = PathBuf::from(&*percent_decode_str(path).decode_utf8_lossy());
+    Some(path.file_stem()?.to_string_lossy().into_owned())
+}
+
+/// How [`footnote_style`] arranges the footnotes of a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FootnoteStyle {
+    /// `Some claim (The source.)`, with the content of the footnote in parentheses in place of
+    /// the reference.
+    Inline,
+    /// `Some claim[^1]`, with footnotes numbered from 1 in order of their first reference and
+    /// their definitions collected at the end of the note.
+    Endnotes,
+}
+
+/// This postprocessor rearranges the footnotes (`[^label]`) of notes according to `style`.
+///
+/// With [`FootnoteStyle::Inline`], references are replaced with the content of their footnote in
+/// parentheses and the definitions are removed. With [`FootnoteStyle::Endnotes`], footnotes are
+/// renumbered within each note, in order of their first reference, and their definitions are moved
+/// to the end of the note in that order. Definitions which are never referenced come last.
+/// References to footnotes which aren't defined are left untouched either way.
+pub fn footnote_style(
+    style: FootnoteStyle,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        if context.note_depth() > 1 {
+            return PostprocessorResult::Continue;
+        }
+        let (rest, definitions) = take_footnote_definitions(mem::take(events));
+        *events = match style {
+            FootnoteStyle::Inline => inline_footnotes(rest, &definitions),
+            FootnoteStyle::Endnotes => endnotes(rest, definitions),
+        };
+        PostprocessorResult::Continue
+    }
+}
+
+/// Split the footnote definitions off `events`, returning the remaining events along with the
+/// content of each definition, in the order they're defined.
+fn take_footnote_definitions(
+    events: MarkdownEvents<'_>,
+) -> (MarkdownEvents<'_>, Vec<(CowStr<'_>, MarkdownEvents<'_>)>) {
+    let mut rest = Vec::with_capacity(events.len());
+    let mut definitions: Vec<(CowStr<'_>, MarkdownEvents<'_>)> = Vec::new();
+    let mut in_definition = false;
+    for event in events {
+        match event {
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                in_definition = true;
+                definitions.push((label, Vec::new()));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => in_definition = false,
+            event if in_definition => {
+                if let Some((_, content)) = definitions.last_mut() {
+                    content.push(event);
+                }
+            }
+            event => rest.push(event),
+        }
+    }
+    (rest, definitions)
+}
+
+/// Replace each footnote reference in `events` with the content of its definition, in
+/// parentheses. The paragraphs of a definition are joined with a space.
+fn inline_footnotes<'a>(
+    events: MarkdownEvents<'a>,
+    definitions: &[(CowStr<'a>, MarkdownEvents<'a>)],
+) -> MarkdownEvents<'a> {
+    let mut inlined = Vec::with_capacity(events.len());
+    for event in events {
+        let Event::FootnoteReference(ref label) = event else {
+            inlined.push(event);
+            continue;
+        };
+        let Some((_, content)) = definitions.iter().find(|(defined, _)| defined == label) else {
+            inlined.push(event);
+            continue;
+        };
+        inlined.push(Event::Text(CowStr::Borrowed(" (")));
+        let mut paragraphs = 0_usize;
+        for content_event in content {
+            match content_event {
+                Event::Start(Tag::Paragraph) => {
+                    if paragraphs > 0 {
+                        inlined.push(Event::Text(CowStr::Borrowed(" ")));
+                    }
+                    paragraphs = paragraphs.saturating_add(1);
+                }
+                Event::End(TagEnd::Paragraph) => {}
+                other => inlined.push(other.clone()),
+            }
+        }
+        inlined.push(Event::Text(CowStr::Borrowed(")")));
+    }
+    inlined
+}
+
+/// Renumber the footnote references in `events` from 1, in order of their first reference, and
+/// append `definitions` in that same order.
+fn endnotes<'a>(
+    events: MarkdownEvents<'a>,
+    mut definitions: Vec<(CowStr<'a>, MarkdownEvents<'a>)>,
+) -> MarkdownEvents<'a> {
+    // Labels of the defined footnotes, in order of their new numbers.
+    let mut order: Vec<CowStr<'a>> = Vec::new();
+    let mut renumbered = Vec::with_capacity(events.len());
+    for event in events {
+        match event {
+            Event::FootnoteReference(label)
+                if definitions.iter().any(|(defined, _)| *defined == label) =>
+            {
+                let number = order
+                    .iter()
+                    .position(|ordered| *ordered == label)
+                    .unwrap_or_else(|| {
+                        order.push(label);
+                        order.len().saturating_sub(1)
+                    })
+                    .saturating_add(1);
+                renumbered.push(Event::FootnoteReference(CowStr::from(number.to_string())));
+            }
+            event => renumbered.push(event),
+        }
+    }
+
+    // Definitions which are never referenced keep their original order, after the others.
+    definitions.sort_by_key(|(label, _)| {
+        order
+            .iter()
+            .position(|ordered| ordered == label)
+            .unwrap_or(usize::MAX)
+    });
+    for (idx, (_, content)) in definitions.into_iter().enumerate() {
+        let label = CowStr::from(idx.saturating_add(1).to_string());
+        renumbered.push(Event::Start(Tag::FootnoteDefinition(label)));
+        renumbered.extend(content);
+        renumbered.push(Event::End(TagEnd::FootnoteDefinition));
+    }
+    renumbered
+}
+
+/// This postprocessor rewrites links to other notes and files within the vault into site-root
+/// absolute URLs.
+///
+/// Relative links such as `../folder/Note.md#heading` are resolved against the location of the
+/// note within the export destination, prefixed with `base` and stripped of their `.md` extension,
+/// resulting in `{base}/folder/Note#heading`. When `trailing_slash` is true, note URLs get a
+/// trailing slash (`{base}/folder/Note/#heading`) as many static site generators expect.
+///
+/// External links (those with a URL scheme such as `https:`), links which are already absolute,
+/// links to headings within the same note and images are left untouched.
+pub fn absolute_link_rewriter(
+    base: String,
+    trailing_slash: bool,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let note_dir = context
+            .destination_relative()
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+        for event in events.iter_mut() {
+            if let Event::Start(Tag::Link { dest_url, .. }) = event {
+                let base = base.trim_end_matches('/');
+                if let Some(url) = absolute_url(base, &note_dir, dest_url, trailing_slash) {
+                    *dest_url = CowStr::from(url);
+                }
+            }
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+fn absolute_url(base: &str, note_dir: &Path, link: &str, trailing_slash: bool) -> Option<String> {
+    if link.is_empty() || link.starts_with(['/', '#']) || has_url_scheme(link) {
+        return None;
+    }
+    let (path, fragment) = link
+        .split_once('#')
+        .map_or((link, None), |(path, fragment)| (path, Some(fragment)));
+
+    let mut target = destination_path(note_dir, path);
+    let is_note = target.extension().is_some_and(|ext| ext == "md");
+    if is_note {
+        target.set_extension("");
+    }
+
+    let mut url = format!("{base}/{}", url_path(&target));
+    if is_note && trailing_slash {
+        url.push('/');
+    }
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    Some(url)
+}
+
+/// Resolve the percent-encoded relative `link` found in a note located in `note_dir` to a path
+/// relative to the root of the export destination.
+fn destination_path(note_dir: &Path, link: &str) -> PathBuf {
+    let mut target = PathBuf::new();
+    for component in note_dir
+        .join(&*percent_decode_str(link).decode_utf8_lossy())
+        .components()
+    {
+        match component {
+            Component::Normal(part) => target.push(part),
+            Component::ParentDir => {
+                target.pop();
+            }
+            _ => {}
+        }
+    }
+    target
+}
+
+/// Join the components of `path` into a percent-encoded URL path.
+fn url_path(path: &Path) -> String {
+    let segments: Vec<String> = path
+        .components()
+        .map(|component| {
+            utf8_percent_encode(
+                &component.as_os_str().to_string_lossy(),
+                PERCENTENCODE_CHARS,
+            )
+            .to_string()
+        })
+        .collect();
+    segments.join("/")
+}
+
+/// This postprocessor rewrites images within the vault to absolute URLs at `base_url`, for sites
+/// which serve images from a CDN.
+///
+/// Both embedded images (`![[image.png]]`) and markdown images (`![alt](image.png)`) are resolved
+/// against the location of the note within the export destination and prefixed with `base_url`, so
+/// `![[image.png]]` within `folder/Note.md` becomes `![image.png]({base_url}/folder/image.png)`.
+/// Alt text, query strings and fragments are kept as-is.
+///
+/// Images with a URL scheme (such as `https:`) or an absolute path are left untouched. The image
+/// files themselves are still exported as usual, so they can be uploaded to the CDN separately.
+pub fn cdn_image_rewriter(
+    base_url: String,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let note_dir = context
+            .destination_relative()
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+        let base = base_url.trim_end_matches('/');
+        for event in events.iter_mut() {
+            if let Event::Start(Tag::Image { dest_url, .. }) = event {
+                if let Some(url) = cdn_url(base, &note_dir, dest_url) {
+                    *dest_url = CowStr::from(url);
+                }
+            }
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+fn cdn_url(base: &str, note_dir: &Path, image: &str) -> Option<String> {
+    if image.is_empty() || image.starts_with('/') || has_url_scheme(image) {
+        return None;
+    }
+    let (path, suffix) = image.split_at(image.find(['?', '#']).unwrap_or(image.len()));
+    Some(format!(
+        "{base}/{}{suffix}",
+        url_path(&destination_path(note_dir, path))
+    ))
+}
+
+pub(crate) fn has_url_scheme(link: &str) -> bool {
+    link.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// This postprocessor turns [Obsidian callouts] into HTML.
+///
+/// Foldable callouts (`> [!faq]- Title` or `> [!faq]+ Title`) become
+/// `<details><summary>Title</summary>...</details>` elements, which are expanded by default for
+/// `+`. Other callouts become `<div class="callout callout-faq">` elements containing a
+/// `<div class="callout-title">`. Callouts without an explicit title use their type as the title,
+/// as Obsidian does. The content of callouts is kept as markdown, including any nested callouts,
+/// which are converted as well.
+///
+/// [Obsidian callouts]: https://help.obsidian.md/Editing+and+formatting/Callouts
+pub fn callouts_to_details(
+    _context: &mut Context,
+    events: &mut MarkdownEvents<'_>,
+) -> PostprocessorResult {
+    *events = convert_callouts(mem::take(events));
+    PostprocessorResult::Continue
+}
+
+fn convert_callouts(events: MarkdownEvents<'_>) -> MarkdownEvents<'_> {
+    let mut converted = Vec::with_capacity(events.len());
+    let mut events = events.into_iter();
+    while let Some(event) = events.next() {
+        if !matches!(event, Event::Start(Tag::BlockQuote(_))) {
+            converted.push(event);
+            continue;
+        }
+        let mut inner = Vec::new();
+        let mut end = None;
+        let mut depth = 0_usize;
+        for inner_event in events.by_ref() {
+            match inner_event {
+                Event::Start(Tag::BlockQuote(_)) => depth = depth.saturating_add(1),
+                Event::End(TagEnd::BlockQuote(_)) if depth == 0 => {
+                    end = Some(inner_event);
+                    break;
+                }
+                Event::End(TagEnd::BlockQuote(_)) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            inner.push(inner_event);
+        }
+        match split_callout(inner) {
+            Ok((header, footer, body)) => {
+                converted.extend(html_block(header));
+                converted.extend(convert_callouts(body));
+                converted.extend(html_block(footer));
+            }
+            Err(inner) => {
+                converted.push(event);
+                converted.extend(convert_callouts(inner));
+                converted.extend(end);
+            }
+        }
+    }
+    converted
+}
+
+/// Split the contents of a blockquote into the opening and closing HTML of the callout it
+/// represents and the body of the callout, or return the contents unchanged if the blockquote
+/// isn't a callout.
+fn split_callout(
+    mut inner: MarkdownEvents<'_>,
+) -> Result<(String, String, MarkdownEvents<'_>), MarkdownEvents<'_>> {
+    if inner.first() != Some(&Event::Start(Tag::Paragraph)) {
+        return Err(inner);
+    }
+    let Some(line_end) = inner.iter().position(|event| {
+        matches!(
+            event,
+            Event::SoftBreak | Event::HardBreak | Event::End(TagEnd::Paragraph)
+        )
+    }) else {
+        return Err(inner);
+    };
+    let first_line: String = inner
+        .get(1..line_end)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(&**text),
+            _ => None,
+        })
+        .collect();
+    let Some(captures) = CALLOUT_RE.captures(&first_line) else {
+        return Err(inner);
+    };
+
+    let kind = captures["kind"].to_lowercase();
+    let title = match captures["title"].trim() {
+        "" => {
+            let mut chars = kind.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        }
+        title => title.to_owned(),
+    };
+    let title = escape_html(&title);
+    let (header, footer) = match captures.name("fold").map_or("", |fold| fold.as_str()) {
+        "" => (
+            format!(
+                "<div class=\"callout callout-{kind}\">\n<div class=\"callout-title\">{title}</div>\n"
+            ),
+            "</div>\n".to_owned(),
+        ),
+        fold => (
+            format!(
+                "<details class=\"callout callout-{kind}\"{}>\n<summary>{title}</summary>\n",
+                if fold == "+" { " open" } else { "" }
+            ),
+            "</details>\n".to_owned(),
+        ),
+    };
+
+    let mut body = inner.split_off(line_end);
+    // The rest of the first paragraph (if any) becomes a paragraph of its own.
+    match body.first_mut() {
+        Some(Event::End(TagEnd::Paragraph)) => {
+            body.remove(0);
+        }
+        Some(line_break) => *line_break = Event::Start(Tag::Paragraph),
+        None => {}
+    }
+    Ok((header, footer, body))
+}
+
+fn html_block(html: String) -> [Event<'static>; 3] {
+    [
+        Event::Start(Tag::HtmlBlock),
+        Event::Html(CowStr::from(html)),
+        Event::End(TagEnd::HtmlBlock),
+    ]
+}
+
+/// This postprocessor rewrites display math (`$$...$$`) into fenced `math` code blocks.
+///
+/// This is the form expected by renderers such as GitLab and some static site generators. When
+/// `wrap_inline` is true, inline math (`$...$`) is additionally emitted as
+/// `<span class="math">...</span>`.
+///
+/// Math is recognized by the markdown parser itself, so lone dollar signs (such as prices like
+/// `$5`) and escaped dollar signs (`\$`) are left untouched.
+pub fn math_to_fenced(
+    wrap_inline: bool,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |_context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut rewritten = Vec::with_capacity(events.len());
+        let mut open_tags = Vec::new();
+        // Set after display math split a paragraph in two, so that line breaks directly
+        // following the math block and an empty trailing paragraph can be dropped.
+        let mut reopened = false;
+
+        for event in mem::take(events) {
+            let was_reopened = mem::replace(&mut reopened, false);
+            match event {
+                Event::SoftBreak | Event::HardBreak if was_reopened => reopened = true,
+                Event::End(TagEnd::Paragraph) if was_reopened => {
+                    open_tags.pop();
+                    rewritten.pop();
+                }
+                Event::DisplayMath(math) if open_tags.last() == Some(&TagEnd::Paragraph) => {
+                    while matches!(rewritten.last(), Some(Event::SoftBreak | Event::HardBreak)) {
+                        rewritten.pop();
+                    }
+                    if rewritten.last() == Some(&Event::Start(Tag::Paragraph)) {
+                        rewritten.pop();
+                    } else {
+                        rewritten.push(Event::End(TagEnd::Paragraph));
+                    }
+                    rewritten.extend(fenced_math_block(&math));
+                    rewritten.push(Event::Start(Tag::Paragraph));
+                    reopened = true;
+                }
+                Event::DisplayMath(math)
+                    if matches!(
+                        open_tags.last(),
+                        None | Some(TagEnd::Item | TagEnd::BlockQuote(_))
+                    ) =>
+                {
+                    rewritten.extend(fenced_math_block(&math));
+                }
+                Event::InlineMath(math) if wrap_inline => {
+                    rewritten.push(Event::InlineHtml(CowStr::from(format!(
+                        "<span class=\"math\">{}</span>",
+                        escape_html(&math)
+                    ))));
+                }
+                Event::Start(tag) => {
+                    open_tags.push(tag.to_end());
+                    rewritten.push(Event::Start(tag));
+                }
+                Event::End(tag_end) => {
+                    open_tags.pop();
+                    rewritten.push(Event::End(tag_end));
+                }
+                other => rewritten.push(other),
+            }
+        }
+        *events = rewritten;
+        PostprocessorResult::Continue
+    }
+}
+
+fn fenced_math_block(math: &str) -> [Event<'static>; 3] {
+    [
+        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("math")))),
+        Event::Text(CowStr::from(format!("{}\n", math.trim()))),
+        Event::End(TagEnd::CodeBlock),
+    ]
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// This postprocessor wraps the body of a note in an MDX component, passing the frontmatter of
+/// the note to it as a `frontmatter` prop.
+///
+/// For example, `mdx_wrapper("NoteLayout".to_string())` turns a note with `title: Hello` into:
+///
+/// ```mdx
+/// <NoteLayout frontmatter={{"title":"Hello"}}>
+///
+/// ...
+///
+/// </NoteLayout>
+/// ```
+///
+/// This is intended to be combined with an [output extension][crate::Exporter::output_extension]
+/// of `mdx`.
+pub fn mdx_wrapper(
+    component: String,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let props = json_value(&Value::Mapping(context.frontmatter.clone()));
+        events.splice(
+            0..0,
+            html_block(format!("<{component} frontmatter={{{props}}}>\n")),
+        );
+        events.extend(html_block(format!("</{component}>\n")));
+        PostprocessorResult::Continue
+    }
+}
+
+/// This postprocessor wraps the body of a note in a `<div>` carrying the CSS classes which the
+/// note declares through its frontmatter, which Obsidian uses to style individual notes.
+///
+/// Both the `cssclasses` list (`cssclasses: [wide, dark]`) and the older `cssclass` key
+/// (`cssclass: wide`) are read, and the note becomes:
+///
+/// ```markdown
+/// <div class="wide dark">
+///
+/// ...
+///
+/// </div>
+/// ```
+///
+/// The classes in `base_classes` are added to those of every note, so when it's not empty all
+/// notes are wrapped. Otherwise, notes without CSS classes are left alone.
+pub fn cssclass_wrapper(
+    base_classes: Vec<String>,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut classes = base_classes.clone();
+        for key in ["cssclasses", "cssclass"] {
+            if let Some(value) = context.frontmatter.get(key) {
+                classes.extend(publish_list_items(key, value));
+            }
+        }
+        let mut seen = HashSet::new();
+        classes.retain(|class| seen.insert(class.clone()));
+        if classes.is_empty() {
+            return PostprocessorResult::Continue;
+        }
+        let class = escape_html(&classes.join(" ")).replace('"', "&quot;");
+        events.splice(0..0, html_block(format!("<div class=\"{class}\">\n")));
+        events.extend(html_block("</div>\n".to_owned()));
+        PostprocessorResult::Continue
+    }
+}
+
+/// How [`normalize_tasks`] renders the status of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TaskStyle {
+    /// Standard `[ ]`/`[x]` checkboxes. Done (`x`) and cancelled (`-`) tasks are checked, all
+    /// other statuses are unchecked.
+    Checkbox,
+    /// An emoji for each status, such as `🔄` for in-progress tasks (`[/]`).
+    Emoji,
+    /// A disabled HTML checkbox, carrying the original status in a `data-task` attribute so it
+    /// can be styled.
+    Html,
+}
+
+/// This postprocessor rewrites extended task statuses to the given [`TaskStyle`].
+///
+/// Many Obsidian themes and plugins support statuses beyond `[ ]` and `[x]`, such as
+/// `- [/] In progress` or `- [-] Cancelled`, which other markdown renderers don't understand.
+/// The supported statuses are ` ` (to do), `x`/`X` (done), `/` (in progress), `-` (cancelled),
+/// `>` (forwarded), `<` (scheduled), `?` (question) and `!` (important). Unknown statuses are
+/// treated as to do.
+pub fn normalize_tasks(
+    style: TaskStyle,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |_context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut idx = 0;
+        while idx < events.len() {
+            if matches!(events.get(idx), Some(Event::Start(Tag::Item))) {
+                let mut start = idx.saturating_add(1);
+                if matches!(events.get(start), Some(Event::Start(Tag::Paragraph))) {
+                    start = start.saturating_add(1);
+                }
+                if let Some((status, len, rest)) =
+                    task_status(events.get(start..).unwrap_or_default())
+                {
+                    let replacement = task_events(style, status, rest);
+                    events.splice(start..start.saturating_add(len), replacement);
+                }
+            }
+            idx = idx.saturating_add(1);
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+/// Find the status of the task at the start of `events`.
+///
+/// Returns the status symbol, the number of events making up the task marker and the text
+/// following the marker within those events. Standard `[ ]` and `[x]` tasks are parsed into
+/// task list markers, but custom statuses come through as plain text.
+fn task_status(events: &[Event<'_>]) -> Option<(char, usize, String)> {
+    if let Some(Event::TaskListMarker(checked)) = events.first() {
+        let status = if *checked { 'x' } else { ' ' };
+        // Unlike custom statuses, the space after the marker isn't part of the following text.
+        return Some((status, 1, " ".to_owned()));
+    }
+
+    let len = events
+        .iter()
+        .take_while(|event| matches!(event, Event::Text(_)))
+        .count();
+    let text = events
+        .iter()
+        .take(len)
+        .fold(String::new(), |mut text, event| {
+            if let Event::Text(part) = event {
+                text.push_str(part);
+            }
+            text
+        });
+    let mut chars = text.chars();
+    let (Some('['), Some(status), Some(']')) = (chars.next(), chars.next(), chars.next()) else {
+        return None;
+    };
+    let rest = chars.as_str();
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some((status, len, rest.to_owned()))
+}
+
+/// Render a task with the given status in `style`, followed by the remaining text `rest`.
+fn task_events<'a>(style: TaskStyle, status: char, rest: String) -> Vec<Event<'a>> {
+    let (checked, emoji) = match status {
+        'x' | 'X' => (true, "✅"),
+        '-' => (true, "❌"),
+        '/' => (false, "🔄"),
+        '>' => (false, "➡️"),
+        '<' => (false, "📅"),
+        '?' => (false, "❓"),
+        '!' => (false, "❗"),
+        _ => (false, "⬜"),
+    };
+    let mut task = match style {
+        TaskStyle::Checkbox => {
+            // The checkbox is followed by a space when rendered already.
+            let rest = rest.strip_prefix(' ').unwrap_or(&rest);
+            let mut task = vec![Event::TaskListMarker(checked)];
+            if !rest.is_empty() {
+                task.push(Event::Text(CowStr::from(rest.to_owned())));
+            }
+            return task;
+        }
+        TaskStyle::Emoji => vec![Event::Text(CowStr::from(emoji))],
+        TaskStyle::Html => {
+            let status = match status {
+                '"' => "&quot;".to_owned(),
+                '&' => "&amp;".to_owned(),
+                '<' => "&lt;".to_owned(),
+                status => status.to_string(),
+            };
+            let checked = if checked { " checked" } else { "" };
+            vec![Event::InlineHtml(CowStr::from(format!(
+                "<input type=\"checkbox\" disabled{checked} data-task=\"{status}\">"
+            )))]
+        }
+    };
+    if !rest.is_empty() {
+        task.push(Event::Text(CowStr::from(rest)));
+    }
+    task
+}
+
+/// The tags and attributes which [`sanitize_html`] keeps.
+///
+/// The default policy allows common formatting and structural tags (such as `<div>`, `<span>`,
+/// `<img>` and `<details>`) along with a few harmless attributes, and removes `<script>` and
+/// `<style>` elements entirely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HtmlPolicy {
+    /// Tags which are kept, in lowercase. Other tags are removed, but their content is kept.
+    pub allowed_tags: HashSet<String>,
+    /// Attributes which are kept on allowed tags, in lowercase. Event handler attributes (such as
+    /// `onclick`) are always removed.
+    pub allowed_attributes: HashSet<String>,
+    /// URL schemes which attributes such as `href` and `src` may use, in lowercase. Attributes
+    /// with any other scheme (such as `javascript:`) are removed, while relative URLs are kept.
+    pub allowed_url_schemes: HashSet<String>,
+    /// Tags which are removed along with all of their content, in lowercase.
+    pub removed_content_tags: HashSet<String>,
+}
+
+impl Default for HtmlPolicy {
+    fn default() -> Self {
+        let set = |items: &[&str]| items.iter().map(|&item| item.to_owned()).collect();
+        Self {
+            allowed_tags: set(&[
+                "a",
+                "abbr",
+                "audio",
+                "b",
+                "blockquote",
+                "br",
+                "caption",
+                "code",
+                "col",
+                "colgroup",
+                "dd",
+                "del",
+                "details",
+                "div",
+                "dl",
+                "dt",
+                "em",
+                "figcaption",
+                "figure",
+                "h1",
+                "h2",
+                "h3",
+                "h4",
+                "h5",
+                "h6",
+                "hr",
+                "i",
+                "img",
+                "ins",
+                "kbd",
+                "li",
+                "mark",
+                "ol",
+                "p",
+                "pre",
+                "q",
+                "s",
+                "samp",
+                "small",
+                "source",
+                "span",
+                "strong",
+                "sub",
+                "summary",
+                "sup",
+                "table",
+                "tbody",
+                "td",
+                "tfoot",
+                "th",
+                "thead",
+                "tr",
+                "u",
+                "ul",
+                "video",
+            ]),
+            allowed_attributes: set(&[
+                "align", "alt", "cite", "class", "colspan", "controls", "dir", "height", "href",
+                "id", "lang", "open", "rowspan", "span", "src", "start", "title", "type", "width",
+            ]),
+            allowed_url_schemes: set(&["http", "https", "mailto"]),
+            removed_content_tags: set(&["script", "style"]),
+        }
+    }
+}
+
+/// Attributes whose value is a URL, which is checked against
+/// [`HtmlPolicy::allowed_url_schemes`].
+const URL_ATTRIBUTES: [&str; 7] = [
+    "action",
+    "background",
+    "cite",
+    "formaction",
+    "href",
+    "poster",
+    "src",
+];
+
+static HTML_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"^<(?P<closing>/)?(?P<name>[A-Za-z][A-Za-z0-9:-]*)(?P<attributes>(?:\s+[^\s"'>/=]+(?:\s*=\s*(?:"[^"]*"|'[^']*'|[^\s"'=<>`]+))?)*)\s*(?P<self_closing>/)?>"#,
+    )
+    .unwrap()
+});
+static HTML_ATTRIBUTE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?P<name>[^\s"'>/=]+)(?:\s*=\s*(?:"(?P<double>[^"]*)"|'(?P<single>[^']*)'|(?P<unquoted>[^\s"'=<>`]+)))?"#,
+    )
+    .unwrap()
+});
+/// Comments, declarations (`<!DOCTYPE html>`), CDATA sections and processing instructions, which
+/// are always removed.
+static HTML_MARKUP_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?s:<!--.*?-->|<!\[CDATA\[.*?\]\]>|<![A-Za-z][^>]*>|<\?.*?>)").unwrap()
+});
+
+/// This postprocessor removes raw HTML which isn't allowed by `policy` from notes, such as
+/// `<script>` elements, `<iframe>`s and event handler attributes.
+///
+/// Tags which aren't allowed are removed while keeping their content, so
+/// `<center>Text</center>` becomes `Text`, except for the
+/// [removed content tags][HtmlPolicy::removed_content_tags], which are removed entirely.
+/// Attributes which aren't allowed are removed from the remaining tags. Any `<` which doesn't
+/// start a tag is escaped, so the parts of a removed tag can't combine into a new one.
+///
+/// Only HTML is changed, so code blocks containing HTML are kept as-is.
+pub fn sanitize_html(
+    policy: HtmlPolicy,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |_context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut sanitizer = HtmlSanitizer {
+            policy: &policy,
+            removing: None,
+        };
+        let mut output = Vec::with_capacity(events.len());
+        // HTML blocks are split into an event per line, but tags may span several lines.
+        let mut html_block: Option<String> = None;
+
+        for event in mem::take(events) {
+            match event {
+                Event::Start(Tag::HtmlBlock) => {
+                    html_block = Some(String::new());
+                    output.push(event);
+                }
+                Event::Html(html) => match html_block.as_mut() {
+                    Some(block) => block.push_str(&html),
+                    None => output.push(Event::Html(CowStr::from(sanitizer.sanitize(&html)))),
+                },
+                Event::End(TagEnd::HtmlBlock) => {
+                    let html = sanitizer.sanitize(&html_block.take().unwrap_or_default());
+                    sanitizer.removing = None;
+                    if html.trim().is_empty() {
+                        // Drop the start of the now empty block.
+                        output.pop();
+                    } else {
+                        output.push(Event::Html(CowStr::from(html)));
+                        output.push(event);
+                    }
+                }
+                Event::InlineHtml(html) => {
+                    let html = sanitizer.sanitize(&html);
+                    if !html.is_empty() {
+                        output.push(Event::InlineHtml(CowStr::from(html)));
+                    }
+                }
+                Event::End(end) => {
+                    // Inline HTML can't span several blocks, so an unclosed `<script>` ends along
+                    // with the block containing it.
+                    if !matches!(
+                        end,
+                        TagEnd::Emphasis
+                            | TagEnd::Strong
+                            | TagEnd::Strikethrough
+                            | TagEnd::Link
+                            | TagEnd::Image
+                    ) {
+                        sanitizer.removing = None;
+                    }
+                    output.push(Event::End(end));
+                }
+                // Structure is kept, so removing content doesn't leave unbalanced tags behind.
+                Event::Start(_) => output.push(event),
+                _ if sanitizer.removing.is_some() => {}
+                _ => output.push(event),
+            }
+        }
+        *events = output;
+        PostprocessorResult::Continue
+    }
+}
+
+/// Sanitizes the HTML within a note according to an [`HtmlPolicy`].
+struct HtmlSanitizer<'p> {
+    policy: &'p HtmlPolicy,
+    /// The (lowercase) name of the [removed content tag][HtmlPolicy::removed_content_tags] whose
+    /// content is currently being removed.
+    removing: Option<String>,
+}
+
+impl HtmlSanitizer<'_> {
+    fn sanitize(&mut self, html: &str) -> String {
+        let mut sanitized = String::with_capacity(html.len());
+        let mut rest = html;
+        while !rest.is_empty() {
+            let text_len = rest.find('<').unwrap_or(rest.len());
+            let (text, markup) = rest.split_at(text_len);
+            if self.removing.is_none() {
+                sanitized.push_str(text);
+            }
+            rest = markup;
+            if rest.is_empty() {
+                break;
+            }
+
+            if let Some(found) = HTML_MARKUP_RE.find(rest) {
+                rest = rest.get(found.end()..).unwrap_or_default();
+            } else if let Some(captures) = HTML_TAG_RE.captures(rest) {
+                let name = captures["name"].to_lowercase();
+                let closing = captures.name("closing").is_some();
+                if let Some(removing) = &self.removing {
+                    if closing && *removing == name {
+                        self.removing = None;
+                    }
+                } else if self.policy.removed_content_tags.contains(&name) {
+                    if !closing && captures.name("self_closing").is_none() {
+                        self.removing = Some(name);
+                    }
+                } else if self.policy.allowed_tags.contains(&name) {
+                    sanitized.push('<');
+                    if closing {
+                        sanitized.push('/');
+                    }
+                    sanitized.push_str(&captures["name"]);
+                    if !closing {
+                        self.push_attributes(&mut sanitized, &captures["attributes"]);
+                    }
+                    if captures.name("self_closing").is_some() {
+                        sanitized.push_str(" /");
+                    }
+                    sanitized.push('>');
+                } else {
+                    // Tags which aren't allowed are dropped, but their content is kept.
+                }
+                rest = rest.get(captures[0].len()..).unwrap_or_default();
+            } else {
+                if self.removing.is_none() {
+                    sanitized.push_str("&lt;");
+                }
+                rest = rest.get(1..).unwrap_or_default();
+            }
+        }
+        sanitized
+    }
+
+    /// Append the attributes within `attributes` which are allowed by the policy to `sanitized`.
+    fn push_attributes(&self, sanitized: &mut String, attributes: &str) {
+        for captures in HTML_ATTRIBUTE_RE.captures_iter(attributes) {
+            let name = captures["name"].to_lowercase();
+            if name.starts_with("on") || !self.policy.allowed_attributes.contains(&name) {
+                continue;
+            }
+            let value = captures
+                .name("double")
+                .or_else(|| captures.name("single"))
+                .or_else(|| captures.name("unquoted"))
+                .map(|value| value.as_str());
+            if let Some(value) = value {
+                if URL_ATTRIBUTES.contains(&name.as_str()) && !self.is_allowed_url(value) {
+                    continue;
+                }
+            }
+
+            sanitized.push(' ');
+            sanitized.push_str(&captures["name"]);
+            if let Some(value) = value {
+                sanitized.push_str("=\"");
+                sanitized.push_str(&value.replace('"', "&quot;"));
+                sanitized.push('"');
+            }
+        }
+    }
+
+    /// Return whether `url` is relative or uses one of the allowed URL schemes.
+    fn is_allowed_url(&self, url: &str) -> bool {
+        // Browsers ignore whitespace and control characters within schemes (`java\tscript:`).
+        let url: String = url
+            .chars()
+            .filter(|c| !c.is_whitespace() && !c.is_control())
+            .collect();
+        let prefix_end = url.find(['/', '?', '#']).unwrap_or(url.len());
+        let prefix = url.get(..prefix_end).unwrap_or_default();
+        // Character references such as `&#58;` could hide a scheme.
+        if prefix.contains('&') {
+            return false;
+        }
+        prefix.split_once(':').map_or(true, |(scheme, _)| {
+            self.policy
+                .allowed_url_schemes
+                .contains(&scheme.to_lowercase())
+        })
+    }
+}
+
+/// This postprocessor inserts a fixed header, such as a license or attribution notice, at the top
+/// of the body of each note, after its frontmatter.
+///
+/// The `text` is parsed as (standard) markdown, so Obsidian-specific syntax like `[[links]]` isn't
+/// processed. When `as_comment` is set, it's inserted as an HTML comment (`<!-- text -->`) instead,
+/// which doesn't show up on the rendered page. The header is only added to root notes, not to the
+/// notes embedded within them, so each exported note contains it exactly once.
+#[allow(clippy::needless_pass_by_value)]
+pub fn add_header(
+    text: String,
+    as_comment: bool,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    let header: Vec<Event<'static>> = if as_comment {
+        // `-->` within the text would end the comment early.
+        let comment = format!("<!-- {} -->\n", text.trim().replace("-->", "--&gt;"));
+        html_block(comment).into()
+    } else {
+        Parser::new(&text).map(Event::into_static).collect()
+    };
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        if context.note_depth() == 1 {
+            events.splice(0..0, header.iter().cloned());
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+/// This embed postprocessor wraps the content of embedded notes (`![[Note]]`) in a collapsible
+/// `<details>` element, so embeds show up as labeled sections rather than being inlined silently.
+///
+/// When `show_title` is set, the [title][Context::title] of the embedded note is used as the
+/// `<summary>` of the element. Otherwise, browsers show a generic label such as "Details". As each
+/// embed is wrapped separately, embeds within embedded notes end up nested within each other.
+///
+/// This should be added through
+/// [`Exporter::add_embed_postprocessor`][crate::Exporter::add_embed_postprocessor].
+pub fn embed_as_details(
+    show_title: bool,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let header = if show_title {
+            format!(
+                "<details>\n<summary>{}</summary>\n",
+                escape_html(&context.title())
+            )
+        } else {
+            "<details>\n".to_owned()
+        };
+        events.splice(0..0, html_block(header));
+        events.extend(html_block("</details>\n".to_owned()));
+        PostprocessorResult::Continue
+    }
+}
+
+/// This embed postprocessor renders embeds of a single block (`![[Note#^blockid]]`) as a
+/// blockquote, rather than inlining the content of the block.
+///
+/// When `attribute` is set, the quote ends with a "— [Note](Note.md)" line linking back to the
+/// note which the block was taken from, using its [title][Context::title]. Embeds of entire notes
+/// or of sections are left alone.
+///
+/// This should be added through
+/// [`Exporter::add_embed_postprocessor`][crate::Exporter::add_embed_postprocessor].
+pub fn block_embed_as_quote(
+    attribute: bool,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        if context.embedded_block().is_none() || events.is_empty() {
+            return PostprocessorResult::Continue;
+        }
+        events.insert(0, Event::Start(Tag::BlockQuote(None)));
+        if let Some(source_url) = context.embed_source_url().filter(|_| attribute) {
+            events.extend([
+                Event::Start(Tag::Paragraph),
+                Event::Text(CowStr::from("— ")),
+                Event::Start(Tag::Link {
+                    link_type: LinkType::Inline,
+                    dest_url: CowStr::from(source_url.to_owned()),
+                    title: CowStr::from(""),
+                    id: CowStr::from(""),
+                }),
+                Event::Text(CowStr::from(context.title())),
+                Event::End(TagEnd::Link),
+                Event::End(TagEnd::Paragraph),
+            ]);
+        }
+        events.push(Event::End(TagEnd::BlockQuote(None)));
+        PostprocessorResult::Continue
+    }
+}
+
+/// The file extensions which [`media_embeds_to_html`] treats as audio and video.
+///
+/// The default covers the audio and video formats which Obsidian plays back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MediaExtensions {
+    /// Extensions of audio files, in lowercase and without a leading dot.
+    pub audio: HashSet<String>,
+    /// Extensions of video files, in lowercase and without a leading dot.
+    pub video: HashSet<String>,
+}
+
+impl Default for MediaExtensions {
+    fn default() -> Self {
+        let set = |items: &[&str]| items.iter().map(|&item| item.to_owned()).collect();
+        Self {
+            audio: set(&["3gp", "flac", "m4a", "mp3", "oga", "ogg", "opus", "wav"]),
+            video: set(&["m4v", "mkv", "mov", "mp4", "ogv", "webm"]),
+        }
+    }
+}
+
+/// This postprocessor turns embeds of audio and video files (`![[audio.mp3]]`, `![[clip.mp4]]`)
+/// into `<audio controls>` and `<video controls>` players, like Obsidian displays them.
+///
+/// Such embeds are otherwise exported as links to the file. The files themselves are still copied
+/// as usual, and the players point to the same location as these links would. Which files count
+/// as audio or video is determined by their extension, according to `extensions`.
+pub fn media_embeds_to_html(
+    extensions: MediaExtensions,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |_context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        replace_embeds(events, |url| media_element(&extensions, url));
+        PostprocessorResult::Continue
+    }
+}
+
+/// Replace the links which embeds of files turn into with the HTML returned by `element` for
+/// their URL, leaving the links for which it returns `None` untouched.
+fn replace_embeds(events: &mut MarkdownEvents<'_>, element: impl Fn(&str) -> Option<String>) {
+    let mut idx = 0;
+    while let Some(event) = events.get(idx) {
+        let html = match event {
+            Event::Start(Tag::Link { dest_url, id, .. }) if &**id == EMBED_LINK_ID => {
+                element(dest_url)
+            }
+            _ => None,
+        };
+        if let Some(html) = html {
+            let end = events
+                .iter()
+                .skip(idx)
+                .position(|link_event| matches!(link_event, Event::End(TagEnd::Link)))
+                .map_or(idx, |offset| idx.saturating_add(offset));
+            events.splice(idx..=end, [Event::InlineHtml(CowStr::from(html))]);
+        }
+        idx = idx.saturating_add(1);
+    }
+}
+
+/// Return the HTML element playing the file at `url`, if its extension marks it as audio or video.
+fn media_element(extensions: &MediaExtensions, url: &str) -> Option<String> {
+    let path = percent_decode_str(url).decode_utf8_lossy();
+    let extension = Path::new(path.as_ref())
+        .extension()?
+        .to_string_lossy()
+        .to_lowercase();
+    let tag = if extensions.audio.contains(&extension) {
+        "audio"
+    } else if extensions.video.contains(&extension) {
+        "video"
+    } else {
+        return None;
+    };
+    let src = escape_html(url).replace('"', "&quot;");
+    Some(format!("<{tag} controls src=\"{src}\"></{tag}>"))
+}
+
+/// The HTML element which [`pdf_embeds_to_html`] displays embedded PDF files with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PdfEmbedStyle {
+    /// `<embed src="doc.pdf#page=3" type="application/pdf">`
+    Embed,
+    /// `<iframe src="doc.pdf#page=3"></iframe>`
+    Iframe,
+}
+
+/// This postprocessor turns embeds of PDF files (`![[doc.pdf]]`) into an `<embed>` or `<iframe>`
+/// element according to `style`, which displays the document inline like Obsidian does.
+///
+/// Such embeds are otherwise exported as links to the file. A page selected through the embed
+/// (`![[doc.pdf#page=3]]`) is kept as the `#page=3` fragment of the element's URL, which makes
+/// browsers open the document at that page.
+pub fn pdf_embeds_to_html(
+    style: PdfEmbedStyle,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |_context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        replace_embeds(events, |url| pdf_element(style, url));
+        PostprocessorResult::Continue
+    }
+}
+
+/// Return the HTML element displaying the file at `url`, if it's a PDF file.
+fn pdf_element(style: PdfEmbedStyle, url: &str) -> Option<String> {
+    let path = url.split_once('#').map_or(url, |(path, _fragment)| path);
+    let path = percent_decode_str(path).decode_utf8_lossy();
+    let is_pdf = Path::new(path.as_ref())
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+    if !is_pdf {
+        return None;
+    }
+    let src = escape_html(url).replace('"', "&quot;");
+    Some(match style {
+        PdfEmbedStyle::Embed => format!("<embed src=\"{src}\" type=\"application/pdf\">"),
+        PdfEmbedStyle::Iframe => format!("<iframe src=\"{src}\"></iframe>"),
+    })
+}
+
+/// How [`mermaid_handling`] exports ` ```mermaid ` code blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MermaidMode {
+    /// `<div class="mermaid">...</div>`, which is rendered in the browser by mermaid.js.
+    DivWrapper,
+    /// `![Mermaid diagram](mermaid-<hash>.svg)`, an image pre-rendered by an external renderer.
+    ///
+    /// The source of each diagram is written to `mermaid-<hash>.mmd` within `dir`, which is
+    /// relative to the destination, and the image is expected next to it.
+    ImagePlaceholder { dir: PathBuf },
+}
+
+/// This postprocessor converts ` ```mermaid ` code blocks for renderers which lack support for
+/// [Mermaid] diagrams, according to `mode`.
+///
+/// With [`MermaidMode::ImagePlaceholder`], diagrams are named after a hash of their source, so the
+/// same diagram always maps to the same image and images only need to be rendered again when the
+/// diagram changes. The sources of diagrams are exported along with the note (see
+/// [`Context::add_output_file`]), so nothing is written during a dry run.
+///
+/// [Mermaid]: https://mermaid.js.org/
+pub fn mermaid_handling(
+    mode: MermaidMode,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        let mut converted = Vec::with_capacity(events.len());
+        // The source of the mermaid block currently being read, if any.
+        let mut diagram: Option<String> = None;
+        for event in mem::take(events) {
+            match (event, &mut diagram) {
+                (Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))), None)
+                    if info.split_whitespace().next() == Some("mermaid") =>
+                {
+                    diagram = Some(String::new());
+                }
+                (Event::Text(text), Some(source)) => source.push_str(&text),
+                (Event::End(TagEnd::CodeBlock), Some(_)) => {
+                    let source = diagram.take().unwrap_or_default();
+                    match &mode {
+                        MermaidMode::DivWrapper => converted.extend(mermaid_div(&source)),
+                        MermaidMode::ImagePlaceholder { dir } => {
+                            converted.extend(mermaid_image(context, dir, source));
+                        }
+                    }
+                }
+                (event, _) => converted.push(event),
+            }
+        }
+        *events = converted;
+        PostprocessorResult::Continue
+    }
+}
+
+fn mermaid_div(source: &str) -> [Event<'static>; 3] {
+    // A blank line would end the HTML block early, and mermaid ignores blank lines anyway.
+    let lines: Vec<String> = source
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(escape_html)
+        .collect();
+    html_block(format!(
+        "<div class=\"mermaid\">\n{}\n</div>\n",
+        lines.join("\n")
+    ))
+}
+
+/// Add `source` to the export as its sidecar file within `dir` and return the events of the image
+/// which it's rendered to.
+fn mermaid_image(context: &Context, dir: &Path, source: String) -> [Event<'static>; 5] {
+    let name = format!("mermaid-{:016x}", fnv1a_hash(source.as_bytes()));
+    context.add_output_file(dir.join(format!("{name}.mmd")), source.into_bytes());
+    let image = context
+        .destination_root()
+        .join(dir)
+        .join(format!("{name}.svg"));
+    let link = context
+        .destination
+        .parent()
+        .and_then(|note_dir| diff_paths(&image, note_dir))
+        .unwrap_or(image);
+    [
+        Event::Start(Tag::Paragraph),
+        Event::Start(Tag::Image {
+            link_type: LinkType::Inline,
+            dest_url: CowStr::from(url_path(&link)),
+            title: CowStr::Borrowed(""),
+            id: CowStr::Borrowed(""),
+        }),
+        Event::Text(CowStr::Borrowed("Mermaid diagram")),
+        Event::End(TagEnd::Image),
+        Event::End(TagEnd::Paragraph),
+    ]
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, which (unlike the hashers in the standard library) is
+/// guaranteed to stay the same across releases.
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// This postprocessor sets the frontmatter `key` (such as `excerpt` or `description`) to an
+/// excerpt of the note, as used by listing pages and search engines.
+///
+/// The excerpt is the text of the first top-level paragraph which contains any, without
+/// formatting, links or images. When it's longer than `max_chars` characters, it's cut off at the
+/// last word which fits and ends with "…". Notes which already have the key are left alone.
+#[allow(clippy::needless_pass_by_value)]
+pub fn auto_excerpt(
+    key: String,
+    max_chars: usize,
+) -> impl Fn(&mut Context, &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    move |context: &mut Context, events: &mut MarkdownEvents<'_>| -> PostprocessorResult {
+        if context.frontmatter.contains_key(key.as_str()) {
+            return PostprocessorResult::Continue;
+        }
+        if let Some(excerpt) = first_paragraph_text(events)
+            .map(|text| truncate_on_word(&text, max_chars))
+            .filter(|excerpt| !excerpt.is_empty())
+        {
+            context
+                .frontmatter
+                .insert(Value::String(key.clone()), Value::String(excerpt));
+        }
+        PostprocessorResult::Continue
+    }
+}
+
+/// Return the plain text of the first top-level paragraph within `events` which contains any,
+/// with whitespace collapsed.
+fn first_paragraph_text(events: &[Event<'_>]) -> Option<String> {
+    let mut depth = 0_usize;
+    let mut in_image = false;
+    let mut paragraph: Option<String> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::Image { .. }) => in_image = true,
+            Event::End(TagEnd::Image) => in_image = false,
+            Event::Start(tag) => {
+                if depth == 0 && matches!(tag, Tag::Paragraph) {
+                    paragraph = Some(String::new());
+                }
+                depth = depth.saturating_add(1);
+            }
+            Event::End(_) => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    let text = paragraph.take().unwrap_or_default();
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    if !text.is_empty() {
+                        return Some(text);
+                    }
+                }
+            }
+            Event::Text(fragment) | Event::Code(fragment) | Event::InlineMath(fragment)
+                if !in_image =>
+            {
+                if let Some(text) = paragraph.as_mut() {
+                    text.push_str(fragment);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(text) = paragraph.as_mut() {
+                    text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Shorten `text` to at most `max_chars` characters, cutting it off after the last whole word
+/// which fits and ending it with "…".
+fn truncate_on_word(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_owned();
+    }
+    let limit = max_chars.saturating_sub(1);
+    let mut truncated = String::new();
+    for word in text.split(' ') {
+        let separator = usize::from(!truncated.is_empty());
+        let length = truncated
+            .chars()
+            .count()
+            .saturating_add(separator)
+            .saturating_add(word.chars().count());
+        if length > limit {
+            break;
+        }
+        if separator == 1 {
+            truncated.push(' ');
+        }
+        truncated.push_str(word);
+    }
+    if truncated.is_empty() {
+        // The first word alone is too long, so it's cut off instead.
+        truncated = text.chars().take(limit).collect();
+    }
+    if truncated.is_empty() {
+        return truncated;
+    }
+    let mut truncated = truncated
+        .trim_end_matches([',', ';', ':', '.', '-'])
+        .to_owned();
+    truncated.push('…');
+    truncated
+}
+
+/// The type of a frontmatter value, as checked by [`validate_frontmatter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum YamlType {
+    /// Any value, including `null`.
+    Any,
+    /// An empty value (`null` or `~`).
+    Null,
+    /// `true` or `false`.
+    Bool,
+    /// An integer or floating point number.
+    Number,
+    /// A string, including dates such as `2023-01-05`.
+    String,
+    /// A list of values.
+    List,
+    /// A mapping of keys to values.
+    Mapping,
+}
+
+impl YamlType {
+    /// Return the type of `value`.
+    #[must_use]
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(_) => Self::Bool,
+            Value::Number(_) => Self::Number,
+            Value::String(_) => Self::String,
+            Value::Sequence(_) => Self::List,
+            Value::Mapping(_) => Self::Mapping,
+            Value::Tagged(tagged) => Self::of(&tagged.value),
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        self == Self::Any || self == Self::of(value)
+    }
+}
+
+impl fmt::Display for YamlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Any => "any value",
+            Self::Null => "null",
+            Self::Bool => "a boolean",
+            Self::Number => "a number",
+            Self::String => "a string",
+            Self::List => "a list",
+            Self::Mapping => "a mapping",
+        })
+    }
+}
+
+/// The keys which [`validate_frontmatter`] expects in the frontmatter of notes, along with the
+/// types of their values.
+///
+/// # Example
+///
+/// ```
+/// use obsidian_export::postprocessors::{FrontmatterSchema, YamlType};
+///
+/// let schema = FrontmatterSchema::new()
+///     .require("title", YamlType::String)
+///     .optional("tags", YamlType::List);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FrontmatterSchema {
+    /// Keys which every note must have.
+    pub required: BTreeSet<String>,
+    /// The type which the value under each key must have, for keys which are present.
+    pub types: BTreeMap<String, YamlType>,
+}
+
+impl FrontmatterSchema {
+    /// Create an empty schema, which accepts any frontmatter.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require every note to have `key`, with a value of type `value_type`.
+    #[must_use]
+    pub fn require(mut self, key: &str, value_type: YamlType) -> Self {
+        self.required.insert(key.to_owned());
+        self.types.insert(key.to_owned(), value_type);
+        self
+    }
+
+    /// Allow notes to leave out `key`, but require its value to be of type `value_type` when
+    /// present.
+    #[must_use]
+    pub fn optional(mut self, key: &str, value_type: YamlType) -> Self {
+        self.required.remove(key);
+        self.types.insert(key.to_owned(), value_type);
+        self
+    }
+
+    /// Check `frontmatter` against this schema, returning the first violation found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when a required key is missing or a value has the wrong type.
+    pub fn validate(&self, frontmatter: &Frontmatter) -> Result<(), FrontmatterValidationError> {
+        if let Some(key) = self
+            .required
+            .iter()
+            .find(|key| !frontmatter.contains_key(key.as_str()))
+        {
+            return Err(FrontmatterValidationError::MissingKey { key: key.clone() });
+        }
+        for (key, expected) in &self.types {
+            if let Some(value) = frontmatter.get(key.as_str()) {
+                if !expected.matches(value) {
+                    return Err(FrontmatterValidationError::WrongType {
+                        key: key.clone(),
+                        expected: *expected,
+                        found: YamlType::of(value),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The reason a note was rejected by [`validate_frontmatter`].
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+#[non_exhaustive]
+pub enum FrontmatterValidationError {
+    #[snafu(display("missing required frontmatter key '{key}'"))]
+    /// The frontmatter lacks the required `key`.
+    MissingKey { key: String },
+    #[snafu(display("frontmatter key '{key}' should be {expected}, but is {found}"))]
+    /// The value under `key` has the wrong type.
+    WrongType {
+        key: String,
+        expected: YamlType,
+        found: YamlType,
+    },
+}
+
+/// This fallible postprocessor rejects notes whose frontmatter doesn't match `schema`, for
+/// example because a required key is missing or a value has the wrong type.
+///
+/// A rejected note aborts the export with an
+/// [`ExportError::PostprocessorError`][crate::ExportError::PostprocessorError], whose source is a
+/// [`FrontmatterValidationError`] naming the offending key. This should be added through
+/// [`Exporter::add_fallible_postprocessor`][crate::Exporter::add_fallible_postprocessor].
+pub fn validate_frontmatter(
+    schema: FrontmatterSchema,
+) -> impl Fn(
+    &mut Context,
+    &mut MarkdownEvents<'_>,
+) -> Result<PostprocessorResult, Box<dyn std::error::Error + Send + Sync>> {
+    move |context: &mut Context, _events: &mut MarkdownEvents<'_>| {
+        schema.validate(&context.frontmatter)?;
+        Ok(PostprocessorResult::Continue)
+    }
+}
+
 pub fn filter_by_tags(
     skip_tags: Vec<String>,
     only_tags: Vec<String>,
@@ -1,11 +1,16 @@
-use std::fmt;
 use std::sync::LazyLock;
+use std::{fmt, mem};
 
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
 use regex::Regex;
 
 static OBSIDIAN_NOTE_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"^(?P<file>[^#|]+)??(#(?P<section>.+?))??(\|(?P<label>.+?))??$").unwrap()
+    // The label is separated by `\|` rather than `|` within tables. It may contain any characters,
+    // including `#` and `|`.
+    Regex::new(r"^(?P<file>[^#|]+?)??(#(?P<section>[^|]*?))??(\\?\|(?P<label>.*))?$").unwrap()
 });
+pub static WIKILINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?P<embed>!)?\[\[(?P<reference>[^\[\]]+)\]\]").unwrap());
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 /// `ObsidianNoteReference` represents the structure of a `[[note]]` or `![[embed]]` reference.
@@ -47,6 +52,8 @@ pub struct RefParser {
     // ref_text concatenates the values from these partial events so that there's a fully-formed
     // string to work with by the time the final `]]` is encountered.
     pub ref_text: String,
+    // The byte offset of the opening brackets of the reference within the note.
+    pub offset: usize,
 }
 
 impl RefParser {
@@ -55,6 +62,7 @@ impl RefParser {
             state: RefParserState::NoState,
             ref_type: None,
             ref_text: String::new(),
+            offset: 0,
         }
     }
 
@@ -66,17 +74,26 @@ impl RefParser {
         self.state = RefParserState::NoState;
         self.ref_type = None;
         self.ref_text.clear();
+        self.offset = 0;
     }
 }
 
-impl<'a> ObsidianNoteReference<'a> {
+impl ObsidianNoteReference<'_> {
     pub fn from_str(text: &str) -> ObsidianNoteReference<'_> {
         let captures = OBSIDIAN_NOTE_LINK_RE
             .captures(text)
             .expect("note link regex didn't match - bad input?");
-        let file = captures.name("file").map(|v| v.as_str().trim());
-        let label = captures.name("label").map(|v| v.as_str());
-        let section = captures.name("section").map(|v| v.as_str().trim());
+        // Empty parts are left out, so `[[Note|]]` is displayed as `Note`, while `[[ #Heading]]`
+        // has no note name either, referring to the current note.
+        let part = |name| {
+            captures
+                .name(name)
+                .map(|v| v.as_str().trim())
+                .filter(|v| !v.is_empty())
+        };
+        let file = part("file");
+        let section = part("section");
+        let label = part("label");
 
         ObsidianNoteReference {
             file,
@@ -90,15 +107,15 @@ impl<'a> ObsidianNoteReference<'a> {
     }
 }
 
-impl<'a> fmt::Display for ObsidianNoteReference<'a> {
+impl fmt::Display for ObsidianNoteReference<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let label = self.label.map_or_else(
             || match (self.file, self.section) {
                 (Some(file), Some(section)) => format!("{file} > {section}"),
                 (Some(file), None) => file.to_owned(),
                 (None, Some(section)) => section.to_owned(),
-
-                _ => panic!("Reference exists without file or section!"),
+                // An empty reference, such as `[[#]]`.
+                (None, None) => String::new(),
             },
             ToString::to_string,
         );
@@ -106,6 +123,60 @@ impl<'a> fmt::Display for ObsidianNoteReference<'a> {
     }
 }
 
+/// A file referenced by a note, as found by [`referenced_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReference {
+    /// The file as it's written within the note.
+    pub file: String,
+    /// Whether the file is embedded using `![[embed]]`, rather than linked to.
+    pub is_embed: bool,
+}
+
+/// Find the files referenced by the note `content`, through either wikilinks (`[[note]]`,
+/// `![[embed]]`) or markdown links and images, as they are written within the note.
+///
+/// This is a lightweight scan which doesn't resolve references or process embeds. References
+/// within code are ignored.
+pub fn referenced_files(content: &str) -> Vec<FileReference> {
+    let mut references = Vec::new();
+    let mut text = String::new();
+    let mut in_code_block = false;
+    for event in Parser::new(content) {
+        match event {
+            Event::Text(fragment) if !in_code_block => {
+                // Wikilinks are often split across multiple text events.
+                text.push_str(&fragment);
+                continue;
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                if let Some(file) = dest_url.split('#').next().filter(|file| !file.is_empty()) {
+                    references.push(FileReference {
+                        file: file.to_owned(),
+                        is_embed: false,
+                    });
+                }
+            }
+            _ => {}
+        }
+        references.extend(wikilink_files(&mem::take(&mut text)));
+    }
+    references.extend(wikilink_files(&text));
+    references
+}
+
+fn wikilink_files(text: &str) -> impl Iterator<Item = FileReference> + '_ {
+    WIKILINK_RE.captures_iter(text).filter_map(|captures| {
+        let reference = captures.name("reference")?.as_str();
+        let file = ObsidianNoteReference::from_str(reference).file?;
+        Some(FileReference {
+            file: file.to_owned(),
+            is_embed: captures.name("embed").is_some(),
+        })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,6 +225,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_aliased_note_refs() {
+        assert_eq!(
+            ObsidianNoteReference::from_str("Real Name|Display #Text"),
+            ObsidianNoteReference {
+                file: Some("Real Name"),
+                label: Some("Display #Text"),
+                section: None,
+            }
+        );
+        assert_eq!(
+            ObsidianNoteReference::from_str("Note#Heading\\|Escaped label"),
+            ObsidianNoteReference {
+                file: Some("Note"),
+                label: Some("Escaped label"),
+                section: Some("Heading"),
+            }
+        );
+        assert_eq!(
+            ObsidianNoteReference::from_str("Note\\|Escaped label"),
+            ObsidianNoteReference {
+                file: Some("Note"),
+                label: Some("Escaped label"),
+                section: None,
+            }
+        );
+        assert_eq!(
+            ObsidianNoteReference::from_str("Note|Label|with pipe"),
+            ObsidianNoteReference {
+                file: Some("Note"),
+                label: Some("Label|with pipe"),
+                section: None,
+            }
+        );
+        assert_eq!(
+            ObsidianNoteReference::from_str(" Note | Spaced label "),
+            ObsidianNoteReference {
+                file: Some("Note"),
+                label: Some("Spaced label"),
+                section: None,
+            }
+        );
+        assert_eq!(
+            ObsidianNoteReference::from_str("Note|"),
+            ObsidianNoteReference {
+                file: Some("Note"),
+                label: None,
+                section: None,
+            }
+        );
+        assert_eq!(
+            ObsidianNoteReference::from_str("#"),
+            ObsidianNoteReference {
+                file: None,
+                label: None,
+                section: None,
+            }
+        );
+    }
+
     #[test]
     fn test_display_of_note_refs() {
         assert_eq!(
@@ -0,0 +1,64 @@
+use pulldown_cmark_to_cmark::{cmark_with_options, Options};
+
+use crate::MarkdownEvents;
+
+/// A `MarkdownSerializer` turns the [`MarkdownEvents`] of a note into markdown text, once all
+/// [postprocessors][crate::Postprocessor] have run.
+///
+/// This is the final step of exporting a note, so implementing it gives full control over the
+/// formatting of the output, such as the style of headings or links. The exporter writes the
+/// frontmatter before the serialized body and adds a final newline after it.
+///
+/// See [`Exporter::set_serializer`][crate::Exporter::set_serializer].
+///
+/// # Example
+///
+/// ```
+/// use obsidian_export::pulldown_cmark_to_cmark::Options;
+/// use obsidian_export::{CmarkSerializer, Exporter};
+/// # use std::path::PathBuf;
+/// # use tempfile::TempDir;
+/// # let tmp_dir = TempDir::new().expect("failed to make tempdir");
+/// # let source = PathBuf::from("tests/testdata/input/postprocessors");
+/// # let destination = tmp_dir.path().to_path_buf();
+///
+/// let mut exporter = Exporter::new(source, destination);
+/// // Use `-` for bullets and `_` for emphasis.
+/// exporter.set_serializer(Box::new(CmarkSerializer::new(Options {
+///     list_token: '-',
+///     emphasis_token: '_',
+///     ..Options::default()
+/// })));
+/// exporter.run().unwrap();
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub trait MarkdownSerializer: Send + Sync {
+    /// Serialize `events` to markdown text.
+    fn serialize(&self, events: &MarkdownEvents<'_>) -> String;
+}
+
+/// The default [`MarkdownSerializer`], which uses [`pulldown_cmark_to_cmark`] with the given
+/// [`Options`] to choose between formatting alternatives, such as the characters used for bullets
+/// and emphasis.
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct CmarkSerializer {
+    options: Options<'static>,
+}
+
+impl CmarkSerializer {
+    /// Create a serializer which formats markdown according to `options`.
+    #[must_use]
+    pub const fn new(options: Options<'static>) -> Self {
+        Self { options }
+    }
+}
+
+impl MarkdownSerializer for CmarkSerializer {
+    fn serialize(&self, events: &MarkdownEvents<'_>) -> String {
+        let mut buffer = String::new();
+        cmark_with_options(events.iter(), &mut buffer, self.options.clone())
+            .expect("formatting to string not expected to fail");
+        buffer
+    }
+}
@@ -0,0 +1,406 @@
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+use log::warn;
+use percent_encoding::percent_decode_str;
+use rayon::prelude::*;
+use serde_yaml::Value;
+use snafu::ResultExt;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::frontmatter::{extract_frontmatter, frontmatter_from_str};
+use crate::walker::{all_vault_files, vault_contents_with_ignore_files};
+use crate::{
+    default_note_extensions,
+    has_note_extension,
+    lookup_filename_in_vault,
+    lookup_filename_near,
+    ExportError,
+    FrontMatterDecodeSnafu,
+    Frontmatter,
+    ReadSnafu,
+    WalkOptions,
+};
+
+type Result<T, E = ExportError> = std::result::Result<T, E>;
+
+/// `LinkResolutionMode` determines how the paths within references such as `[[folder/note]]` are
+/// interpreted. This corresponds to the "New link format" setting of Obsidian.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LinkResolutionMode {
+    /// References contain the shortest path which uniquely identifies a file, which may be just
+    /// the filename (`[[note]]`) or include some of its parent directories (`[[folder/note]]`).
+    ///
+    /// When multiple files match a reference, the one in the same directory as the note containing
+    /// the reference is used, or else the one closest to the root of the vault.
+    #[default]
+    ShortestPath,
+    /// References contain a path relative to the note containing the reference
+    /// (`[[../folder/note]]`).
+    Relative,
+    /// References contain the full path from the root of the vault (`[[folder/note]]`).
+    Absolute,
+}
+
+/// `VaultIndex` holds the files of an Obsidian vault, which are used to resolve references such as
+/// `[[note]]` to the files they point to.
+///
+/// An index is built automatically by [`Exporter::run`][crate::Exporter::run]. It may also be
+/// built ahead of time through [`Exporter::build_index`][crate::Exporter::build_index], to query
+/// the targets of references or to reuse it across calls to
+/// [`Exporter::export_str`][crate::Exporter::export_str].
+#[derive(Debug, Clone)]
+pub struct VaultIndex {
+    root: PathBuf,
+    contents: Vec<PathBuf>,
+    aliases: HashMap<String, PathBuf>,
+    permalinks: HashMap<PathBuf, String>,
+    ignore_hidden: bool,
+    follow_symlinks: bool,
+    note_extensions: Vec<String>,
+    /// Files which exist within the vault but aren't part of `contents`. These are only needed in
+    /// rare cases, so they're collected on first use.
+    excluded: OnceLock<Vec<PathBuf>>,
+}
+
+impl VaultIndex {
+    /// Build an index of the vault located at `root`, including all files which would be exported
+    /// when using the given [`WalkOptions`].
+    pub fn new(root: &Path, walk_options: WalkOptions<'_>) -> Result<Self> {
+        Self::with_note_extensions(root, walk_options, default_note_extensions())
+    }
+
+    /// Build an index like [`VaultIndex::new`], treating files with the given extensions as notes
+    /// (see [`Exporter::note_extensions`][crate::Exporter::note_extensions]).
+    ///
+    /// References may leave out the extension of notes (`[[note]]`), and aliases are read from the
+    /// frontmatter of notes.
+    pub fn with_note_extensions(
+        root: &Path,
+        walk_options: WalkOptions<'_>,
+        note_extensions: Vec<String>,
+    ) -> Result<Self> {
+        Self::with_ignore_files(root, walk_options, note_extensions, &[])
+    }
+
+    /// Build an index like [`VaultIndex::with_note_extensions`], also leaving out the files
+    /// matched by `ignore_files` (see
+    /// [`Exporter::add_ignore_file`][crate::Exporter::add_ignore_file]).
+    pub(crate) fn with_ignore_files(
+        root: &Path,
+        walk_options: WalkOptions<'_>,
+        note_extensions: Vec<String>,
+        ignore_files: &[PathBuf],
+    ) -> Result<Self> {
+        let (ignore_hidden, follow_symlinks) =
+            (walk_options.ignore_hidden, walk_options.follow_symlinks);
+        let contents = vault_contents_with_ignore_files(root, walk_options, ignore_files)?;
+        let aliases = collect_aliases(&contents, &note_extensions)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            contents,
+            aliases,
+            permalinks: HashMap::new(),
+            ignore_hidden,
+            follow_symlinks,
+            note_extensions,
+            excluded: OnceLock::new(),
+        })
+    }
+
+    /// Record the permalinks which notes declare through the frontmatter `key` (such as
+    /// `permalink`), which determine where these notes are exported to (see
+    /// [`Exporter::use_permalink_frontmatter`][crate::Exporter::use_permalink_frontmatter]).
+    pub fn with_permalinks(mut self, key: &str) -> Result<Self> {
+        self.permalinks = self
+            .contents
+            .par_iter()
+            .filter(|path| has_note_extension(path, &self.note_extensions))
+            .map(|path| {
+                let frontmatter = read_frontmatter(path)?;
+                let permalink = frontmatter.get(key).and_then(Value::as_str);
+                Ok(permalink.map(|link| (path.clone(), link.to_owned())))
+            })
+            .filter_map(Result::transpose)
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Return the permalink declared by the note at `path`, if permalinks were recorded through
+    /// [`VaultIndex::with_permalinks`].
+    #[must_use]
+    pub fn permalink(&self, path: &Path) -> Option<&str> {
+        self.permalinks.get(path).map(String::as_str)
+    }
+
+    /// Return the root directory of the vault.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Return all of the files contained in the vault.
+    #[must_use]
+    pub fn contents(&self) -> &[PathBuf] {
+        &self.contents
+    }
+
+    /// Return the notes contained in the vault, leaving out attachments.
+    pub fn all_notes(&self) -> impl Iterator<Item = &Path> {
+        self.contents
+            .iter()
+            .filter(move |path| has_note_extension(path, &self.note_extensions))
+            .map(PathBuf::as_path)
+    }
+
+    /// Find the file in the vault that `filename` refers to, falling back to note aliases (declared
+    /// through the `aliases` frontmatter key) when no file matches.
+    ///
+    /// Both `/` and `\` are accepted as path separators, so references written on any platform
+    /// resolve the same way.
+    #[must_use]
+    pub fn lookup(&self, filename: &str) -> Option<&PathBuf> {
+        let filename = normalize_separators(filename);
+        lookup_filename_in_vault(&filename, &self.contents, &self.note_extensions)
+            .or_else(|| self.aliases.get(&normalize_alias(&filename)))
+    }
+
+    /// Find the file in the vault that a reference to `filename` within the note at `source`
+    /// refers to, interpreting `filename` according to the given [`LinkResolutionMode`].
+    ///
+    /// Percent-encoded references (such as `My%20Note`, as found in links copied from elsewhere)
+    /// are decoded when they don't match a file as-is. Note aliases are used as a fallback when no
+    /// file matches. Both `/` and `\` are accepted as path separators.
+    #[must_use]
+    pub fn resolve(
+        &self,
+        filename: &str,
+        source: &Path,
+        mode: LinkResolutionMode,
+    ) -> Option<&PathBuf> {
+        with_decoded(&normalize_separators(filename), |candidate| {
+            resolve_in(&self.contents, self, candidate, source, mode)
+                .or_else(|| self.aliases.get(&normalize_alias(candidate)))
+        })
+    }
+
+    /// Find the file that a reference to `filename` within the note at `source` refers to among
+    /// the files which exist within the vault, but are excluded from the index (for example
+    /// through `.export-ignore`).
+    ///
+    /// This is the counterpart of [`VaultIndex::resolve`], and is used to tell references to
+    /// excluded files apart from references to files which don't exist at all.
+    #[must_use]
+    pub fn resolve_excluded(
+        &self,
+        filename: &str,
+        source: &Path,
+        mode: LinkResolutionMode,
+    ) -> Option<&PathBuf> {
+        with_decoded(&normalize_separators(filename), |candidate| {
+            resolve_in(self.excluded(), self, candidate, source, mode)
+        })
+    }
+
+    fn excluded(&self) -> &[PathBuf] {
+        self.excluded.get_or_init(|| {
+            let walk_options = WalkOptions {
+                ignore_hidden: self.ignore_hidden,
+                follow_symlinks: self.follow_symlinks,
+                ..WalkOptions::default()
+            };
+            match all_vault_files(&self.root, &walk_options) {
+                Ok(files) => {
+                    let included: HashSet<&PathBuf> = self.contents.iter().collect();
+                    files
+                        .into_iter()
+                        .filter(|file| !included.contains(file))
+                        .collect()
+                }
+                Err(err) => {
+                    warn!(
+                        "Unable to list files excluded from the export\n\tVault: '{}'\n\tError: {}",
+                        self.root.display(),
+                        err,
+                    );
+                    vec![]
+                }
+            }
+        })
+    }
+}
+
+/// Replace Windows-style `\` path separators in `filename` with `/`.
+fn normalize_separators(filename: &str) -> Cow<'_, str> {
+    if filename.contains('\\') {
+        Cow::Owned(filename.replace('\\', "/"))
+    } else {
+        Cow::Borrowed(filename)
+    }
+}
+
+/// Call `lookup` with `filename`, and then with its percent-decoded form if that differs and
+/// nothing was found.
+fn with_decoded<'i>(
+    filename: &str,
+    lookup: impl Fn(&str) -> Option<&'i PathBuf>,
+) -> Option<&'i PathBuf> {
+    lookup(filename).or_else(|| match percent_decode_str(filename).decode_utf8() {
+        Ok(decoded) if decoded != filename => lookup(&decoded),
+        _ => None,
+    })
+}
+
+/// Find the file among `contents` that a reference to `filename` within the note at `source`
+/// refers to, interpreting `filename` according to the given [`LinkResolutionMode`].
+fn resolve_in<'c>(
+    contents: &'c [PathBuf],
+    index: &VaultIndex,
+    filename: &str,
+    source: &Path,
+    mode: LinkResolutionMode,
+) -> Option<&'c PathBuf> {
+    let (root, note_extensions) = (&index.root, &index.note_extensions);
+    let source_dir = source.parent().unwrap_or(root);
+    match mode {
+        LinkResolutionMode::ShortestPath => {
+            lookup_filename_near(filename, contents, Some(source_dir), note_extensions)
+        }
+        LinkResolutionMode::Relative if !filename.starts_with('/') => {
+            lookup_path(contents, &source_dir.join(filename), note_extensions)
+        }
+        LinkResolutionMode::Relative | LinkResolutionMode::Absolute => lookup_path(
+            contents,
+            &root.join(filename.trim_start_matches('/')),
+            note_extensions,
+        ),
+    }
+}
+
+/// Find the file at exactly `target` (with or without one of the `note_extensions`), falling back
+/// to a case-insensitive match.
+fn lookup_path<'c>(
+    contents: &'c [PathBuf],
+    target: &Path,
+    note_extensions: &[String],
+) -> Option<&'c PathBuf> {
+    let target = normalize_path(target);
+    let target_normalized = target.to_string_lossy().nfc().collect::<String>();
+    let target_lowered = target_normalized.to_lowercase();
+    let is_match = |path: &PathBuf, expected: &str, lowercase: bool| {
+        let mut path = path.to_string_lossy().nfc().collect::<String>();
+        if lowercase {
+            path = path.to_lowercase();
+        }
+        let path = PathBuf::from(path);
+        path == Path::new(expected)
+            || note_extensions
+                .iter()
+                .any(|extension| path == Path::new(&format!("{expected}.{extension}")))
+    };
+    contents
+        .iter()
+        .find(|path| is_match(path, &target_normalized, false))
+        .or_else(|| {
+            contents
+                .iter()
+                .find(|path| is_match(path, &target_lowered, true))
+        })
+}
+
+/// Lexically resolve `.` and `..` components of `path`.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Build a map of the aliases declared through the `aliases` frontmatter key of notes to the notes
+/// declaring them.
+///
+/// Aliases which collide with an actual file in the vault are ignored, as are aliases which were
+/// already claimed by another note.
+fn collect_aliases(
+    vault_contents: &[PathBuf],
+    note_extensions: &[String],
+) -> Result<HashMap<String, PathBuf>> {
+    let declared: Vec<(&PathBuf, Vec<String>)> = vault_contents
+        .par_iter()
+        .filter(|path| has_note_extension(path, note_extensions))
+        .map(|path| Ok((path, read_note_aliases(path)?)))
+        .collect::<Result<_>>()?;
+
+    let mut aliases: HashMap<String, PathBuf> = HashMap::new();
+    for (path, names) in declared {
+        for alias in names {
+            if let Some(existing) =
+                lookup_filename_in_vault(&alias, vault_contents, note_extensions)
+            {
+                if existing != path {
+                    warn!(
+                        "Alias matches an existing note, which takes precedence\n\tAlias: '{}'\n\tSource: '{}'\n\tExisting note: '{}'",
+                        alias,
+                        path.display(),
+                        existing.display(),
+                    );
+                }
+                continue;
+            }
+            match aliases.entry(normalize_alias(&alias)) {
+                Entry::Occupied(entry) => {
+                    warn!(
+                        "Alias is declared by multiple notes, using the first one\n\tAlias: '{}'\n\tSource: '{}'\n\tUsing: '{}'",
+                        alias,
+                        path.display(),
+                        entry.get().display(),
+                    );
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(path.clone());
+                }
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+/// Read the aliases declared in the frontmatter of the note at `path`.
+///
+/// Obsidian accepts both a list of aliases and a single string.
+fn read_note_aliases(path: &Path) -> Result<Vec<String>> {
+    let frontmatter = read_frontmatter(path)?;
+    let aliases = match frontmatter.get("aliases") {
+        Some(Value::Sequence(aliases)) => aliases
+            .iter()
+            .filter_map(Value::as_str)
+            .map(ToOwned::to_owned)
+            .collect(),
+        Some(Value::String(alias)) => vec![alias.clone()],
+        _ => vec![],
+    };
+    Ok(aliases)
+}
+
+/// Read the frontmatter of the note at `path`.
+fn read_frontmatter(path: &Path) -> Result<Frontmatter> {
+    let content = fs::read_to_string(path).context(ReadSnafu { path })?;
+    frontmatter_from_str(&extract_frontmatter(&content)).context(FrontMatterDecodeSnafu { path })
+}
+
+/// Normalize an alias for case-insensitive lookups.
+fn normalize_alias(alias: &str) -> String {
+    alias.trim().nfc().collect::<String>().to_lowercase()
+}
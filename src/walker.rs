@@ -1,10 +1,13 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::{DirEntry, Walk, WalkBuilder};
 use snafu::ResultExt;
 
-use crate::{ExportError, WalkDirSnafu};
+use crate::{ExportError, IgnoreFileSnafu, PassthroughPatternSnafu, ReadSnafu, WalkDirSnafu};
 
 type Result<T, E = ExportError> = std::result::Result<T, E>;
 type FilterFn = dyn Fn(&DirEntry) -> bool + Send + Sync + 'static;
@@ -27,6 +30,14 @@ pub struct WalkOptions<'a> {
     ///
     /// This is enabled by default.
     pub honor_gitignore: bool,
+    /// Whether to follow symbolic links to files and directories.
+    ///
+    /// When enabled, a file which can be reached through multiple paths (for example both directly
+    /// and through a symlinked directory) is only included once. Symlinks which point to one of
+    /// their own parent directories result in an [`ExportError::SymlinkLoop`] error.
+    ///
+    /// This is disabled by default.
+    pub follow_symlinks: bool,
     /// An optional custom filter function which is called for each directory entry to determine if
     /// it should be included or not.
     ///
@@ -34,7 +45,7 @@ pub struct WalkOptions<'a> {
     pub filter_fn: Option<&'static FilterFn>,
 }
 
-impl<'a> fmt::Debug for WalkOptions<'a> {
+impl fmt::Debug for WalkOptions<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let filter_fn_fmt = match self.filter_fn {
             Some(_) => "<function set>",
@@ -44,12 +55,13 @@ impl<'a> fmt::Debug for WalkOptions<'a> {
             .field("ignore_filename", &self.ignore_filename)
             .field("ignore_hidden", &self.ignore_hidden)
             .field("honor_gitignore", &self.honor_gitignore)
+            .field("follow_symlinks", &self.follow_symlinks)
             .field("filter_fn", &filter_fn_fmt)
             .finish()
     }
 }
 
-impl<'a> WalkOptions<'a> {
+impl WalkOptions<'_> {
     /// Create a new set of options using default values.
     #[must_use]
     pub fn new() -> Self {
@@ -57,11 +69,12 @@ impl<'a> WalkOptions<'a> {
             ignore_filename: ".export-ignore",
             ignore_hidden: true,
             honor_gitignore: true,
+            follow_symlinks: false,
             filter_fn: None,
         }
     }
 
-    fn build_walker(self, path: &Path) -> Walk {
+    fn build_walker(self, path: &Path, ignore_files: &[PathBuf]) -> Result<Walk> {
         let mut walker = WalkBuilder::new(path);
         walker
             .standard_filters(false)
@@ -71,16 +84,33 @@ impl<'a> WalkOptions<'a> {
             .require_git(true)
             .git_ignore(self.honor_gitignore)
             .git_global(self.honor_gitignore)
-            .git_exclude(self.honor_gitignore);
+            .git_exclude(self.honor_gitignore)
+            .follow_links(self.follow_symlinks)
+            // Sorting entries makes the order in which files are found independent of the
+            // filesystem, so exports are reproducible.
+            .sort_by_file_name(Ord::cmp);
 
-        if let Some(filter) = self.filter_fn {
-            walker.filter_entry(filter);
+        let global_ignore = match ignore_files {
+            [] => None,
+            files => Some(GlobalIgnore::new(path, files, self.ignore_filename)?),
+        };
+        match (self.filter_fn, global_ignore) {
+            (Some(filter), Some(global_ignore)) => {
+                walker.filter_entry(move |entry| filter(entry) && !global_ignore.is_ignored(entry));
+            }
+            (Some(filter), None) => {
+                walker.filter_entry(filter);
+            }
+            (None, Some(global_ignore)) => {
+                walker.filter_entry(move |entry| !global_ignore.is_ignored(entry));
+            }
+            (None, None) => {}
         }
-        walker.build()
+        Ok(walker.build())
     }
 }
 
-impl<'a> Default for WalkOptions<'a> {
+impl Default for WalkOptions<'_> {
     fn default() -> Self {
         Self::new()
     }
@@ -88,18 +118,168 @@ impl<'a> Default for WalkOptions<'a> {
 
 /// `vault_contents` returns all of the files in an Obsidian vault located at `path` which would be
 /// exported when using the given [`WalkOptions`].
+///
+/// Files are returned in a stable order, sorted by path.
 pub fn vault_contents(root: &Path, opts: WalkOptions<'_>) -> Result<Vec<PathBuf>> {
+    vault_contents_with_ignore_files(root, opts, &[])
+}
+
+/// Like [`vault_contents`], additionally excluding the files matched by `ignore_files` (see
+/// [`Exporter::add_ignore_file`][crate::Exporter::add_ignore_file]).
+pub fn vault_contents_with_ignore_files(
+    root: &Path,
+    opts: WalkOptions<'_>,
+    ignore_files: &[PathBuf],
+) -> Result<Vec<PathBuf>> {
+    let follow_symlinks = opts.follow_symlinks;
+    collect_files(
+        root,
+        opts.build_walker(root, ignore_files)?,
+        follow_symlinks,
+    )
+}
+
+/// `all_vault_files` returns all of the files in an Obsidian vault located at `path`, including
+/// those excluded from the export by ignore files, git's ignore rules or the `filter_fn` of the
+/// given [`WalkOptions`].
+///
+/// Hidden files are only included when [`WalkOptions::ignore_hidden`] is disabled.
+pub fn all_vault_files(root: &Path, opts: &WalkOptions<'_>) -> Result<Vec<PathBuf>> {
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .hidden(opts.ignore_hidden)
+        .follow_links(opts.follow_symlinks)
+        .sort_by_file_name(Ord::cmp)
+        .build();
+    collect_files(root, walker, opts.follow_symlinks)
+}
+
+/// `passthrough_files` returns all of the files in an Obsidian vault located at `root` which match
+/// one of the given `patterns`, following the gitignore syntax, regardless of ignore rules.
+///
+/// Hidden files are included, except for the contents of `.git` directories.
+pub fn passthrough_files(
+    root: &Path,
+    patterns: &[String],
+    follow_symlinks: bool,
+) -> Result<Vec<PathBuf>> {
+    let matcher = passthrough_matcher(root, patterns)?;
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .follow_links(follow_symlinks)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_name(Ord::cmp)
+        .build();
+    Ok(collect_files(root, walker, follow_symlinks)?
+        .into_iter()
+        .filter(|path| matcher.matched_path_or_any_parents(path, false).is_ignore())
+        .collect())
+}
+
+/// Build a matcher for the given passthrough `patterns`, which are relative to `root`.
+pub fn passthrough_matcher(root: &Path, patterns: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .context(PassthroughPatternSnafu { pattern })?;
+    }
+    builder.build().context(PassthroughPatternSnafu {
+        pattern: patterns.join(", "),
+    })
+}
+
+fn collect_files(root: &Path, walker: Walk, follow_symlinks: bool) -> Result<Vec<PathBuf>> {
     let mut contents = Vec::new();
-    let walker = opts.build_walker(root);
+    let mut seen = HashSet::new();
     for entry in walker {
-        let entry = entry.context(WalkDirSnafu { path: root })?;
+        let entry = entry.map_err(|err| match symlink_loop(&err) {
+            Some((path, ancestor)) => ExportError::SymlinkLoop { path, ancestor },
+            None => ExportError::WalkDirError {
+                path: root.to_path_buf(),
+                source: err,
+            },
+        })?;
         let path = entry.path();
         let metadata = entry.metadata().context(WalkDirSnafu { path })?;
 
         if metadata.is_dir() {
             continue;
         }
+        // With symlinks, the same file may be reachable through multiple paths. Only the first
+        // one is kept so the file isn't exported (and resolved by references) twice.
+        if follow_symlinks && !seen.insert(path.canonicalize().context(ReadSnafu { path })?) {
+            continue;
+        }
         contents.push(path.to_path_buf());
     }
     Ok(contents)
 }
+
+/// Matches paths against the [global ignore files][crate::Exporter::add_ignore_file] of a vault.
+///
+/// The walker doesn't allow ignore files to be rooted at the vault, so these are applied through
+/// a filter instead. To give the ignore files within the vault a higher precedence, paths which
+/// they match explicitly (either ignored or whitelisted) are left to the walker.
+struct GlobalIgnore {
+    root: PathBuf,
+    ignore_filename: String,
+    global: Gitignore,
+    /// The ignore file within each directory, or an empty matcher if there's none.
+    per_directory: Mutex<HashMap<PathBuf, Gitignore>>,
+}
+
+impl GlobalIgnore {
+    fn new(root: &Path, files: &[PathBuf], ignore_filename: &str) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(root);
+        for file in files {
+            if let Some(err) = builder.add(file) {
+                return Err(err).context(IgnoreFileSnafu { path: file });
+            }
+        }
+        let global = builder.build().context(IgnoreFileSnafu { path: root })?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            ignore_filename: ignore_filename.to_owned(),
+            global,
+            per_directory: Mutex::default(),
+        })
+    }
+
+    fn is_ignored(&self, entry: &DirEntry) -> bool {
+        let path = entry.path();
+        let is_dir = entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir());
+        let mut per_directory = self
+            .per_directory
+            .lock()
+            .expect("ignore cache mutex poisoned");
+        let matched_within_vault = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .any(|dir| {
+                // Errors within these files are reported by the walker itself.
+                let matcher = per_directory
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| Gitignore::new(dir.join(&self.ignore_filename)).0);
+                !matcher.matched(path, is_dir).is_none()
+            });
+        drop(per_directory);
+        !matched_within_vault && self.global.matched(path, is_dir).is_ignore()
+    }
+}
+
+/// Find the symlink loop (if any) which caused `err`, returning the offending path along with the
+/// ancestor directory it points to.
+fn symlink_loop(err: &ignore::Error) -> Option<(PathBuf, PathBuf)> {
+    match err {
+        ignore::Error::Loop { ancestor, child } => Some((child.clone(), ancestor.clone())),
+        ignore::Error::WithPath { err: inner, .. }
+        | ignore::Error::WithDepth { err: inner, .. }
+        | ignore::Error::WithLineNumber { err: inner, .. } => symlink_loop(inner),
+        ignore::Error::Partial(errs) => errs.iter().find_map(symlink_loop),
+        _ => None,
+    }
+}
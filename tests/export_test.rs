@@ -1,13 +1,48 @@
 #![allow(clippy::shadow_unrelated)]
 
-use std::fs::{create_dir, read_to_string, set_permissions, File, Permissions};
+use std::collections::HashMap;
+use std::fs::{create_dir, read_dir, read_to_string, set_permissions, File, Permissions};
 use std::io::prelude::*;
 #[cfg(not(target_os = "windows"))]
-use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
-
-use obsidian_export::{ExportError, Exporter, FrontmatterStrategy};
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, SystemTime};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use obsidian_export::pulldown_cmark_to_cmark::Options;
+use obsidian_export::{
+    slugify_filename,
+    vault_contents,
+    CmarkSerializer,
+    Context,
+    DataFormat,
+    DryRunAction,
+    DuplicateEmbedBehavior,
+    ExcludedEmbedBehavior,
+    ExportConfig,
+    ExportError,
+    Exporter,
+    FinalizeContext,
+    Frontmatter,
+    FrontmatterStrategy,
+    InternalLinkFormat,
+    LineEnding,
+    LinkResolutionMode,
+    ListMerge,
+    MarkdownEvents,
+    MarkdownSerializer,
+    PostprocessorResult,
+    ProgressEvent,
+    UnresolvedLink,
+    UnresolvedLinkStyle,
+    VaultIndex,
+    WalkOptions,
+    YamlOptions,
+};
 use pretty_assertions::assert_eq;
+use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+use rstest::rstest;
 use tempfile::TempDir;
 use walkdir::WalkDir;
 
@@ -102,6 +137,32 @@ fn test_frontmatter_always() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn test_frontmatter_yaml_options() {
+    let src_dir = TempDir::new().expect("failed to make tempdir");
+    let dest_dir = TempDir::new().expect("failed to make tempdir");
+    std::fs::write(
+        src_dir.path().join("Note.md"),
+        "---\ndescription: A long description which goes on and on, well past the eighty \
+         characters at which YAML emitters commonly wrap lines\nsummary: |\n  line one\n  line \
+         two\n---\n\nText\n",
+    )
+    .unwrap();
+
+    let mut options = YamlOptions::default();
+    options.no_wrap = false;
+    options.block_scalars = false;
+    let mut exporter = Exporter::new(src_dir.path().to_path_buf(), dest_dir.path().to_path_buf());
+    exporter.frontmatter_yaml_options(options);
+    exporter.run().expect("exporter returned error");
+
+    let expected = "---\ndescription: \"A long description which goes on and on, well past the \
+                    eighty\n  characters at which YAML emitters commonly wrap lines\"\nsummary: \
+                    \"line one\\nline two\\n\"\n---\n\nText\n";
+    let actual = read_to_string(dest_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+}
+
 #[test]
 fn test_exclude() {
     let tmp_dir = TempDir::new().expect("failed to make tempdir");
@@ -120,6 +181,216 @@ fn test_exclude() {
     );
 }
 
+/// Formats lists using `-` bullets and emphasis using `_`, rather than `*` for both.
+struct DashSerializer;
+
+impl MarkdownSerializer for DashSerializer {
+    fn serialize(&self, events: &MarkdownEvents<'_>) -> String {
+        CmarkSerializer::new(Options {
+            list_token: '-',
+            emphasis_token: '_',
+            ..Options::default()
+        })
+        .serialize(events)
+    }
+}
+
+#[test]
+fn test_custom_serializer() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/serializer/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.set_serializer(Box::new(DashSerializer));
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string("tests/testdata/expected/serializer/Note.md").unwrap(),
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+    );
+}
+
+#[test]
+fn test_passthrough_patterns_without_writing() {
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/passthrough/"),
+        PathBuf::from("/nonexistent"),
+    );
+    exporter
+        .add_passthrough_pattern("CNAME")
+        .add_passthrough_pattern("raw/*.md");
+
+    let files: HashMap<PathBuf, String> = exporter
+        .iter_exports()
+        .map(|file| {
+            let file = file.unwrap();
+            (file.path, String::from_utf8(file.content).unwrap())
+        })
+        .collect();
+    assert_eq!(
+        files.get(Path::new("CNAME")).unwrap(),
+        "notes.example.com\n"
+    );
+    assert_eq!(
+        files.get(Path::new("raw/Template.md")).unwrap(),
+        "Copied as-is: [[Note]]\n"
+    );
+
+    let mut output = Vec::new();
+    exporter
+        .to_writer(Path::new("raw/Template.md"), &mut output)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Copied as-is: [[Note]]\n"
+    );
+}
+
+#[test]
+fn test_passthrough_patterns() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/passthrough/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+    // CNAME is excluded through .export-ignore and .nojekyll is hidden.
+    assert!(!tmp_dir.path().join("CNAME").exists());
+    assert!(!tmp_dir.path().join(".nojekyll").exists());
+
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/passthrough/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter
+        .add_passthrough_pattern("CNAME")
+        .add_passthrough_pattern(".nojekyll")
+        .add_passthrough_pattern("raw/*.md");
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("CNAME")).unwrap(),
+        "notes.example.com\n"
+    );
+    assert!(tmp_dir.path().join(".nojekyll").exists());
+    assert!(!tmp_dir.path().join(".export-ignore").exists());
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("raw/Template.md")).unwrap(),
+        "Copied as-is: [[Note]]\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        "Links to [Template](raw/Template.md).\n"
+    );
+
+    exporter.add_passthrough_pattern("raw/[");
+    assert!(matches!(
+        exporter.run().unwrap_err(),
+        ExportError::PassthroughPatternError { .. }
+    ));
+}
+
+#[test]
+fn test_custom_ignore_filename() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/ignore-files/vault/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.ignore_filename(".exportignore".to_owned());
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("Note.md").exists());
+    assert!(!tmp_dir.path().join("drafts/Draft.md").exists());
+
+    // With the default filename, `.exportignore` files have no effect.
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/ignore-files/vault/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+    assert!(tmp_dir.path().join("drafts/Draft.md").exists());
+}
+
+#[test]
+fn test_global_ignore_file() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/ignore-files/vault/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.ignore_filename(".exportignore".to_owned());
+    exporter.add_ignore_file(PathBuf::from(
+        "tests/testdata/input/ignore-files/shared-ignore",
+    ));
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("Note.md").exists());
+    assert!(!tmp_dir.path().join("Scratch.md").exists());
+    assert!(!tmp_dir.path().join("drafts/Draft.md").exists());
+    assert!(!tmp_dir.path().join("private/Secret.md").exists());
+    // Ignore files within the vault take precedence over the global ignore file.
+    assert!(tmp_dir.path().join("private/Keep.md").exists());
+}
+
+#[test]
+fn test_missing_global_ignore_file() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/ignore-files/vault/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_ignore_file(PathBuf::from("tests/testdata/input/ignore-files/missing"));
+    let err = exporter.run().expect_err("exporter should have failed");
+    assert!(
+        matches!(err, ExportError::IgnoreFileError { .. }),
+        "unexpected error: {:?}",
+        err
+    );
+}
+
+#[test]
+fn test_resolve_frontmatter_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-links/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.resolve_frontmatter_links(true);
+    exporter.run().expect("exporter returned error");
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected = read_to_string("tests/testdata/expected/frontmatter-links/Note.md").unwrap();
+    assert_eq!(expected, actual);
+    assert_eq!(
+        vec!["Missing"],
+        exporter
+            .unresolved_links()
+            .iter()
+            .map(|link| link.reference.as_str())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_frontmatter_links_are_kept_by_default() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-links/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert!(actual.contains("related: '[[Other Note]]'"), "{}", actual);
+}
+
 #[test]
 fn test_single_file_to_dir() {
     let tmp_dir = TempDir::new().expect("failed to make tempdir");
@@ -343,6 +614,54 @@ fn test_infinite_recursion() {
     }
 }
 
+#[test]
+fn test_embedded_frontmatter_is_stripped() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/embed-frontmatter/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.frontmatter_strategy(FrontmatterStrategy::Never);
+    exporter.run().expect("exporter returned error");
+
+    let expected = read_to_string("tests/testdata/expected/embed-frontmatter/Note.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+    assert!(!actual.contains("---"));
+    assert!(!actual.contains("title:"));
+}
+
+#[test]
+fn test_embed_recursion_limit() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let input = PathBuf::from("tests/testdata/input/embed-depth/");
+    let mut exporter = Exporter::new(input.clone(), tmp_dir.path().to_path_buf());
+    exporter.start_at(input.join("Outline.md"));
+
+    exporter.embed_recursion_limit(2);
+    match exporter.run().unwrap_err() {
+        ExportError::FileExportError { source, .. } => match *source {
+            ExportError::RecursionLimitExceeded { file_tree } => assert_eq!(
+                file_tree,
+                vec![
+                    input.join("Outline.md"),
+                    input.join("Section.md"),
+                    input.join("Snippet.md"),
+                ]
+            ),
+            _ => panic!("Wrong error variant for source, got: {:?}", source),
+        },
+        err => panic!("Wrong error variant: {:?}", err),
+    }
+
+    exporter.embed_recursion_limit(3);
+    exporter.run().expect("exporter returned error");
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Outline.md")).unwrap(),
+        "# Outline\n\n## Section\n\nA snippet.\n"
+    );
+}
+
 #[test]
 fn test_no_recursive_embeds() {
     let tmp_dir = TempDir::new().expect("failed to make tempdir");
@@ -360,6 +679,105 @@ fn test_no_recursive_embeds() {
     );
 }
 
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_async() {
+    let sync_dir = TempDir::new().expect("failed to make tempdir");
+    let async_dir = TempDir::new().expect("failed to make tempdir");
+    let input = PathBuf::from("tests/testdata/input/main-samples/");
+
+    Exporter::new(input.clone(), sync_dir.path().to_path_buf())
+        .run()
+        .expect("exporter returned error");
+    Exporter::new(input, async_dir.path().to_path_buf())
+        .run_async()
+        .await
+        .expect("exporter returned error");
+
+    let exported_files = |dir: &Path| -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = WalkDir::new(dir)
+            .into_iter()
+            .map(|entry| entry.unwrap().into_path())
+            .filter(|path| path.is_file())
+            .map(|path| path.strip_prefix(dir).unwrap().to_path_buf())
+            .collect();
+        files.sort();
+        files
+    };
+    let files = exported_files(sync_dir.path());
+    assert!(!files.is_empty());
+    assert_eq!(files, exported_files(async_dir.path()));
+    for file in files {
+        assert_eq!(
+            std::fs::read(sync_dir.path().join(&file)).unwrap(),
+            std::fs::read(async_dir.path().join(&file)).unwrap(),
+            "{} differs",
+            file.display()
+        );
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_run_async_yields_between_notes() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let processed = Mutex::new(0_usize);
+    let count_notes = |_context: &mut Context, _events: &mut MarkdownEvents<'_>| {
+        *processed.lock().unwrap() += 1;
+        PostprocessorResult::Continue
+    };
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/main-samples/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_postprocessor(&count_notes);
+
+    // Dropping the export when it first yields cancels it after the first note.
+    let mut export = Box::pin(exporter.run_async());
+    tokio::select! {
+        biased;
+        result = &mut export => panic!("export finished without yielding: {:?}", result),
+        () = std::future::ready(()) => {}
+    }
+    drop(export);
+    assert_eq!(*processed.lock().unwrap(), 1);
+
+    exporter.run_async().await.expect("exporter returned error");
+    assert!(*processed.lock().unwrap() > 2);
+}
+
+#[rstest]
+#[case(LineEnding::Lf, "\n", "\n")]
+#[case(LineEnding::Crlf, "\r\n", "\r\n")]
+#[case(LineEnding::PreserveSource, "\n", "\r\n")]
+fn test_line_ending(
+    #[case] line_ending: LineEnding,
+    #[case] from_lf: &str,
+    #[case] from_crlf: &str,
+) {
+    let src_dir = TempDir::new().expect("failed to make tempdir");
+    let dest_dir = TempDir::new().expect("failed to make tempdir");
+    let note = "---\ntitle: Note\n---\n\n# Heading\n\nFirst line\nsecond line\n\n* item\n";
+    std::fs::write(src_dir.path().join("Unix.md"), note).unwrap();
+    std::fs::write(
+        src_dir.path().join("Windows.md"),
+        note.replace('\n', "\r\n"),
+    )
+    .unwrap();
+
+    let mut exporter = Exporter::new(src_dir.path().to_path_buf(), dest_dir.path().to_path_buf());
+    exporter.line_ending(line_ending);
+    exporter.run().expect("exporter returned error");
+
+    for (filename, newline) in [("Unix.md", from_lf), ("Windows.md", from_crlf)] {
+        assert_eq!(
+            read_to_string(dest_dir.path().join(filename)).unwrap(),
+            note.replace('\n', newline),
+            "{filename} does not have the expected line endings"
+        );
+    }
+}
+
 #[test]
 fn test_preserve_mtime() {
     let tmp_dir = TempDir::new().expect("failed to make tempdir");
@@ -398,6 +816,166 @@ fn test_no_preserve_mtime() {
     assert_ne!(src_meta.modified().unwrap(), dest_meta.modified().unwrap());
 }
 
+#[cfg(unix)]
+#[test]
+fn test_preserve_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let vault = TempDir::new().expect("failed to make tempdir");
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let script = vault.path().join("build.sh");
+    std::fs::write(&script, "#!/bin/sh\necho hello\n").unwrap();
+    set_permissions(&script, Permissions::from_mode(0o755)).unwrap();
+
+    let mut exporter = Exporter::new(vault.path().to_path_buf(), tmp_dir.path().to_path_buf());
+    exporter.preserve_permissions(true);
+    exporter.run().expect("exporter returned error");
+
+    let dest_meta = std::fs::metadata(tmp_dir.path().join("build.sh")).unwrap();
+    assert_eq!(dest_meta.permissions().mode() & 0o777, 0o755);
+}
+
+#[test]
+fn test_git_dates() {
+    let repo = TempDir::new().expect("failed to make tempdir");
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let git = |args: &[&str], date: &str| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(repo.path())
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .status()
+            .expect("failed to run git");
+        assert!(status.success());
+    };
+    let vault = repo.path().join("vault");
+    create_dir(&vault).unwrap();
+    std::fs::write(vault.join("Note.md"), "First version.\n").unwrap();
+    std::fs::write(
+        vault.join("Dated.md"),
+        "---\ncreated: 2019-05-01\n---\n\nKeeps its own date.\n",
+    )
+    .unwrap();
+    git(&["init", "--quiet"], "2020-01-01T00:00:00Z");
+    git(&["add", "."], "2020-01-01T00:00:00Z");
+    git(
+        &["commit", "--quiet", "-m", "Add notes"],
+        "2020-01-01T10:00:00+00:00",
+    );
+    std::fs::write(vault.join("Note.md"), "Second version.\n").unwrap();
+    git(
+        &["commit", "--quiet", "-am", "Update note"],
+        "2021-06-15T12:30:00+02:00",
+    );
+    std::fs::write(vault.join("Uncommitted.md"), "Not committed yet.\n").unwrap();
+
+    let mut exporter = Exporter::new(vault, tmp_dir.path().to_path_buf());
+    exporter.git_dates(true);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        "---\ncreated: 2020-01-01T10:00:00+00:00\nmodified: 2021-06-15T12:30:00+02:00\n---\n\nSecond version.\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Dated.md")).unwrap(),
+        "---\ncreated: 2019-05-01\nmodified: 2020-01-01T10:00:00+00:00\n---\n\nKeeps its own date.\n"
+    );
+    // Files without any commits fall back to the dates from the filesystem.
+    let uncommitted = read_to_string(tmp_dir.path().join("Uncommitted.md")).unwrap();
+    assert!(uncommitted.contains("\ncreated: "));
+    assert!(uncommitted.contains("\nmodified: "));
+}
+
+#[test]
+fn test_git_dates_outside_repository() {
+    let vault = TempDir::new().expect("failed to make tempdir");
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    std::fs::write(vault.path().join("Note.md"), "Not in git.\n").unwrap();
+
+    let mut exporter = Exporter::new(vault.path().to_path_buf(), tmp_dir.path().to_path_buf());
+    exporter.git_dates(true);
+    exporter.run().expect("exporter returned error");
+
+    let note = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert!(note.starts_with("---\ncreated: "));
+    assert!(note.contains("\nmodified: "));
+    assert!(note.ends_with("---\n\nNot in git.\n"));
+}
+
+#[test]
+fn test_modified_since() {
+    let vault = TempDir::new().expect("failed to make tempdir");
+    let cutoff = SystemTime::now() - Duration::from_secs(3600);
+    let long_ago = cutoff - Duration::from_secs(3600);
+    for (name, content, modified) in [
+        ("Changed.md", "Changed content.\n", SystemTime::now()),
+        ("Unchanged.md", "Unchanged content.\n", long_ago),
+        ("Embeds changed.md", "![[Changed]]\n", long_ago),
+        ("Embeds embedder.md", "![[Embeds changed]]\n", long_ago),
+        ("Links changed.md", "[[Changed]]\n", long_ago),
+        ("new.png", "", SystemTime::now()),
+        ("old.png", "", long_ago),
+    ] {
+        let path = vault.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(vault.path().to_path_buf(), tmp_dir.path().to_path_buf());
+    exporter.modified_since(cutoff);
+    exporter.run().expect("exporter returned error");
+
+    let mut exported: Vec<String> = read_dir(tmp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    exported.sort();
+    assert_eq!(
+        vec![
+            "Changed.md",
+            "Embeds changed.md",
+            "Embeds embedder.md",
+            "new.png"
+        ],
+        exported
+    );
+    assert_eq!(
+        "Changed content.\n",
+        read_to_string(tmp_dir.path().join("Embeds embedder.md")).unwrap()
+    );
+}
+
+#[test]
+fn test_unicode_normalization() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let source = Path::new("tests/testdata/input/unicode-normalization/");
+    // Filenames within the vault use NFD, as commonly found on macOS, while links use NFC.
+    assert!(source.join("Cafe\u{301}.md").exists());
+
+    let mut exporter = Exporter::new(source.to_path_buf(), tmp_dir.path().to_path_buf());
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string("tests/testdata/expected/unicode-normalization/Links.md").unwrap(),
+        read_to_string(tmp_dir.path().join("Links.md")).unwrap(),
+    );
+    assert!(tmp_dir.path().join("Caf\u{e9}.md").exists());
+    assert!(tmp_dir
+        .path()
+        .join("R\u{e9}sum\u{e9}/Exp\u{e9}rience.md")
+        .exists());
+    assert_eq!(exporter.unresolved_links(), vec![]);
+}
+
 #[test]
 fn test_non_ascii_filenames() {
     let tmp_dir = TempDir::new().expect("failed to make tempdir");
@@ -438,24 +1016,1681 @@ fn test_non_ascii_filenames() {
 }
 
 #[test]
-fn test_same_filename_different_directories() {
+fn test_links_to_non_markdown_files() {
     let tmp_dir = TempDir::new().expect("failed to make tempdir");
     Exporter::new(
-        PathBuf::from("tests/testdata/input/same-filename-different-directories"),
+        PathBuf::from("tests/testdata/input/file-links/"),
         tmp_dir.path().to_path_buf(),
     )
     .run()
-    .unwrap();
+    .expect("exporter returned error");
 
-    let expected = if cfg!(windows) {
-        read_to_string("tests/testdata/expected/same-filename-different-directories/Note.md")
-            .unwrap()
-            .replace('/', "\\")
-    } else {
-        read_to_string("tests/testdata/expected/same-filename-different-directories/Note.md")
-            .unwrap()
-    };
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        "See [Report.pdf](docs/Report.pdf) and [the report](docs/Report.pdf).\n\nEmbeds of files which are neither notes nor images become links as well:\n\n[Report.pdf](docs/Report.pdf)\n"
+    );
+    assert!(tmp_dir.path().join("docs/Report.pdf").exists());
+}
 
-    let actual = read_to_string(tmp_dir.path().join(PathBuf::from("Note.md"))).unwrap();
-    assert_eq!(expected, actual);
+#[test]
+fn test_resolve_relative_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/relative-links/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.filename_slugifier(&slugify_filename);
+    exporter.resolve_relative_links(true);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string("tests/testdata/expected/relative-links/Source.md").unwrap(),
+        read_to_string(tmp_dir.path().join("notes/source.md")).unwrap(),
+    );
+    let unresolved: Vec<String> = exporter
+        .unresolved_links()
+        .into_iter()
+        .map(|link| link.reference)
+        .collect();
+    assert_eq!(unresolved, vec!["Missing.md"]);
+}
+
+#[test]
+fn test_output_extension() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/output-extension/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.output_extension(".mdx".to_owned());
+    exporter.run().expect("exporter returned error");
+
+    let mut files: Vec<String> = WalkDir::new(tmp_dir.path())
+        .into_iter()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| !entry.file_type().is_dir())
+        .map(|entry| {
+            entry
+                .path()
+                .strip_prefix(tmp_dir.path())
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        vec!["Note.mdx", "Source.mdx", "image.png", "sub/Other.mdx"]
+    );
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.mdx")).unwrap(),
+        "Links to [Other](sub/Other.mdx), [Other > Some heading](sub/Other.mdx#some-heading), [Source](Source.mdx) and [image.png](image.png).\n\n![image.png](image.png)\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Source.mdx")).unwrap(),
+        "A note with a .markdown extension, linking back to [Note](Note.mdx).\n"
+    );
+}
+
+#[rstest]
+#[case(
+    LinkResolutionMode::ShortestPath,
+    "[Note](Note.md), [b/Note](../b/Note.md), *../b/Note* and [deep/Other](../b/deep/Other.md)\n"
+)]
+#[case(
+    LinkResolutionMode::Relative,
+    "[Note](Note.md), *b/Note*, [../b/Note](../b/Note.md) and *deep/Other*\n"
+)]
+#[case(
+    LinkResolutionMode::Absolute,
+    "[Note](../Note.md), [b/Note](../b/Note.md), *../b/Note* and *deep/Other*\n"
+)]
+fn test_link_resolution_mode(#[case] mode: LinkResolutionMode, #[case] expected: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/link-resolution/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.link_resolution_mode(mode);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("a/Source.md")).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_links_within_embeds_resolve_relative_to_embedded_note() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/nested-embed-links/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.link_resolution_mode(LinkResolutionMode::Relative);
+    exporter.run().expect("exporter returned error");
+
+    // Both links only resolve from the location of the embedded note, but are relative to the
+    // note embedding it once exported.
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        "Embedding a note from a subfolder:\n\nLinks to [Sibling](sub/Sibling.md) and [../Other](Other.md).\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("sub/Embedded.md")).unwrap(),
+        "Links to [Sibling](Sibling.md) and [../Other](../Other.md).\n"
+    );
+    assert_eq!(exporter.unresolved_links(), vec![]);
+}
+
+#[test]
+fn test_daily_note_folder() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/daily-notes/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.daily_note_folder(PathBuf::from("Journal"));
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string("tests/testdata/expected/daily-notes/Note.md").unwrap(),
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+    );
+}
+
+#[test]
+fn test_permalink_frontmatter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/permalinks/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.use_permalink_frontmatter(Some("permalink".to_owned()));
+    exporter.run().expect("exporter returned error");
+
+    assert!(!tmp_dir.path().join("posts/My Post.md").exists());
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Home.md")).unwrap(),
+        "Read [My Post](blog/my-post/index.md) and [the details](blog/my-post/index.md#details), or see [About](about.md).\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("blog/my-post/index.md")).unwrap(),
+        "---\npermalink: /blog/my-post/\n---\n\nBack to [Home](../../Home.md).\n\n## Details\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("about.md")).unwrap(),
+        "---\npermalink: /about\n---\n\nSee [My Post](blog/my-post/index.md).\n"
+    );
+}
+
+#[test]
+fn test_destination_mapper() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/destination-mapper/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    // Export dated notes to a `year/month/` folder, and everything else to the root.
+    exporter.destination_mapper(&|context| {
+        let filename = PathBuf::from(context.destination.file_name().unwrap());
+        let date = context
+            .frontmatter
+            .get("date")
+            .and_then(serde_yaml::Value::as_str);
+        match date
+            .map(|date| date.split('-').collect::<Vec<_>>())
+            .as_deref()
+        {
+            Some([year, month, _day]) => Path::new(year).join(month).join(filename),
+            _ => filename,
+        }
+    });
+    exporter.run().expect("exporter returned error");
+
+    assert!(!tmp_dir.path().join("posts").exists());
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Home.md")).unwrap(),
+        "Latest posts: [First Post](2023/01/First%20Post.md) and [Second Post > Update](2024/03/Second%20Post.md#update).\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("2023/01/First Post.md")).unwrap(),
+        "---\ndate: 2023-01-05\n---\n\nBack to [Home](../../Home.md), or read [Second Post](../../2024/03/Second%20Post.md).\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("2024/03/Second Post.md")).unwrap(),
+        "---\ndate: 2024-03-10\n---\n\nFollows [First Post](../../2023/01/First%20Post.md).\n\n## Update\n"
+    );
+}
+
+#[test]
+fn test_same_note_heading_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/same-note-links/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.run().expect("exporter returned error");
+
+    let expected = read_to_string("tests/testdata/expected/same-note-links/Note.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+    assert_eq!(exporter.unresolved_links(), vec![]);
+}
+
+#[test]
+fn test_frontmatter_defaults() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-defaults/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.frontmatter_defaults_file("_defaults.yaml".to_owned());
+    exporter.frontmatter_list_merge("tags".to_owned(), ListMerge::Append);
+    exporter.run().expect("exporter returned error");
+
+    for file in ["About.md", "posts/First.md", "posts/Second.md"] {
+        let expected =
+            read_to_string(Path::new("tests/testdata/expected/frontmatter-defaults/").join(file))
+                .unwrap();
+        let actual = read_to_string(tmp_dir.path().join(file)).unwrap();
+        assert_eq!(expected, actual, "{}", file);
+    }
+    assert!(!tmp_dir.path().join("_defaults.yaml").exists());
+    assert!(!tmp_dir.path().join("posts/_defaults.yaml").exists());
+}
+
+#[test]
+fn test_frontmatter_only_as_data() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-data/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.frontmatter_only_as_data(Some(DataFormat::Json));
+    exporter.run().expect("exporter returned error");
+
+    for file in ["Jane Doe.json", "Post.md"] {
+        let expected =
+            read_to_string(Path::new("tests/testdata/expected/frontmatter-data/").join(file))
+                .unwrap();
+        let actual = read_to_string(tmp_dir.path().join(file)).unwrap();
+        assert_eq!(expected, actual, "{}", file);
+    }
+    assert!(!tmp_dir.path().join("Jane Doe.md").exists());
+    // Notes without frontmatter are exported as usual, even when they're empty.
+    assert!(tmp_dir.path().join("Empty.md").exists());
+}
+
+#[rstest]
+#[case(InternalLinkFormat::Markdown, "Markdown.md")]
+#[case(InternalLinkFormat::Wikilink, "Wikilink.md")]
+#[case(InternalLinkFormat::Docusaurus, "Docusaurus.md")]
+fn test_wikilink_aliases(#[case] format: InternalLinkFormat, #[case] expected_file: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/wikilink-aliases/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.internal_link_format(format);
+    exporter.run().expect("exporter returned error");
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected =
+        read_to_string(Path::new("tests/testdata/expected/wikilink-aliases/").join(expected_file))
+            .unwrap();
+    assert_eq!(expected, actual);
+    assert_eq!(exporter.unresolved_links(), vec![]);
+}
+
+#[test]
+fn test_docusaurus_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/docusaurus/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.internal_link_format(InternalLinkFormat::Docusaurus);
+    exporter.run().expect("exporter returned error");
+
+    let expected = read_to_string("tests/testdata/expected/docusaurus/Note.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_docusaurus_links_with_base_path() {
+    let vault = PathBuf::from("tests/testdata/input/docusaurus/");
+    let vault_index = VaultIndex::new(&vault, WalkOptions::default()).unwrap();
+    let mut exporter = Exporter::new(vault, PathBuf::from("/nonexistent"));
+    exporter.internal_link_format(InternalLinkFormat::Docusaurus);
+    exporter.docs_base_path("/handbook/".to_owned());
+
+    assert_eq!(
+        exporter
+            .export_str("[[Getting Started#Install the CLI!]]", &vault_index)
+            .unwrap(),
+        "[Getting Started > Install the CLI!](/handbook/guides/getting-started#install-the-cli)\n"
+    );
+}
+
+#[rstest]
+#[case(LinkResolutionMode::ShortestPath)]
+#[case(LinkResolutionMode::Relative)]
+#[case(LinkResolutionMode::Absolute)]
+fn test_backslash_separated_links(#[case] mode: LinkResolutionMode) {
+    let vault = PathBuf::from("tests/testdata/input/link-resolution/");
+    let vault_index = VaultIndex::new(&vault, WalkOptions::default()).unwrap();
+    let mut exporter = Exporter::new(vault, PathBuf::from("/nonexistent"));
+    exporter.link_resolution_mode(mode);
+
+    assert_eq!(
+        exporter
+            .export_str("[[b\\deep\\Other]]", &vault_index)
+            .unwrap(),
+        "[b\\deep\\Other](b/deep/Other.md)\n"
+    );
+}
+
+#[rstest]
+#[case(LinkResolutionMode::ShortestPath)]
+#[case(LinkResolutionMode::Relative)]
+fn test_percent_encoded_links(#[case] mode: LinkResolutionMode) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/encoded-links/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.link_resolution_mode(mode);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Source.md")).unwrap(),
+        "Links to [My Note](My%20Note.md), [My%20Note](My%20Note.md) and [the other note](sub%20folder/Other%20Note.md).\n"
+    );
+}
+
+#[test]
+fn test_internal_link_format_wikilink() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/keep-wikilinks/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.internal_link_format(InternalLinkFormat::Wikilink);
+    exporter.assets_dir(Some(PathBuf::from("assets")));
+    captured_warnings();
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        read_to_string("tests/testdata/input/keep-wikilinks/Note.md").unwrap()
+    );
+    assert!(tmp_dir.path().join("assets/image.png").exists());
+    let warnings = captured_warnings();
+    assert!(
+        warnings.iter().any(|warning| {
+            warning.contains("Reference: 'Missing'") && warning.contains("keep-wikilinks/Note.md")
+        }),
+        "no warning about the unresolved link in: {:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_same_filename_different_directories() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/same-filename-different-directories"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .unwrap();
+
+    let expected = if cfg!(windows) {
+        read_to_string("tests/testdata/expected/same-filename-different-directories/Note.md")
+            .unwrap()
+            .replace('/', "\\")
+    } else {
+        read_to_string("tests/testdata/expected/same-filename-different-directories/Note.md")
+            .unwrap()
+    };
+
+    let actual = read_to_string(tmp_dir.path().join(PathBuf::from("Note.md"))).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_aliases() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/aliases/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+
+    let expected = read_to_string("tests/testdata/expected/aliases/Linker.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Linker.md")).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_export_str() {
+    let vault = PathBuf::from("tests/testdata/input/aliases/");
+    let vault_index = VaultIndex::new(&vault, WalkOptions::default()).unwrap();
+    let exporter = Exporter::new(vault, PathBuf::from("/nonexistent"));
+
+    let source = "---\ntitle: In memory\n---\n\nSee [[Collision]] and [[Nickname|a nickname]].\n";
+    assert_eq!(
+        exporter.export_str(source, &vault_index).unwrap(),
+        "---\ntitle: In memory\n---\n\nSee [Collision](Collision.md) and [a nickname](people/Jonathan%20Smith.md).\n"
+    );
+}
+
+#[test]
+fn test_build_index() {
+    let vault = PathBuf::from("tests/testdata/input/aliases/");
+    let exporter = Exporter::new(vault.clone(), PathBuf::from("/nonexistent"));
+    let vault_index = exporter.build_index().unwrap();
+
+    assert_eq!(
+        vault_index.lookup("Other"),
+        Some(&vault.join("Other.md")),
+        "wikilinks should resolve to the note they name"
+    );
+    assert_eq!(
+        vault_index.lookup("Nickname"),
+        Some(&vault.join("people/Jonathan Smith.md")),
+        "aliases should resolve to the note declaring them"
+    );
+    assert_eq!(vault_index.lookup("Missing"), None);
+
+    let mut notes: Vec<&Path> = vault_index.all_notes().collect();
+    notes.sort();
+    assert_eq!(
+        notes,
+        vec![
+            vault.join("Collision.md"),
+            vault.join("Linker.md"),
+            vault.join("Other.md"),
+            vault.join("people/Jonathan Smith.md"),
+        ]
+    );
+}
+
+#[test]
+fn test_to_writer() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let vault = PathBuf::from("tests/testdata/input/aliases/");
+    let mut exporter = Exporter::new(vault, tmp_dir.path().to_path_buf());
+    exporter.run().unwrap();
+
+    for note in ["Linker.md", "people/Jonathan Smith.md"] {
+        let mut output = Vec::new();
+        exporter.to_writer(Path::new(note), &mut output).unwrap();
+        assert!(!output.is_empty());
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            read_to_string(tmp_dir.path().join(note)).unwrap(),
+            "{note} differs from the file export"
+        );
+    }
+}
+
+#[test]
+fn test_to_writer_missing_note() {
+    let exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/aliases/"),
+        PathBuf::from("/nonexistent"),
+    );
+    let mut output = Vec::new();
+    match exporter.to_writer(Path::new("Missing.md"), &mut output) {
+        Err(ExportError::PathDoesNotExist { .. }) => {}
+        other => panic!("expected PathDoesNotExist, got {:?}", other),
+    }
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_code_block_info_strings_are_preserved() {
+    let vault = PathBuf::from("tests/testdata/input/aliases/");
+    let vault_index = VaultIndex::new(&vault, WalkOptions::default()).unwrap();
+    let exporter = Exporter::new(vault, PathBuf::from("/nonexistent"));
+
+    for info in [
+        "rust,ignore",
+        "rust,should_panic,edition2021",
+        r#"python {linenos=true, hl_lines=[2]} title="Example script""#,
+    ] {
+        let source = format!("```{info}\nprint('hello')\n```\n");
+        let exported = exporter.export_str(&source, &vault_index).unwrap();
+        assert!(
+            exported.contains(&format!("````{info}\n")),
+            "info string `{}` was not preserved: {:?}",
+            info,
+            exported
+        );
+    }
+}
+
+#[test]
+fn test_export_str_applies_postprocessors() {
+    let vault = PathBuf::from("tests/testdata/input/aliases/");
+    let vault_index = VaultIndex::new(&vault, WalkOptions::default()).unwrap();
+    let mut exporter = Exporter::new(vault, PathBuf::from("/nonexistent"));
+    exporter.frontmatter_strategy(FrontmatterStrategy::Never);
+    let skip_drafts = |ctx: &mut Context, _events: &mut MarkdownEvents<'_>| {
+        if ctx.frontmatter.contains_key("draft") {
+            PostprocessorResult::StopAndSkipNote
+        } else {
+            PostprocessorResult::Continue
+        }
+    };
+    exporter.add_postprocessor(&skip_drafts);
+
+    assert_eq!(
+        exporter
+            .export_str("---\ntitle: Note\n---\n\n[[Other]]", &vault_index)
+            .unwrap(),
+        "[Other](Other.md)\n"
+    );
+    assert_eq!(
+        exporter
+            .export_str("---\ndraft: true\n---\n\n[[Other]]", &vault_index)
+            .unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn test_export_config_shared_between_exporters() {
+    let exported_notes = Mutex::new(Vec::new());
+    let record_notes = |ctx: &mut Context, _events: &mut MarkdownEvents<'_>| {
+        let filename = ctx.destination.file_name().unwrap().to_owned();
+        exported_notes.lock().unwrap().push(filename);
+        PostprocessorResult::Continue
+    };
+    let mut config = ExportConfig::default();
+    config
+        .frontmatter_strategy(FrontmatterStrategy::Never)
+        .output_extension("markdown".to_owned())
+        .add_postprocessor(&record_notes);
+
+    let tags_dir = TempDir::new().expect("failed to make tempdir");
+    let mdx_dir = TempDir::new().expect("failed to make tempdir");
+    config
+        .build(
+            PathBuf::from("tests/testdata/input/frontmatter-tags/"),
+            tags_dir.path().to_path_buf(),
+        )
+        .run()
+        .expect("exporter returned error");
+    config
+        .build(
+            PathBuf::from("tests/testdata/input/mdx-wrapper/"),
+            mdx_dir.path().to_path_buf(),
+        )
+        .run()
+        .expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tags_dir.path().join("Untagged.markdown")).unwrap(),
+        "Some text.\n"
+    );
+    assert_eq!(
+        read_to_string(mdx_dir.path().join("Other.markdown")).unwrap(),
+        "No frontmatter here.\n"
+    );
+    let mut exported_notes = exported_notes.into_inner().unwrap();
+    exported_notes.sort();
+    assert_eq!(
+        exported_notes,
+        [
+            "Note.markdown",
+            "Note.markdown",
+            "Other.markdown",
+            "Untagged.markdown"
+        ]
+    );
+}
+
+#[test]
+fn test_iter_exports() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/main-samples/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let exported: HashMap<PathBuf, Vec<u8>> = exporter
+        .iter_exports()
+        .map(|file| file.map(|file| (file.path, file.content)))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read_dir(tmp_dir.path()).unwrap().count(), 0);
+
+    exporter.run().unwrap();
+    assert_eq!(
+        exported.len(),
+        WalkDir::new(tmp_dir.path())
+            .into_iter()
+            .filter(|entry| !entry.as_ref().unwrap().file_type().is_dir())
+            .count()
+    );
+    let note = PathBuf::from("obsidian-wikilinks.md");
+    assert_eq!(
+        String::from_utf8(exported.get(&note).unwrap().clone()).unwrap(),
+        read_to_string(tmp_dir.path().join(note)).unwrap(),
+    );
+}
+
+#[test]
+fn test_write_sitemap() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/sitemap/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let published = |frontmatter: &Frontmatter| {
+        frontmatter.get("publish") == Some(&serde_yaml::Value::Bool(true))
+    };
+    exporter.add_frontmatter_filter(&published);
+    exporter.filename_slugifier(&slugify_filename);
+    exporter.write_sitemap(Some("https://example.com/notes/".to_owned()));
+    exporter.run().expect("exporter returned error");
+
+    let sitemap = read_to_string(tmp_dir.path().join("sitemap.xml")).unwrap();
+    assert_eq!(
+        read_to_string("tests/testdata/expected/sitemap/sitemap.xml").unwrap(),
+        sitemap,
+    );
+    assert!(!sitemap.contains("draft"));
+}
+
+#[test]
+fn test_frontmatter_filter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-filter/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let published = |frontmatter: &Frontmatter| {
+        frontmatter.get("publish") == Some(&serde_yaml::Value::Bool(true))
+    };
+    exporter.add_frontmatter_filter(&published);
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("Published.md").exists());
+    assert!(!tmp_dir.path().join("Draft.md").exists());
+
+    // Draft.md embeds itself, so exporting it fails once its embeds are expanded.
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-filter/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .unwrap_err();
+}
+
+#[test]
+fn test_respect_export_frontmatter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/export-frontmatter/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.respect_export_frontmatter(true);
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("Note.md").exists());
+    assert!(!tmp_dir.path().join("Private.md").exists());
+    assert!(!tmp_dir.path().join("Draft.md").exists());
+    // Attachments which are also referenced by an exported note, or not referenced at all, are
+    // still copied.
+    assert!(tmp_dir.path().join("shared.png").exists());
+    assert!(tmp_dir.path().join("unused.png").exists());
+}
+
+#[test]
+fn test_respect_export_frontmatter_skips_attachments_of_skipped_notes() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/export-frontmatter/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.respect_export_frontmatter(true);
+    exporter.run().expect("exporter returned error");
+
+    assert!(!tmp_dir.path().join("private.png").exists());
+    assert!(!tmp_dir.path().join("diagram.png").exists());
+
+    // Without the option, all notes and attachments are exported.
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/export-frontmatter/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+    assert!(tmp_dir.path().join("Private.md").exists());
+    assert!(tmp_dir.path().join("private.png").exists());
+}
+
+#[rstest]
+#[case(None, "images/image.png")]
+#[case(Some(PathBuf::from("static")), "static/image.png")]
+fn test_attachments_of_embedded_notes_are_exported(
+    #[case] assets_dir: Option<PathBuf>,
+    #[case] attachment: &str,
+) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/transitive-attachments/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    // The embedded note itself isn't exported, but its content (including the image) is inlined
+    // into the root note.
+    exporter.respect_export_frontmatter(true);
+    exporter.excluded_embed_behavior(ExcludedEmbedBehavior::Inline);
+    exporter.assets_dir(assets_dir);
+    exporter.run().expect("exporter returned error");
+
+    assert!(!tmp_dir.path().join("notes/Sub note.md").exists());
+    assert!(tmp_dir.path().join(attachment).exists());
+    assert_eq!(
+        format!("Root note.\n\nSub note.\n\n![image.png]({})\n", attachment),
+        read_to_string(tmp_dir.path().join("Root.md")).unwrap(),
+    );
+}
+
+#[test]
+fn test_attachments_of_skipped_embedded_notes_are_skipped() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/transitive-attachments/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.respect_export_frontmatter(true);
+    exporter.excluded_embed_behavior(ExcludedEmbedBehavior::Omit);
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("Root.md").exists());
+    assert!(!tmp_dir.path().join("images/image.png").exists());
+}
+
+#[test]
+fn test_frontmatter_query() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-query/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.frontmatter_query(&|frontmatter| {
+        frontmatter.get("category") == Some(&serde_yaml::Value::from("blog"))
+            && frontmatter.get("draft") != Some(&serde_yaml::Value::Bool(true))
+    });
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("Post.md").exists());
+    assert!(tmp_dir.path().join("Second post.md").exists());
+    assert!(!tmp_dir.path().join("Draft.md").exists());
+    assert!(!tmp_dir.path().join("Journal.md").exists());
+    assert!(!tmp_dir.path().join("Scratch.md").exists());
+
+    // Only attachments used by matching notes are copied.
+    assert!(tmp_dir.path().join("header.png").exists());
+    assert!(tmp_dir.path().join("shared.png").exists());
+    assert!(!tmp_dir.path().join("draft.png").exists());
+    assert!(!tmp_dir.path().join("journal.png").exists());
+}
+
+#[test]
+fn test_frontmatter_query_replaces_previous_query() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-query/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.frontmatter_query(&|_| false);
+    exporter.frontmatter_query(&|frontmatter| {
+        frontmatter.get("category") == Some(&serde_yaml::Value::from("journal"))
+    });
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("Journal.md").exists());
+    assert!(!tmp_dir.path().join("Post.md").exists());
+    assert!(tmp_dir.path().join("journal.png").exists());
+    assert!(tmp_dir.path().join("shared.png").exists());
+    assert!(!tmp_dir.path().join("header.png").exists());
+}
+
+#[rstest]
+#[case(
+    ExcludedEmbedBehavior::LinkTo,
+    "Intro.\n\n[Secret](private/Secret.md)\n\n[Draft](Draft.md)\n"
+)]
+#[case(ExcludedEmbedBehavior::Omit, "Intro.\n\n\n\n\n")]
+#[case(
+    ExcludedEmbedBehavior::Inline,
+    "Intro.\n\nSecret content.\n\nDraft content.\n"
+)]
+fn test_excluded_embed_behavior(#[case] behavior: ExcludedEmbedBehavior, #[case] expected: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/excluded-embeds/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let published = |frontmatter: &Frontmatter| {
+        frontmatter.get("publish") != Some(&serde_yaml::Value::Bool(false))
+    };
+    exporter.add_frontmatter_filter(&published);
+    exporter.excluded_embed_behavior(behavior);
+    exporter.run().expect("exporter returned error");
+
+    assert!(!tmp_dir.path().join("private/Secret.md").exists());
+    assert!(!tmp_dir.path().join("Draft.md").exists());
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_excluded_embeds_are_replaced_before_parsing() {
+    let src_dir = TempDir::new().expect("failed to make tempdir");
+    let dest_dir = TempDir::new().expect("failed to make tempdir");
+    std::fs::write(src_dir.path().join("Note.md"), "![[Draft]]\n").unwrap();
+    std::fs::write(
+        src_dir.path().join("Draft.md"),
+        "---\npublish: false\n---\n\n![[Missing]]\n",
+    )
+    .unwrap();
+
+    let mut exporter = Exporter::new(src_dir.path().to_path_buf(), dest_dir.path().to_path_buf());
+    let published = |frontmatter: &Frontmatter| {
+        frontmatter.get("publish") != Some(&serde_yaml::Value::Bool(false))
+    };
+    exporter.add_frontmatter_filter(&published);
+    exporter.excluded_embed_behavior(ExcludedEmbedBehavior::Omit);
+    exporter.run().expect("exporter returned error");
+
+    // The body of the excluded note, including its embed of a missing note, is never parsed.
+    assert_eq!(exporter.unresolved_links(), vec![]);
+}
+
+#[rstest]
+#[case(
+    DuplicateEmbedBehavior::Inline,
+    "Shared snippet.\n\nSome text.\n\nShared snippet.\n"
+)]
+#[case(
+    DuplicateEmbedBehavior::LinkTo,
+    "Shared snippet.\n\nSome text.\n\n[Snippet](Snippet.md)\n"
+)]
+#[case(DuplicateEmbedBehavior::Omit, "Shared snippet.\n\nSome text.\n\n\n")]
+fn test_duplicate_embed_behavior(#[case] behavior: DuplicateEmbedBehavior, #[case] expected: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/duplicate-embeds/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.duplicate_embed_behavior(behavior);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        expected
+    );
+    // Embeds are only deduplicated within a single note.
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Other.md")).unwrap(),
+        "Also embeds it: Shared snippet.\n"
+    );
+}
+
+#[test]
+fn test_max_embed_bytes() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let vault = tmp_dir.path().join("vault");
+    let dest = tmp_dir.path().join("dest");
+    create_dir(&vault).unwrap();
+    create_dir(&dest).unwrap();
+
+    std::fs::write(vault.join("Note.md"), "![[Large]]\n\n![[Small]]\n").unwrap();
+    std::fs::write(
+        vault.join("Large.md"),
+        "A long line of text.\n\n".repeat(100),
+    )
+    .unwrap();
+    std::fs::write(vault.join("Small.md"), "A short note.\n").unwrap();
+
+    let mut exporter = Exporter::new(vault, dest.clone());
+    exporter.max_embed_bytes(200);
+    captured_warnings();
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(dest.join("Note.md")).unwrap(),
+        "[Large](Large.md)\n\nA short note.\n"
+    );
+    let warnings = captured_warnings();
+    assert!(
+        warnings.iter().any(|warning| {
+            warning.starts_with("Embedded note exceeds the maximum embed size")
+                && warning.contains("Large.md")
+        }),
+        "no warning about the large embed in: {:?}",
+        warnings
+    );
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn test_follow_symlinks() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let vault = tmp_dir.path().join("vault");
+    let shared = tmp_dir.path().join("shared");
+    let dest = tmp_dir.path().join("dest");
+    create_dir(&vault).unwrap();
+    create_dir(&shared).unwrap();
+    create_dir(vault.join("real")).unwrap();
+    create_dir(&dest).unwrap();
+
+    std::fs::write(vault.join("Note.md"), "[[Shared]] and [[Inner]]").unwrap();
+    std::fs::write(vault.join("real/Inner.md"), "Inner note").unwrap();
+    std::fs::write(shared.join("Shared.md"), "Shared note").unwrap();
+    // A directory outside of the vault, shared through a symlink.
+    symlink(&shared, vault.join("templates")).unwrap();
+    // A second path to a directory which is already part of the vault.
+    symlink(vault.join("real"), vault.join("linked")).unwrap();
+
+    let mut exporter = Exporter::new(vault, dest.clone());
+    exporter.follow_symlinks(true);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(dest.join("templates/Shared.md")).unwrap(),
+        "Shared note\n"
+    );
+    let inner_copies: Vec<_> = ["real/Inner.md", "linked/Inner.md"]
+        .iter()
+        .filter(|path| dest.join(path).exists())
+        .collect();
+    let [inner] = inner_copies.as_slice() else {
+        panic!(
+            "Inner.md should be exported exactly once, got: {:?}",
+            inner_copies
+        );
+    };
+    assert_eq!(
+        read_to_string(dest.join("Note.md")).unwrap(),
+        format!("[Shared](templates/Shared.md) and [Inner]({inner})\n")
+    );
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn test_follow_symlinks_loop() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let vault = tmp_dir.path().join("vault");
+    create_dir(&vault).unwrap();
+    std::fs::write(vault.join("Note.md"), "A note").unwrap();
+    symlink(&vault, vault.join("loop")).unwrap();
+
+    let mut exporter = Exporter::new(vault, tmp_dir.path().join("dest"));
+    exporter.follow_symlinks(true);
+    match exporter.run().unwrap_err() {
+        ExportError::SymlinkLoop { .. } => {}
+        err => panic!("Wrong error variant: {:?}", err),
+    }
+}
+
+#[test]
+fn test_sorted_traversal() {
+    let contents = || {
+        vault_contents(
+            Path::new("tests/testdata/input/main-samples/"),
+            WalkOptions::default(),
+        )
+        .unwrap()
+    };
+    let first = contents();
+
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(first, sorted, "vault contents should be sorted by path");
+    assert_eq!(first, contents(), "vault contents should be stable");
+}
+
+#[test]
+fn test_dry_run() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let source = PathBuf::from("tests/testdata/input/postprocessors");
+    let actions = Mutex::new(Vec::new());
+    let callback = |action: &DryRunAction| actions.lock().unwrap().push(action.clone());
+
+    let mut exporter = Exporter::new(source.clone(), tmp_dir.path().to_path_buf());
+    exporter.dry_run(true);
+    exporter.on_dry_run_action(&callback);
+    exporter.add_postprocessor(&|ctx, _mdevents| {
+        if ctx.current_file().ends_with("hard_linebreaks.md") {
+            return PostprocessorResult::StopAndSkipNote;
+        }
+        if ctx.current_file().ends_with("Note.md") {
+            ctx.destination.set_file_name("MovedNote.md");
+        }
+        PostprocessorResult::Continue
+    });
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_dir(tmp_dir.path()).unwrap().count(),
+        0,
+        "dry run should not write any files"
+    );
+
+    let mut actions = actions.into_inner().unwrap();
+    actions.sort_by_key(|action| format!("{action:?}"));
+    assert_eq!(
+        actions,
+        vec![
+            DryRunAction::Create {
+                source: source.join("Note.md"),
+                destination: tmp_dir.path().join("MovedNote.md"),
+            },
+            DryRunAction::Create {
+                source: source.join("_embed.md"),
+                destination: tmp_dir.path().join("_embed.md"),
+            },
+            DryRunAction::Skip {
+                source: source.join("hard_linebreaks.md"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_dry_run_overwrite() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let source = PathBuf::from("tests/testdata/input/single-file/note.md");
+    let destination = tmp_dir.path().join("note.md");
+    File::create(&destination).unwrap();
+    let actions = Mutex::new(Vec::new());
+    let callback = |action: &DryRunAction| actions.lock().unwrap().push(action.clone());
+
+    let mut exporter = Exporter::new(source.clone(), destination.clone());
+    exporter.dry_run(true);
+    exporter.on_dry_run_action(&callback);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(read_to_string(&destination).unwrap(), "");
+    assert_eq!(
+        actions.into_inner().unwrap(),
+        vec![DryRunAction::Overwrite {
+            source,
+            destination
+        }]
+    );
+}
+
+#[test]
+fn test_progress_events() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let events = Mutex::new(Vec::new());
+    let callback = |event: ProgressEvent| events.lock().unwrap().push(event);
+
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/main-samples/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.on_progress(&callback);
+    exporter.run().expect("exporter returned error");
+
+    let exported_files = WalkDir::new(tmp_dir.path())
+        .into_iter()
+        .filter(|entry| !entry.as_ref().unwrap().file_type().is_dir())
+        .count();
+    let events = events.into_inner().unwrap();
+
+    assert_eq!(
+        events.first(),
+        Some(&ProgressEvent::Started {
+            total: exported_files
+        })
+    );
+    assert_eq!(events.last(), Some(&ProgressEvent::Finished));
+    let indexes: Vec<usize> = events
+        .iter()
+        .filter_map(|event| match event {
+            ProgressEvent::NoteExported { index, .. } => Some(*index),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(indexes, (1..=exported_files).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_filename_slugifier() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/slugify/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.filename_slugifier(&slugify_filename);
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("white-image.png").exists());
+    for note in ["my-note.md", "other-note.md", "Sub Folder/nested-note.md"] {
+        assert_eq!(
+            read_to_string(PathBuf::from("tests/testdata/expected/slugify/").join(note)).unwrap(),
+            read_to_string(tmp_dir.path().join(note)).unwrap(),
+            "{note} does not have expected content"
+        );
+    }
+}
+
+#[test]
+fn test_assets_dir() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/assets-dir/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.assets_dir(Some(PathBuf::from("static")));
+    exporter.run().expect("exporter returned error");
+
+    assert!(tmp_dir.path().join("static/image.png").exists());
+    assert!(tmp_dir.path().join("static/document.pdf").exists());
+    assert!(
+        !tmp_dir.path().join("static/unused.png").exists(),
+        "unreferenced attachments should not be copied"
+    );
+    assert!(!tmp_dir.path().join("media").exists());
+
+    for note in ["a/Note A.md", "b/c/Note B.md"] {
+        assert_eq!(
+            read_to_string(PathBuf::from("tests/testdata/expected/assets-dir/").join(note))
+                .unwrap(),
+            read_to_string(tmp_dir.path().join(note)).unwrap(),
+            "{note} does not have expected content"
+        );
+    }
+}
+
+#[test]
+fn test_pdf_page_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/pdf-embeds/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string("tests/testdata/expected/pdf-embeds/Note.md").unwrap(),
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+    );
+    assert!(tmp_dir.path().join("docs/User manual.pdf").exists());
+}
+
+#[test]
+fn test_image_alt_from_filename() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/wikilink-images/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.image_alt_from_filename(true);
+    exporter.run().expect("exporter returned error");
+
+    let expected = read_to_string("tests/testdata/expected/wikilink-images/Note.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_image_alt_from_filename_with_assets_dir() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/wikilink-images/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.image_alt_from_filename(true);
+    exporter.assets_dir(Some(PathBuf::from("assets")));
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        "An image: ![pic](assets/pic.png)\n\nWith alias text: ![A white square](assets/pic.png)\n\nWith \
+         a path: ![Other Pic](assets/Other%20Pic.png)\n\nEmbedded note with ![pic](assets/pic.png) \
+         inside.\n"
+    );
+}
+
+#[test]
+fn test_image_alt_from_filename_with_slugifier() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/wikilink-images/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.image_alt_from_filename(true);
+    exporter.filename_slugifier(&slugify_filename);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("note.md")).unwrap(),
+        "An image: ![pic](pic.png)\n\nWith alias text: ![A white square](pic.png)\n\nWith a path: \
+         ![Other Pic](media/other-pic.png)\n\nEmbedded note with ![pic](pic.png) inside.\n"
+    );
+}
+
+#[test]
+fn test_cross_vault_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/cross-vault/main/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_vault(
+        "Work".to_owned(),
+        PathBuf::from("tests/testdata/input/cross-vault/work/"),
+        "https://example.com/work/".to_owned(),
+    );
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        read_to_string("tests/testdata/expected/cross-vault/Note.md").unwrap()
+    );
+    let unresolved: Vec<String> = exporter
+        .unresolved_links()
+        .into_iter()
+        .map(|link| link.reference)
+        .collect();
+    assert_eq!(unresolved, vec!["Work::Missing".to_owned()]);
+}
+
+#[test]
+fn test_cross_vault_links_use_walk_options() {
+    let main_dir = TempDir::new().expect("failed to make tempdir");
+    let work_dir = TempDir::new().expect("failed to make tempdir");
+    let dest_dir = TempDir::new().expect("failed to make tempdir");
+    std::fs::write(
+        main_dir.path().join("Note.md"),
+        "[[Work::Plan]] and [[Work::Draft]]\n",
+    )
+    .unwrap();
+    std::fs::write(work_dir.path().join("Plan.md"), "Plan.\n").unwrap();
+    std::fs::write(work_dir.path().join("Draft.md"), "Draft.\n").unwrap();
+    std::fs::write(work_dir.path().join(".publish-ignore"), "Draft.md\n").unwrap();
+
+    let mut exporter = Exporter::new(main_dir.path().to_path_buf(), dest_dir.path().to_path_buf());
+    exporter.ignore_filename(".publish-ignore".to_owned());
+    exporter.add_vault(
+        "Work".to_owned(),
+        work_dir.path().to_path_buf(),
+        "https://example.com/work/".to_owned(),
+    );
+    exporter.run().expect("exporter returned error");
+
+    let unresolved: Vec<String> = exporter
+        .unresolved_links()
+        .into_iter()
+        .map(|link| link.reference)
+        .collect();
+    assert_eq!(unresolved, vec!["Work::Draft".to_owned()]);
+}
+
+#[test]
+fn test_index_filename() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/index-pages/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.index_filename("index.md".to_owned());
+    exporter.run().expect("exporter returned error");
+
+    assert!(
+        !tmp_dir.path().join("guides/guides.md").exists(),
+        "folder notes should be exported as the index page of their folder"
+    );
+
+    let walker = WalkDir::new("tests/testdata/expected/index-pages/")
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_file());
+    for entry in walker {
+        let expected = read_to_string(entry.path()).unwrap();
+        let note = entry
+            .path()
+            .strip_prefix("tests/testdata/expected/index-pages/")
+            .unwrap();
+        let actual = read_to_string(tmp_dir.path().join(note)).unwrap();
+        assert_eq!(
+            expected,
+            actual,
+            "{} does not have expected content",
+            note.display()
+        );
+    }
+}
+
+#[test]
+fn test_index_filename_with_existing_index_page() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let vault = tmp_dir.path().join("vault");
+    let dest = tmp_dir.path().join("dest");
+    create_dir(&vault).unwrap();
+    create_dir(vault.join("docs")).unwrap();
+    create_dir(&dest).unwrap();
+
+    std::fs::write(vault.join("docs/docs.md"), "The folder note.\n").unwrap();
+    std::fs::write(vault.join("docs/index.md"), "The index page.\n").unwrap();
+    std::fs::write(vault.join("Note.md"), "[[docs]]\n").unwrap();
+
+    let mut exporter = Exporter::new(vault, dest.clone());
+    exporter.index_filename("index.md".to_owned());
+    captured_warnings();
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(dest.join("docs/index.md")).unwrap(),
+        "The index page.\n"
+    );
+    assert_eq!(
+        read_to_string(dest.join("docs/docs.md")).unwrap(),
+        "The folder note.\n"
+    );
+    assert_eq!(
+        read_to_string(dest.join("Note.md")).unwrap(),
+        "[docs](docs/docs.md)\n"
+    );
+    let warnings = captured_warnings();
+    assert!(
+        warnings.iter().any(|warning| {
+            warning.starts_with("Folder already has an index page") && warning.contains("docs.md")
+        }),
+        "no warning about the index page conflict in: {:?}",
+        warnings
+    );
+}
+
+#[rstest]
+#[case(vec![], vec![], "title: A note\ntags:\n- published\ncssclass: wide\nobsidianUIMode: preview\n")]
+#[case(vec!["title", "tags"], vec![], "title: A note\ntags:\n- published\n")]
+#[case(vec![], vec!["cssclass", "obsidianUIMode"], "title: A note\ntags:\n- published\n")]
+#[case(vec!["title", "cssclass"], vec!["cssclass"], "title: A note\n")]
+#[case(vec!["Title"], vec![], "")]
+fn test_frontmatter_allow_and_deny_lists(
+    #[case] allowlist: Vec<&str>,
+    #[case] denylist: Vec<&str>,
+    #[case] expected_frontmatter: &str,
+) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-lists/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.frontmatter_allowlist(allowlist.into_iter().map(String::from).collect());
+    exporter.frontmatter_denylist(denylist.into_iter().map(String::from).collect());
+    exporter.run().expect("exporter returned error");
+
+    let expected = if expected_frontmatter.is_empty() {
+        "Note with frontmatter.\n".to_owned()
+    } else {
+        format!("---\n{expected_frontmatter}---\n\nNote with frontmatter.\n")
+    };
+    assert_eq!(
+        expected,
+        read_to_string(tmp_dir.path().join("note.md")).unwrap()
+    );
+}
+
+/// Collects the warnings logged by all tests, so individual tests can check for the ones they
+/// expect.
+struct CapturingLogger {
+    warnings: Mutex<Vec<String>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.warnings
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    warnings: Mutex::new(Vec::new()),
+};
+
+/// Install [`LOGGER`] (if that hasn't happened yet) and return a copy of the warnings logged so
+/// far.
+fn captured_warnings() -> Vec<String> {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Warn);
+    });
+    LOGGER.warnings.lock().unwrap().clone()
+}
+
+#[test]
+fn test_unresolved_link_logs_warning() {
+    captured_warnings();
+
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/unresolved-link/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+
+    let warnings = captured_warnings();
+    assert!(
+        warnings.iter().any(|warning| {
+            warning.starts_with("Unable to find referenced note")
+                && warning.contains("Reference: 'Missing note'")
+                && warning.contains("unresolved-link/Note.md")
+        }),
+        "no warning about the unresolved link in: {:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_ambiguous_links() {
+    captured_warnings();
+
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    Exporter::new(
+        PathBuf::from("tests/testdata/input/ambiguous-links/"),
+        tmp_dir.path().to_path_buf(),
+    )
+    .run()
+    .expect("exporter returned error");
+
+    // The note in the same folder as the linking note is preferred.
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("team-b/Standup.md")).unwrap(),
+        "Follow up on [Meeting > Action Items](Meeting.md#action-items).\n"
+    );
+    // Otherwise, the choice is stable, and the candidates are reported.
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Overview.md")).unwrap(),
+        "See the [Meeting](team-a/Meeting.md) notes.\n"
+    );
+    let warnings = captured_warnings();
+    assert!(
+        warnings.iter().any(|warning| {
+            warning.starts_with("Ambiguous match for note reference")
+                && warning.contains("Reference: 'Meeting'")
+                && warning.contains("team-a/Meeting.md', '")
+                && warning.contains("team-b/Meeting.md'")
+        }),
+        "no warning about the ambiguous link in: {:?}",
+        warnings
+    );
+    assert_eq!(
+        warnings
+            .iter()
+            .filter(|warning| warning.contains("ambiguous-links/team-a/Meeting.md"))
+            .count(),
+        1,
+        "links resolved through the folder of the linking note aren't ambiguous"
+    );
+}
+
+#[test]
+fn test_unresolved_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/unresolved-link/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.run().expect("exporter returned error");
+
+    let links: Vec<(PathBuf, usize, String)> = exporter
+        .unresolved_links()
+        .into_iter()
+        .map(|link: UnresolvedLink| (link.source, link.offset, link.reference))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (
+                PathBuf::from("tests/testdata/input/unresolved-link/Note.md"),
+                9,
+                "Missing note".to_owned()
+            ),
+            (
+                PathBuf::from("tests/testdata/input/unresolved-link/With frontmatter.md"),
+                27,
+                "Nowhere".to_owned()
+            ),
+        ]
+    );
+
+    // Offsets point at the opening brackets of references, counting from the start of the file.
+    let content = read_to_string("tests/testdata/input/unresolved-link/Note.md").unwrap();
+    assert!(content.get(9..).unwrap().starts_with("[[Missing note]]"));
+    let content =
+        read_to_string("tests/testdata/input/unresolved-link/With frontmatter.md").unwrap();
+    assert!(content.get(27..).unwrap().starts_with("![[Nowhere]]"));
+}
+
+#[test]
+fn test_cloned_exporter_has_own_run_state() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/unresolved-link/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.run().expect("exporter returned error");
+
+    let mut clone = exporter.clone();
+    assert_eq!(clone.unresolved_links(), vec![]);
+    clone.start_at(PathBuf::from(
+        "tests/testdata/input/unresolved-link/Note.md",
+    ));
+    clone.run().expect("exporter returned error");
+    assert_eq!(clone.unresolved_links().len(), 1);
+    assert_eq!(exporter.unresolved_links().len(), 2);
+}
+
+#[test]
+fn test_fail_on_unresolved_links() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/unresolved-link/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.fail_on_unresolved_links(true);
+    let err = exporter
+        .run()
+        .expect_err("export should fail on unresolved links");
+
+    match err {
+        ExportError::UnresolvedLinks { links } => {
+            let references: Vec<&str> = links.iter().map(|link| link.reference.as_str()).collect();
+            assert_eq!(references, vec!["Missing note", "Nowhere"]);
+        }
+        _ => panic!("Wrong error variant: {:?}", err),
+    }
+    // Notes are still exported, so all unresolved links are found.
+    assert!(tmp_dir.path().join("Note.md").exists());
+    assert!(tmp_dir.path().join("With frontmatter.md").exists());
+}
+
+#[rstest]
+#[case(UnresolvedLinkStyle::Emphasis, "Links to *Missing note*.\n")]
+#[case(UnresolvedLinkStyle::PlainText, "Links to Missing note.\n")]
+#[case(
+    UnresolvedLinkStyle::Marked { class: "broken-link".to_owned() },
+    "Links to <span class=\"broken-link\">Missing note</span>.\n"
+)]
+#[case(
+    UnresolvedLinkStyle::Suffix { text: "(missing)".to_owned() },
+    "Links to Missing note (missing).\n"
+)]
+fn test_unresolved_link_style(#[case] style: UnresolvedLinkStyle, #[case] expected: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/unresolved-link/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.unresolved_link_style(style);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_output_filter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/output-filter/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let mark_done = |context: &mut Context, events: &mut MarkdownEvents<'_>| {
+        context.frontmatter.insert(
+            serde_yaml::Value::String("checked".to_owned()),
+            serde_yaml::Value::Bool(true),
+        );
+        events.push(Event::Start(Tag::Paragraph));
+        events.push(Event::Text(CowStr::Borrowed(
+            "Added by a postprocessor: todo",
+        )));
+        events.push(Event::End(TagEnd::Paragraph));
+        PostprocessorResult::Continue
+    };
+    exporter.add_postprocessor(&mark_done);
+    exporter.add_output_filter(&|_context, output| output.replace("todo", "TODO"));
+    exporter.add_output_filter(&|context, output| {
+        format!(
+            "<!-- {} -->\n{output}",
+            context.current_file_relative().display()
+        )
+    });
+    exporter.line_ending(LineEnding::Crlf);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        "<!-- Note.md -->\r\n---\r\nstatus: TODO\r\nchecked: true\r\n---\r\n\r\nThis note is \
+         still TODO.\r\n\r\nAdded by a postprocessor: TODO\r\n"
+    );
+}
+
+#[test]
+fn test_finalizer() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/finalizer/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let write_index = |context: &FinalizeContext| {
+        let mut index = String::new();
+        for note in &context.notes {
+            let title = match note.frontmatter.get("title") {
+                Some(serde_yaml::Value::String(title)) => title.clone(),
+                _ => note
+                    .source
+                    .file_stem()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            let link = note.destination.strip_prefix(&context.destination).unwrap();
+            index.push_str(&format!("- [{}]({})\n", title, link.display()));
+        }
+        let path = context.destination.join("index.md");
+        std::fs::write(&path, index).map_err(|source| ExportError::WriteError { path, source })
+    };
+    exporter.add_finalizer(&write_index);
+    exporter.run().expect("exporter returned error");
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("index.md")).unwrap(),
+        "- [The first note](First.md)\n- [Second](Second.md)\n- [Third](sub/Third.md)\n"
+    );
 }
@@ -0,0 +1,81 @@
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use obsidian_export::finalizers::{emit_redirects, RedirectFormat};
+use obsidian_export::{slugify_filename, Exporter};
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+#[test]
+fn test_emit_netlify_redirects() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/slugify/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.filename_slugifier(&slugify_filename);
+    let finalizer = emit_redirects(RedirectFormat::Netlify);
+    exporter.add_finalizer(&finalizer);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        "/My%20Note /my-note 301\n\
+         /Other%20Note /other-note 301\n\
+         /Sub%20Folder/Nested%20Note /Sub%20Folder/nested-note 301\n",
+        read_to_string(tmp_dir.path().join("_redirects")).unwrap(),
+    );
+}
+
+#[test]
+fn test_emit_no_redirects_for_unchanged_paths() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/slugify/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let finalizer = emit_redirects(RedirectFormat::Netlify);
+    exporter.add_finalizer(&finalizer);
+    exporter.run().unwrap();
+
+    assert!(!tmp_dir.path().join("_redirects").exists());
+}
+
+#[test]
+fn test_emit_jekyll_redirect_from() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/slugify/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.filename_slugifier(&slugify_filename);
+    let finalizer = emit_redirects(RedirectFormat::JekyllRedirectFrom);
+    exporter.add_finalizer(&finalizer);
+    exporter.run().unwrap();
+
+    let note = read_to_string(tmp_dir.path().join("Sub Folder/nested-note.md")).unwrap();
+    assert!(
+        note.starts_with("---\nredirect_from:\n- /Sub%20Folder/Nested%20Note\n---\n\n"),
+        "{}",
+        note
+    );
+}
+
+#[test]
+fn test_emit_html_meta_refresh() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/slugify/"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.filename_slugifier(&slugify_filename);
+    let finalizer = emit_redirects(RedirectFormat::HtmlMetaRefresh);
+    exporter.add_finalizer(&finalizer);
+    exporter.run().unwrap();
+
+    let html = read_to_string(tmp_dir.path().join("Sub Folder/Nested Note.html")).unwrap();
+    assert!(
+        html.contains("<meta http-equiv=\"refresh\" content=\"0; url=/Sub%20Folder/nested-note\">"),
+        "{}",
+        html
+    );
+}
@@ -1,12 +1,58 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::{read_to_string, remove_file};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use obsidian_export::postprocessors::{filter_by_tags, softbreaks_to_hardbreaks};
-use obsidian_export::{Context, Exporter, MarkdownEvents, PostprocessorResult};
+use obsidian_export::postprocessors::{
+    absolute_link_rewriter,
+    add_header,
+    add_source_path,
+    auto_excerpt,
+    block_embed_as_quote,
+    callouts_to_details,
+    cdn_image_rewriter,
+    collapse_blank_lines,
+    cssclass_wrapper,
+    embed_as_details,
+    escape_liquid,
+    filter_by_tags,
+    folder_breadcrumbs,
+    footnote_style,
+    frontmatter_tags_to_inline,
+    insert_toc,
+    linkify_bare_urls,
+    links_to_footnotes,
+    math_to_fenced,
+    mdx_wrapper,
+    media_embeds_to_html,
+    mermaid_handling,
+    normalize_dates,
+    normalize_tasks,
+    obsidian_publish_frontmatter,
+    pdf_embeds_to_html,
+    remove_empty_frontmatter,
+    resolve_templates,
+    sanitize_html,
+    softbreaks_to_hardbreaks,
+    strip_heading_tags,
+    strip_regions,
+    validate_frontmatter,
+    FootnoteStyle,
+    FrontmatterListStyle,
+    FrontmatterSchema,
+    FrontmatterValidationError,
+    HtmlPolicy,
+    MediaExtensions,
+    MermaidMode,
+    PdfEmbedStyle,
+    TagPosition,
+    TaskStyle,
+    YamlType,
+};
+use obsidian_export::{Context, ExportError, Exporter, MarkdownEvents, PostprocessorResult};
 use pretty_assertions::assert_eq;
 use pulldown_cmark::{CowStr, Event};
+use rstest::rstest;
 use serde_yaml::Value;
 use tempfile::TempDir;
 use walkdir::WalkDir;
@@ -84,6 +130,85 @@ fn test_postprocessor_stop_and_skip() {
     assert!(!note_path.exists());
 }
 
+#[test]
+fn test_fallible_postprocessor_error() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/postprocessors"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_fallible_postprocessor(&|ctx, _mdevents| {
+        if ctx.current_file().ends_with("Note.md") {
+            return Err("note failed validation".into());
+        }
+        Ok(PostprocessorResult::Continue)
+    });
+
+    match exporter.run().unwrap_err() {
+        ExportError::FileExportError { source, .. } => match *source {
+            ExportError::PostprocessorError { path, source: err } => {
+                assert_eq!(
+                    path,
+                    PathBuf::from("tests/testdata/input/postprocessors/Note.md")
+                );
+                assert_eq!(err.to_string(), "note failed validation");
+            }
+            err => panic!("Wrong error variant for source, got: {:?}", err),
+        },
+        err => panic!("Wrong error variant: {:?}", err),
+    }
+}
+
+#[test]
+fn test_validate_frontmatter_valid() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-schema/Valid.md"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = validate_frontmatter(
+        FrontmatterSchema::new()
+            .require("title", YamlType::String)
+            .optional("tags", YamlType::List),
+    );
+    exporter.add_fallible_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    assert!(tmp_dir.path().join("Valid.md").exists());
+}
+
+#[rstest]
+#[case("Missing.md", "missing required frontmatter key 'title'")]
+#[case(
+    "WrongType.md",
+    "frontmatter key 'tags' should be a list, but is a string"
+)]
+fn test_validate_frontmatter_rejects(#[case] file: &str, #[case] message: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        Path::new("tests/testdata/input/frontmatter-schema").join(file),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = validate_frontmatter(
+        FrontmatterSchema::new()
+            .require("title", YamlType::String)
+            .optional("tags", YamlType::List),
+    );
+    exporter.add_fallible_postprocessor(&postprocessor);
+
+    match exporter.run().unwrap_err() {
+        ExportError::FileExportError { source, .. } => match *source {
+            ExportError::PostprocessorError { source: err, .. } => {
+                assert_eq!(err.to_string(), message);
+                assert!(err.downcast_ref::<FrontmatterValidationError>().is_some());
+            }
+            err => panic!("Wrong error variant for source, got: {:?}", err),
+        },
+        err => panic!("Wrong error variant: {:?}", err),
+    }
+    assert!(!tmp_dir.path().join(file).exists());
+}
+
 #[test]
 fn test_postprocessor_change_destination() {
     let tmp_dir = TempDir::new().expect("failed to make tempdir");
@@ -295,3 +420,996 @@ fn test_filter_by_tags() {
         );
     }
 }
+
+#[test]
+fn test_absolute_link_rewriter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/absolute-links"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let absolute_link_rewriter = absolute_link_rewriter("/docs/".into(), true);
+    exporter.add_postprocessor(&absolute_link_rewriter);
+    exporter.run().unwrap();
+
+    for note in ["index.md", "folder/Note.md", "folder/sub/Deep Note.md"] {
+        let expected =
+            read_to_string(PathBuf::from("tests/testdata/expected/absolute-links").join(note))
+                .unwrap();
+        let actual = read_to_string(tmp_dir.path().join(note)).unwrap();
+        assert_eq!(expected, actual, "{note} does not have expected content");
+    }
+}
+
+#[test]
+fn test_cdn_image_rewriter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/cdn-images"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let cdn_image_rewriter = cdn_image_rewriter("https://cdn.example.com/assets/".into());
+    exporter.add_postprocessor(&cdn_image_rewriter);
+    exporter.run().unwrap();
+
+    for note in ["Note.md", "folder/Nested.md"] {
+        let actual = read_to_string(tmp_dir.path().join(note)).unwrap();
+        let expected =
+            read_to_string(PathBuf::from("tests/testdata/expected/cdn-images").join(note)).unwrap();
+        assert_eq!(expected, actual, "{note} does not have expected content");
+    }
+}
+
+#[test]
+fn test_absolute_link_rewriter_without_trailing_slash() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/absolute-links"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let absolute_link_rewriter = absolute_link_rewriter(String::new(), false);
+    exporter.add_postprocessor(&absolute_link_rewriter);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("folder/Note.md")).unwrap();
+    assert_eq!(
+        actual,
+        "Link to [index](/index) and [Deep Note](/folder/sub/Deep%20Note).\n"
+    );
+}
+
+#[test]
+fn test_math_to_fenced() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/math"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let math_to_fenced = math_to_fenced(true);
+    exporter.add_postprocessor(&math_to_fenced);
+    exporter.run().unwrap();
+
+    let expected = read_to_string("tests/testdata/expected/math/Math.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Math.md")).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_math_to_fenced_without_inline_wrapping() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/math"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let math_to_fenced = math_to_fenced(false);
+    exporter.add_postprocessor(&math_to_fenced);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Math.md")).unwrap();
+    assert!(actual.starts_with("Inline math like $e^{i\\pi} + 1 = 0$ stays inline.\n"));
+    assert!(actual.contains("while $x_1$ is math."));
+    assert!(!actual.contains("<span"));
+}
+
+#[test]
+fn test_escape_liquid() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/liquid"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_postprocessor(&escape_liquid);
+    exporter.run().unwrap();
+
+    let expected = read_to_string("tests/testdata/expected/liquid/Note.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_add_source_path() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/absolute-links"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let add_source_path = add_source_path("source".to_owned());
+    exporter.add_postprocessor(&add_source_path);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("folder/sub/Deep Note.md")).unwrap();
+    assert!(
+        actual.starts_with("---\nsource: folder/sub/Deep Note.md\n---\n"),
+        "unexpected frontmatter in: {}",
+        actual
+    );
+}
+
+#[test]
+fn test_add_source_path_skips_embeds() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/postprocessors"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let add_source_path = add_source_path("source".to_owned());
+    exporter.add_postprocessor(&add_source_path);
+    exporter.add_embed_postprocessor(&add_source_path);
+    exporter.add_embed_postprocessor(&|ctx, _mdevents| {
+        assert!(!ctx.frontmatter.contains_key("source"));
+        PostprocessorResult::Continue
+    });
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert!(actual.contains("source: Note.md\n"));
+}
+
+#[rstest]
+#[case(
+    Some("Home"),
+    "breadcrumbs:\n- Home\n- Docs\n- Guides\n- Getting started\n",
+    "breadcrumbs:\n- Home\n"
+)]
+#[case(
+    None,
+    "breadcrumbs:\n- Docs\n- Guides\n- Getting started\n",
+    "breadcrumbs: []\n"
+)]
+fn test_folder_breadcrumbs(
+    #[case] root_label: Option<&str>,
+    #[case] nested: &str,
+    #[case] at_root: &str,
+) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/breadcrumbs"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor =
+        folder_breadcrumbs("breadcrumbs".to_owned(), root_label.map(ToOwned::to_owned));
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        read_to_string(
+            tmp_dir
+                .path()
+                .join("Docs/Guides/Getting started/Install.md")
+        )
+        .unwrap(),
+        format!("---\ntitle: Installation\n{nested}---\n\nHow to install.\n")
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Index.md")).unwrap(),
+        format!("---\n{at_root}---\n\nThe home page.\n")
+    );
+}
+
+#[test]
+fn test_resolve_templates_builtin_tokens() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/templates"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = resolve_templates(HashMap::new(), false);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let body = read_to_string(tmp_dir.path().join("Meeting.md")).unwrap();
+    assert!(body.contains("# Weekly sync\n"), "{}", body);
+    let line = body
+        .lines()
+        .find(|line| line.starts_with("Held on "))
+        .unwrap();
+    let date = line.get(8..18).unwrap();
+    assert!(
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok(),
+        "{}",
+        line
+    );
+    let time = line.get(22..27).unwrap();
+    assert!(
+        chrono::NaiveTime::parse_from_str(time, "%H:%M").is_ok(),
+        "{}",
+        line
+    );
+    assert!(line.ends_with(" by {{author}} in {{room}}."), "{}", line);
+    assert!(body.contains("`{{date}}`"), "{}", body);
+    assert!(body.contains("\nTemplate: {{date}}\n"), "{}", body);
+}
+
+#[test]
+fn test_resolve_templates_custom_tokens() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/templates"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let vars = HashMap::from([
+        ("author".to_owned(), "Ada".to_owned()),
+        ("date".to_owned(), "2024-01-31".to_owned()),
+        ("time".to_owned(), "09:30".to_owned()),
+    ]);
+    let postprocessor = resolve_templates(vars, true);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Meeting.md")).unwrap(),
+        "---\ntitle: Weekly sync\n---\n\n# Weekly sync\n\nHeld on 2024-01-31 at 09:30 by Ada in {{room}}.\n\nCode is left alone: `{{date}}`\n\n````\nTemplate: {{date}}\n````\n"
+    );
+}
+
+#[test]
+fn test_remove_empty_frontmatter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/empty-frontmatter"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_postprocessor(&remove_empty_frontmatter);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Null.md")).unwrap(),
+        "A note with only a null frontmatter value.\n"
+    );
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Kept.md")).unwrap(),
+        "---\ntitle: Kept\ntags: []\n---\n\nA note with a non-empty value.\n"
+    );
+}
+
+#[test]
+fn test_links_to_footnotes() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/links-to-footnotes"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let links_to_footnotes = links_to_footnotes("link-".to_owned());
+    exporter.add_postprocessor(&links_to_footnotes);
+    exporter.run().unwrap();
+
+    let expected = read_to_string("tests/testdata/expected/links-to-footnotes/Note.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[rstest]
+#[case(
+    FootnoteStyle::Inline,
+    "The first claim. (An article.) The second claim. (A book with *emphasis*.)\n\nThe first source again. (An article.)\n"
+)]
+#[case(
+    FootnoteStyle::Endnotes,
+    "The first claim.[^1] The second claim.[^2]\n\nThe first source again.[^1]\n\n[^1]: An article.\n\n[^2]: A book with *emphasis*.\n"
+)]
+fn test_footnote_style(#[case] style: FootnoteStyle, #[case] expected: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/footnote-style"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = footnote_style(style);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_normalize_dates() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/dates"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let normalize_dates = normalize_dates(
+        vec![
+            "date".to_owned(),
+            "updated".to_owned(),
+            "created".to_owned(),
+            "title".to_owned(),
+        ],
+        "%Y-%m-%dT%H:%M:%S%:z".to_owned(),
+    );
+    exporter.add_postprocessor(&normalize_dates);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Note.md")).unwrap(),
+        "---\ndate: 2023-01-05T00:00:00+00:00\nupdated: 2023-01-05T10:30:00+02:00\ncreated: 2023-01-04T09:15:00+00:00\ntitle: 2023-01-05 is not only a date\nunrelated: 2023-01-06\n---\n\nA note with dates.\n"
+    );
+}
+
+#[test]
+fn test_normalize_dates_to_utc_designator() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/dates"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let normalize_dates = normalize_dates(vec!["date".to_owned()], "%Y-%m-%dT%H:%M:%SZ".to_owned());
+    exporter.add_postprocessor(&normalize_dates);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert!(
+        actual.starts_with("---\ndate: 2023-01-05T00:00:00Z\n"),
+        "unexpected frontmatter in: {}",
+        actual
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid date format")]
+fn test_normalize_dates_invalid_format() {
+    let _ = normalize_dates(vec!["date".to_owned()], "%Q".to_owned());
+}
+
+#[test]
+fn test_strip_heading_tags() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/heading-tags"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let strip_heading_tags = strip_heading_tags(None);
+    exporter.add_postprocessor(&strip_heading_tags);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected = read_to_string("tests/testdata/expected/heading-tags/Note.md").unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_strip_heading_tags_into_frontmatter() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/heading-tags"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let strip_heading_tags = strip_heading_tags(Some("tags".to_owned()));
+    exporter.add_postprocessor(&strip_heading_tags);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert!(
+        actual.starts_with("---\ntags:\n- existing\n- tag\n- nested/tag\n---\n\n# Heading\n"),
+        "unexpected frontmatter in: {}",
+        actual
+    );
+}
+
+#[test]
+fn test_callouts_to_details() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/callouts"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_postprocessor(&callouts_to_details);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected = read_to_string("tests/testdata/expected/callouts/Note.md").unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_note_extensions() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/note-extensions"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.note_extensions(vec!["md".into(), ".txt".into()]);
+    exporter.add_postprocessor(&foo_to_bar);
+    exporter.run().unwrap();
+
+    for file in ["Note.md", "Plain.txt", "Data.org"] {
+        let actual = read_to_string(tmp_dir.path().join(file)).unwrap();
+        let expected =
+            read_to_string(PathBuf::from("tests/testdata/expected/note-extensions").join(file))
+                .unwrap();
+        assert_eq!(expected, actual, "{}", file);
+    }
+}
+
+#[test]
+fn test_context_title() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/note-titles"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let titles = Mutex::new(HashMap::new());
+    let collect_titles = |ctx: &mut Context, _events: &mut MarkdownEvents<'_>| {
+        let file = ctx.current_file_relative().to_path_buf();
+        titles.lock().unwrap().insert(file, ctx.title());
+        PostprocessorResult::Continue
+    };
+    exporter.add_postprocessor(&collect_titles);
+    exporter.run().unwrap();
+
+    let titles = titles.into_inner().unwrap();
+    assert_eq!(
+        titles.get(Path::new("Frontmatter Title.md")).unwrap(),
+        "From frontmatter"
+    );
+    assert_eq!(
+        titles.get(Path::new("Heading Title.md")).unwrap(),
+        "From first heading"
+    );
+    assert_eq!(
+        titles.get(Path::new("Filename Title.md")).unwrap(),
+        "Filename Title"
+    );
+}
+
+#[test]
+fn test_context_title_is_cached() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/note-titles/Frontmatter Title.md"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let check_title = |ctx: &mut Context, _events: &mut MarkdownEvents<'_>| {
+        assert_eq!(ctx.title(), "From frontmatter");
+        ctx.frontmatter.remove("title");
+        assert_eq!(ctx.title(), "From frontmatter");
+        PostprocessorResult::Continue
+    };
+    exporter.add_postprocessor(&check_title);
+    exporter.run().unwrap();
+}
+
+/// This postprocessor blanks out Obsidian comments (`%% comment %%`) spanning an entire text
+/// event.
+fn strip_comments(_ctx: &mut Context, events: &mut MarkdownEvents<'_>) -> PostprocessorResult {
+    for event in events.iter_mut() {
+        if let Event::Text(text) = event {
+            if text.starts_with("%%") && text.trim_end().ends_with("%%") {
+                *text = CowStr::Borrowed("");
+            }
+        }
+    }
+    PostprocessorResult::Continue
+}
+
+#[test]
+fn test_collapse_blank_lines() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/blank-lines"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_postprocessor(&strip_comments);
+    exporter.add_postprocessor(&collapse_blank_lines);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected = read_to_string("tests/testdata/expected/blank-lines/Note.md").unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_linkify_bare_urls() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/bare-urls"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.add_postprocessor(&linkify_bare_urls);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected = read_to_string("tests/testdata/expected/bare-urls/Note.md").unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_insert_toc() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/toc"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = insert_toc("%% toc %%".to_owned());
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    for file in ["Note.md", "Without marker.md"] {
+        let actual = read_to_string(tmp_dir.path().join(file)).unwrap();
+        let expected = read_to_string(Path::new("tests/testdata/expected/toc").join(file)).unwrap();
+        assert_eq!(expected, actual, "{}", file);
+    }
+}
+
+#[test]
+fn test_strip_regions() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/private-regions"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = strip_regions(
+        "%% BEGIN PRIVATE %%".to_owned(),
+        "%% END PRIVATE %%".to_owned(),
+    );
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    for file in ["Note.md", "Unclosed.md"] {
+        let actual = read_to_string(tmp_dir.path().join(file)).unwrap();
+        let expected =
+            read_to_string(Path::new("tests/testdata/expected/private-regions").join(file))
+                .unwrap();
+        assert_eq!(expected, actual, "{}", file);
+    }
+}
+
+#[test]
+fn test_frontmatter_tags_to_inline() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-tags"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = frontmatter_tags_to_inline(TagPosition::Top, true);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    for file in ["Note.md", "Untagged.md"] {
+        let actual = read_to_string(tmp_dir.path().join(file)).unwrap();
+        let expected =
+            read_to_string(Path::new("tests/testdata/expected/frontmatter-tags").join(file))
+                .unwrap();
+        assert_eq!(expected, actual, "{}", file);
+    }
+}
+
+#[test]
+fn test_frontmatter_tags_to_inline_keeps_key() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/frontmatter-tags"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = frontmatter_tags_to_inline(TagPosition::Bottom, false);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(
+        "---\ntitle: Tagged note\ntags:\n- project\n- '#status/active'\n- area/work/reports\n---\n\n\
+         # Tagged note\n\nSome text.\n\n#project #status/active #area/work/reports\n",
+        actual
+    );
+}
+
+#[test]
+fn test_mdx_wrapper() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/mdx-wrapper"),
+        tmp_dir.path().to_path_buf(),
+    );
+    exporter.output_extension("mdx".to_owned());
+    let postprocessor = mdx_wrapper("NoteLayout".to_owned());
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    for file in ["Note.mdx", "Other.mdx"] {
+        let actual = read_to_string(tmp_dir.path().join(file)).unwrap();
+        let expected =
+            read_to_string(Path::new("tests/testdata/expected/mdx-wrapper").join(file)).unwrap();
+        assert_eq!(expected, actual, "{}", file);
+    }
+}
+
+#[rstest]
+#[case("Scalar.md", "<div class=\"wide\">")]
+#[case("List.md", "<div class=\"wide dark\">")]
+fn test_cssclass_wrapper(#[case] note: &str, #[case] opening_tag: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/cssclasses"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = cssclass_wrapper(vec![]);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join(note)).unwrap();
+    let body = actual.split_once("---\n\n").unwrap().1;
+    assert!(
+        body.starts_with(&format!("{opening_tag}\n\n# ")),
+        "unexpected body in: {}",
+        actual
+    );
+    assert!(body.ends_with("Some text.\n\n</div>\n\n"));
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Plain.md")).unwrap(),
+        "# Plain note\n\nSome text.\n",
+        "notes without CSS classes shouldn't be wrapped"
+    );
+}
+
+#[test]
+fn test_cssclass_wrapper_base_classes() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/cssclasses"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = cssclass_wrapper(vec!["note".to_owned(), "wide".to_owned()]);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let list = read_to_string(tmp_dir.path().join("List.md")).unwrap();
+    assert!(list.contains("<div class=\"note wide dark\">\n"));
+    let plain = read_to_string(tmp_dir.path().join("Plain.md")).unwrap();
+    assert!(plain.starts_with("<div class=\"note wide\">\n"));
+}
+
+#[rstest]
+#[case(TaskStyle::Checkbox, "Checkbox.md")]
+#[case(TaskStyle::Emoji, "Emoji.md")]
+#[case(TaskStyle::Html, "Html.md")]
+fn test_normalize_tasks(#[case] style: TaskStyle, #[case] expected_file: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/tasks"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = normalize_tasks(style);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Tasks.md")).unwrap();
+    let expected =
+        read_to_string(Path::new("tests/testdata/expected/tasks").join(expected_file)).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[rstest]
+#[case(false, "Markdown.md")]
+#[case(true, "Comment.md")]
+fn test_add_header(#[case] as_comment: bool, #[case] expected_file: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/header"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = add_header(
+        "This work is licensed under [CC BY 4.0](https://creativecommons.org/licenses/by/4.0/)."
+            .to_owned(),
+        as_comment,
+    );
+    exporter.add_postprocessor(&postprocessor);
+    // Embedded notes are left alone, even when the header is added as an embed postprocessor.
+    exporter.add_embed_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected =
+        read_to_string(Path::new("tests/testdata/expected/header").join(expected_file)).unwrap();
+    assert_eq!(expected, actual);
+    assert_eq!(actual.matches("This work is licensed").count(), 1);
+}
+
+#[rstest]
+#[case(true, "Titled.md")]
+#[case(false, "Untitled.md")]
+fn test_embed_as_details(#[case] show_title: bool, #[case] expected_file: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/embed-details"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = embed_as_details(show_title);
+    exporter.add_embed_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected =
+        read_to_string(Path::new("tests/testdata/expected/embed-details").join(expected_file))
+            .unwrap();
+    assert_eq!(expected, actual);
+    if show_title {
+        assert!(actual.contains("<summary>Pancake &lt;Recipe&gt;</summary>"));
+    }
+}
+
+#[rstest]
+#[case(true, "Attributed.md")]
+#[case(false, "Unattributed.md")]
+fn test_block_embed_as_quote(#[case] attribute: bool, #[case] expected_file: &str) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/block-quote"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = block_embed_as_quote(attribute);
+    exporter.add_embed_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected =
+        read_to_string(Path::new("tests/testdata/expected/block-quote").join(expected_file))
+            .unwrap();
+    assert_eq!(expected, actual);
+    assert!(actual.contains(" > Simplicity is prerequisite for reliability."));
+    assert_eq!(
+        actual.contains(" > — [Famous quotes](Quotes.md)"),
+        attribute
+    );
+}
+
+#[test]
+fn test_media_embeds_to_html() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/media-embeds"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = media_embeds_to_html(MediaExtensions::default());
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let expected = read_to_string("tests/testdata/expected/media-embeds/Note.md").unwrap();
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert_eq!(expected, actual);
+    assert!(tmp_dir.path().join("interview.mp3").exists());
+    assert!(tmp_dir.path().join("clips/Summer clip.mp4").exists());
+}
+
+#[test]
+fn test_media_embeds_to_html_custom_extensions() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/media-embeds"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let mut extensions = MediaExtensions::default();
+    extensions.audio.remove("mp3");
+    extensions.video.insert("pdf".to_owned());
+    let postprocessor = media_embeds_to_html(extensions);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert!(actual.contains("Listen to the interview: [interview.mp3](interview.mp3)"));
+    assert!(actual.contains("<video controls src=\"clips/Summer%20clip.mp4\"></video>"));
+    assert!(actual.contains("<video controls src=\"handout.pdf\"></video>"));
+}
+
+#[rstest]
+#[case(
+    PdfEmbedStyle::Embed,
+    "<embed src=\"docs/User%20manual.pdf#page=3\" type=\"application/pdf\">",
+    "<embed src=\"docs/User%20manual.pdf\" type=\"application/pdf\">"
+)]
+#[case(
+    PdfEmbedStyle::Iframe,
+    "<iframe src=\"docs/User%20manual.pdf#page=3\"></iframe>",
+    "<iframe src=\"docs/User%20manual.pdf\"></iframe>"
+)]
+fn test_pdf_embeds_to_html(
+    #[case] style: PdfEmbedStyle,
+    #[case] page_embed: &str,
+    #[case] document_embed: &str,
+) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/pdf-embeds"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = pdf_embeds_to_html(style);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    assert!(actual.contains(&format!("\n{page_embed}\n")));
+    assert!(actual.contains(&format!("The whole manual: {document_embed}\n")));
+    assert!(
+        actual.contains("[the appendix](docs/User%20manual.pdf#page=12)"),
+        "links to PDF files should stay links"
+    );
+}
+
+#[test]
+fn test_mermaid_div_wrapper() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/mermaid"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = mermaid_handling(MermaidMode::DivWrapper);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Docs/Note.md")).unwrap(),
+        "A flowchart:\n\n<div class=\"mermaid\">\ngraph TD\n    A[Start] --&gt; B{Is it?}\n    B --&gt;|Yes| C[OK]\n</div>\n\nOther code blocks are left alone:\n\n````rust\nfn main() {}\n````\n"
+    );
+}
+
+#[test]
+fn test_mermaid_image_placeholder() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/mermaid"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = mermaid_handling(MermaidMode::ImagePlaceholder {
+        dir: PathBuf::from("diagrams"),
+    });
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let sidecars: Vec<PathBuf> = std::fs::read_dir(tmp_dir.path().join("diagrams"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(sidecars.len(), 1, "{sidecars:?}");
+    let sidecar = sidecars.first().unwrap();
+    assert_eq!(
+        read_to_string(sidecar).unwrap(),
+        "graph TD\n    A[Start] --> B{Is it?}\n\n    B -->|Yes| C[OK]\n"
+    );
+    let name = sidecar.file_stem().unwrap().to_str().unwrap();
+    assert!(name.starts_with("mermaid-"), "{}", name);
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Docs/Note.md")).unwrap(),
+        format!("A flowchart:\n\n![Mermaid diagram](../diagrams/{name}.svg)\n\nOther code blocks are left alone:\n\n````rust\nfn main() {{}}\n````\n")
+    );
+}
+
+#[test]
+fn test_mermaid_image_placeholder_without_writing() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/mermaid"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = mermaid_handling(MermaidMode::ImagePlaceholder {
+        dir: PathBuf::from("diagrams"),
+    });
+    exporter.add_postprocessor(&postprocessor);
+
+    let paths: Vec<PathBuf> = exporter
+        .iter_exports()
+        .map(|file| file.unwrap().path)
+        .collect();
+    assert_eq!(paths.len(), 2, "{paths:?}");
+    assert_eq!(paths.first().unwrap(), Path::new("Docs/Note.md"));
+    assert_eq!(paths.last().unwrap().extension().unwrap(), "mmd");
+
+    exporter.dry_run(true);
+    exporter.run().unwrap();
+    assert!(
+        !tmp_dir.path().join("diagrams").exists(),
+        "sidecar files shouldn't be written without exporting"
+    );
+}
+
+#[test]
+fn test_auto_excerpt() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/excerpt"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = auto_excerpt("excerpt".to_owned(), 80);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    // The heading and the paragraph holding just an image are skipped, and the excerpt is cut off
+    // after the last word which fits.
+    let post = read_to_string(tmp_dir.path().join("Post.md")).unwrap();
+    assert!(post.starts_with(
+        "---\ntitle: Baking bread\nexcerpt: Baking sourdough bread at home takes patience, a lively starter and good flour…\n---\n"
+    ));
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Flour.md")).unwrap(),
+        "---\nexcerpt: Flour.\n---\n\nFlour.\n"
+    );
+}
+
+#[test]
+fn test_auto_excerpt_keeps_existing_key() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/excerpt/Described.md"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = auto_excerpt("excerpt".to_owned(), 80);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    assert_eq!(
+        read_to_string(tmp_dir.path().join("Described.md")).unwrap(),
+        read_to_string("tests/testdata/input/excerpt/Described.md").unwrap(),
+    );
+}
+
+#[rstest]
+#[case(
+    FrontmatterListStyle::List,
+    "---\ntitle: Scalar\ntags:\n- foo\naliases:\n- Nickname\n- Other name\ncssclasses:\n- wide\npublish: true\n---\n",
+    "---\ntags:\n- foo\n- bar/baz\naliases:\n- Smith\n- John\npublish: true\n---\n"
+)]
+#[case(
+    FrontmatterListStyle::String,
+    "---\ntitle: Scalar\ntags: foo\naliases: Nickname, Other name\ncssclasses: wide\npublish: true\n---\n",
+    "---\ntags: foo, bar/baz\naliases: Smith, John\npublish: true\n---\n"
+)]
+fn test_obsidian_publish_frontmatter(
+    #[case] list_style: FrontmatterListStyle,
+    #[case] scalar_frontmatter: &str,
+    #[case] list_frontmatter: &str,
+) {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/publish-frontmatter"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = obsidian_publish_frontmatter(list_style);
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    // Scalar values (and the singular keys they were given with) are normalized.
+    let scalar = read_to_string(tmp_dir.path().join("Scalar.md")).unwrap();
+    assert_eq!(
+        scalar,
+        format!("{scalar_frontmatter}\nA note with scalar values.\n")
+    );
+    // Existing lists are preserved as-is when lists are expected.
+    let list = read_to_string(tmp_dir.path().join("List.md")).unwrap();
+    assert_eq!(list, format!("{list_frontmatter}\nA note with lists.\n"));
+}
+
+#[test]
+fn test_sanitize_html() {
+    let tmp_dir = TempDir::new().expect("failed to make tempdir");
+    let mut exporter = Exporter::new(
+        PathBuf::from("tests/testdata/input/sanitize-html"),
+        tmp_dir.path().to_path_buf(),
+    );
+    let postprocessor = sanitize_html(HtmlPolicy::default());
+    exporter.add_postprocessor(&postprocessor);
+    exporter.run().unwrap();
+
+    let actual = read_to_string(tmp_dir.path().join("Note.md")).unwrap();
+    let expected = read_to_string("tests/testdata/expected/sanitize-html/Note.md").unwrap();
+    assert_eq!(expected, actual);
+}